@@ -0,0 +1,667 @@
+// Serializable form of the editor's annotations and a renderer for it. Coordinates are in the
+// scene's own `width` x `height` space (the selection as laid out in the editor) and are scaled
+// onto whatever image the scene is rendered over, so the same scene re-renders at any resolution.
+use crate::redaction::{self, RedactionMode, RedactionRegion};
+use crate::visible_watermark;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use tiny_skia::{
+    Color, FillRule, LineCap, LineJoin, Paint, PathBuilder, Pixmap, PixmapPaint, Rect, Stroke,
+    Transform,
+};
+
+pub const SCENE_VERSION: u32 = 1;
+const MARKER_RADIUS: f32 = 13.0;
+const MARKER_TEXT_COLOR: &str = "#ffffff";
+/// fabric.js's default `lineHeight`.
+const DEFAULT_LINE_HEIGHT: f32 = 1.16;
+const DEFAULT_MOSAIC_BLOCK: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScenePoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Annotation {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        color: String,
+        stroke_width: f32,
+    },
+    Line {
+        from: ScenePoint,
+        to: ScenePoint,
+        color: String,
+        stroke_width: f32,
+    },
+    Arrow {
+        from: ScenePoint,
+        to: ScenePoint,
+        color: String,
+        stroke_width: f32,
+    },
+    Pen {
+        points: Vec<ScenePoint>,
+        color: String,
+        stroke_width: f32,
+    },
+    Marker {
+        center: ScenePoint,
+        number: u32,
+        color: String,
+    },
+    Text {
+        x: f32,
+        y: f32,
+        text: String,
+        color: String,
+        font_size: f32,
+        /// Wrap width; text is laid out on explicit line breaks only when absent.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        width: Option<f32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        background: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        line_height: Option<f32>,
+    },
+    Mosaic {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        block_size: Option<f32>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationScene {
+    pub version: u32,
+    pub width: f32,
+    pub height: f32,
+    pub annotations: Vec<Annotation>,
+}
+
+impl AnnotationScene {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version == 0 || self.version > SCENE_VERSION {
+            return Err(format!(
+                "Unsupported annotation scene version {}",
+                self.version
+            ));
+        }
+        if !(self.width > 0.0 && self.height > 0.0) {
+            return Err("Annotation scene has no size".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Parses the colour strings the editor produces: `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb()` and
+/// `rgba()`.
+pub fn parse_color(value: &str) -> Result<Color, String> {
+    let invalid = || format!("Invalid color {}", value);
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        let expanded = match hex.len() {
+            3 => hex.chars().flat_map(|c| [c, c]).collect::<String>(),
+            6 | 8 => hex.to_string(),
+            _ => return Err(invalid()),
+        };
+        let channel = |index: usize| {
+            expanded
+                .get(index..index + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(invalid)
+        };
+        let alpha = if expanded.len() == 8 {
+            channel(6)?
+        } else {
+            255
+        };
+        return Ok(Color::from_rgba8(
+            channel(0)?,
+            channel(2)?,
+            channel(4)?,
+            alpha,
+        ));
+    }
+    let arguments = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))
+        .and_then(|rest| rest.strip_suffix(')'))
+        .ok_or_else(invalid)?;
+    let parts = arguments
+        .split(',')
+        .map(|part| part.trim().parse::<f32>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let byte = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+    match parts.as_slice() {
+        [red, green, blue] => Ok(Color::from_rgba8(
+            byte(*red),
+            byte(*green),
+            byte(*blue),
+            255,
+        )),
+        [red, green, blue, alpha] => Ok(Color::from_rgba8(
+            byte(*red),
+            byte(*green),
+            byte(*blue),
+            byte(alpha.clamp(0.0, 1.0) * 255.0),
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+fn image_to_pixmap(image: &RgbaImage) -> Result<Pixmap, String> {
+    let mut pixmap = Pixmap::new(image.width(), image.height())
+        .ok_or_else(|| "Image is too large to annotate".to_string())?;
+    for (target, source) in pixmap.pixels_mut().iter_mut().zip(image.pixels()) {
+        let [red, green, blue, alpha] = source.0;
+        *target = tiny_skia::ColorU8::from_rgba(red, green, blue, alpha).premultiply();
+    }
+    Ok(pixmap)
+}
+
+fn pixmap_to_image(pixmap: &Pixmap) -> RgbaImage {
+    let mut image = RgbaImage::new(pixmap.width(), pixmap.height());
+    for (target, source) in image.pixels_mut().zip(pixmap.pixels()) {
+        let color = source.demultiply();
+        target.0 = [color.red(), color.green(), color.blue(), color.alpha()];
+    }
+    image
+}
+
+fn solid_paint(color: Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(color);
+    paint.anti_alias = true;
+    paint
+}
+
+fn round_stroke(width: f32) -> Stroke {
+    Stroke {
+        width: width.max(0.5),
+        line_cap: LineCap::Round,
+        line_join: LineJoin::Round,
+        ..Stroke::default()
+    }
+}
+
+fn stroke_polyline(
+    pixmap: &mut Pixmap,
+    points: &[ScenePoint],
+    color: &str,
+    stroke_width: f32,
+    transform: Transform,
+) -> Result<(), String> {
+    let mut builder = PathBuilder::new();
+    for (index, point) in points.iter().enumerate() {
+        if index == 0 {
+            builder.move_to(point.x, point.y);
+        } else {
+            builder.line_to(point.x, point.y);
+        }
+    }
+    if let [only] = points {
+        // A single click still leaves a dot, like a fabric.js pencil stroke.
+        builder.line_to(only.x + 0.01, only.y);
+    }
+    if let Some(path) = builder.finish() {
+        pixmap.stroke_path(
+            &path,
+            &solid_paint(parse_color(color)?),
+            &round_stroke(stroke_width),
+            transform,
+            None,
+        );
+    }
+    Ok(())
+}
+
+/// Same geometry as `makeArrowPath` in the screenshot window.
+fn arrow_head(from: ScenePoint, to: ScenePoint, stroke_width: f32) -> [ScenePoint; 3] {
+    let angle = (to.y - from.y).atan2(to.x - from.x);
+    let head_length = (stroke_width * 4.0).max(14.0);
+    let head_angle = std::f32::consts::PI / 7.0;
+    let corner = |offset: f32| ScenePoint {
+        x: to.x - head_length * (angle + offset).cos(),
+        y: to.y - head_length * (angle + offset).sin(),
+    };
+    [corner(-head_angle), to, corner(head_angle)]
+}
+
+/// Draws a coverage mask in `color` with its top-left corner at device pixel `(left, top)`.
+fn draw_mask(pixmap: &mut Pixmap, mask: &image::GrayImage, left: f32, top: f32, color: Color) {
+    let Some(mut layer) = Pixmap::new(mask.width(), mask.height()) else {
+        return;
+    };
+    for (target, coverage) in layer.pixels_mut().iter_mut().zip(mask.pixels()) {
+        let alpha = color.alpha() * f32::from(coverage[0]) / 255.0;
+        let mut color = color;
+        color.set_alpha(alpha);
+        *target = color.premultiply().to_color_u8();
+    }
+    pixmap.draw_pixmap(
+        left.round() as i32,
+        top.round() as i32,
+        layer.as_ref(),
+        &PixmapPaint::default(),
+        Transform::identity(),
+        None,
+    );
+}
+
+/// Greedy wrap that breaks between characters, like fabric.js `splitByGrapheme`.
+fn wrap_line(line: &str, font_size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for character in line.chars() {
+        current.push(character);
+        if current.chars().count() > 1
+            && visible_watermark::measure_line(&current, font_size) > width
+        {
+            current.pop();
+            lines.push(std::mem::take(&mut current));
+            current.push(character);
+        }
+    }
+    lines.push(current);
+    lines
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_text(
+    pixmap: &mut Pixmap,
+    scale: (f32, f32),
+    origin: ScenePoint,
+    text: &str,
+    color: &str,
+    font_size: f32,
+    width: Option<f32>,
+    background: Option<&str>,
+    line_height: Option<f32>,
+) -> Result<(), String> {
+    let (scale_x, scale_y) = scale;
+    let device_size = font_size * scale_y;
+    if device_size < 1.0 {
+        return Ok(());
+    }
+    let lines = text
+        .split('\n')
+        .flat_map(|line| match width {
+            Some(width) => wrap_line(line, device_size, width * scale_x),
+            None => vec![line.to_string()],
+        })
+        .collect::<Vec<_>>();
+    let line_advance = device_size * line_height.unwrap_or(DEFAULT_LINE_HEIGHT);
+    let left = origin.x * scale_x;
+    let top = origin.y * scale_y;
+
+    if let Some(background) = background {
+        let box_width = width.map(|width| width * scale_x).unwrap_or_else(|| {
+            lines
+                .iter()
+                .map(|line| visible_watermark::measure_line(line, device_size))
+                .fold(0.0, f32::max)
+        });
+        if let Some(rect) = Rect::from_xywh(left, top, box_width, line_advance * lines.len() as f32)
+        {
+            pixmap.fill_rect(
+                rect,
+                &solid_paint(parse_color(background)?),
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+    let color = parse_color(color)?;
+    for (index, line) in lines.iter().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let mask = visible_watermark::render_line(line, device_size);
+        // fabric.js centres each glyph line inside its line box.
+        let offset = (line_advance - mask.height() as f32) / 2.0;
+        draw_mask(
+            pixmap,
+            &mask,
+            left,
+            top + index as f32 * line_advance + offset,
+            color,
+        );
+    }
+    Ok(())
+}
+
+fn draw_marker(
+    pixmap: &mut Pixmap,
+    scale: (f32, f32),
+    center: ScenePoint,
+    number: u32,
+    color: &str,
+) -> Result<(), String> {
+    let (scale_x, scale_y) = scale;
+    let radius = MARKER_RADIUS * (scale_x + scale_y) / 2.0;
+    let (x, y) = (center.x * scale_x, center.y * scale_y);
+    if let Some(circle) = PathBuilder::from_circle(x, y, radius) {
+        pixmap.fill_path(
+            &circle,
+            &solid_paint(parse_color(color)?),
+            FillRule::Winding,
+            Transform::identity(),
+            None,
+        );
+    }
+    let font_size = if number > 99 { 10.0 } else { 14.0 } * scale_y;
+    let mask = visible_watermark::render_line(&number.to_string(), font_size);
+    draw_mask(
+        pixmap,
+        &mask,
+        x - mask.width() as f32 / 2.0,
+        y - mask.height() as f32 / 2.0,
+        parse_color(MARKER_TEXT_COLOR)?,
+    );
+    Ok(())
+}
+
+fn draw_mosaic(
+    pixmap: &mut Pixmap,
+    scale: (f32, f32),
+    origin: ScenePoint,
+    size: (f32, f32),
+    block_size: Option<f32>,
+) -> Result<(), String> {
+    let (scale_x, scale_y) = scale;
+    let region = RedactionRegion::covering(
+        f64::from(origin.x * scale_x),
+        f64::from(origin.y * scale_y),
+        f64::from(size.0 * scale_x),
+        f64::from(size.1 * scale_y),
+    );
+    let block = block_size.unwrap_or(DEFAULT_MOSAIC_BLOCK) * (scale_x + scale_y) / 2.0;
+    let mut image = pixmap_to_image(pixmap);
+    redaction::redact(
+        &mut image,
+        &[region],
+        RedactionMode::Pixelate,
+        Some(block.round().max(1.0) as u32),
+        None,
+    )?;
+    *pixmap = image_to_pixmap(&image)?;
+    Ok(())
+}
+
+/// Draws every annotation over `image` in order, scaling the scene to the image's size.
+pub fn render(image: &RgbaImage, scene: &AnnotationScene) -> Result<RgbaImage, String> {
+    scene.validate()?;
+    let scale = (
+        image.width() as f32 / scene.width,
+        image.height() as f32 / scene.height,
+    );
+    let transform = Transform::from_scale(scale.0, scale.1);
+    let mut pixmap = image_to_pixmap(image)?;
+
+    for annotation in &scene.annotations {
+        match annotation {
+            Annotation::Rect {
+                x,
+                y,
+                width,
+                height,
+                color,
+                stroke_width,
+            } => {
+                if let Some(rect) = Rect::from_xywh(*x, *y, *width, *height) {
+                    let path = PathBuilder::from_rect(rect);
+                    let stroke = Stroke {
+                        width: stroke_width.max(0.5),
+                        line_join: LineJoin::Miter,
+                        ..Stroke::default()
+                    };
+                    pixmap.stroke_path(
+                        &path,
+                        &solid_paint(parse_color(color)?),
+                        &stroke,
+                        transform,
+                        None,
+                    );
+                }
+            }
+            Annotation::Line {
+                from,
+                to,
+                color,
+                stroke_width,
+            } => stroke_polyline(&mut pixmap, &[*from, *to], color, *stroke_width, transform)?,
+            Annotation::Arrow {
+                from,
+                to,
+                color,
+                stroke_width,
+            } => {
+                stroke_polyline(&mut pixmap, &[*from, *to], color, *stroke_width, transform)?;
+                let head = arrow_head(*from, *to, *stroke_width);
+                stroke_polyline(&mut pixmap, &head, color, *stroke_width, transform)?;
+            }
+            Annotation::Pen {
+                points,
+                color,
+                stroke_width,
+            } => stroke_polyline(&mut pixmap, points, color, *stroke_width, transform)?,
+            Annotation::Marker {
+                center,
+                number,
+                color,
+            } => draw_marker(&mut pixmap, scale, *center, *number, color)?,
+            Annotation::Text {
+                x,
+                y,
+                text,
+                color,
+                font_size,
+                width,
+                background,
+                line_height,
+            } => draw_text(
+                &mut pixmap,
+                scale,
+                ScenePoint { x: *x, y: *y },
+                text,
+                color,
+                *font_size,
+                *width,
+                background.as_deref(),
+                *line_height,
+            )?,
+            Annotation::Mosaic {
+                x,
+                y,
+                width,
+                height,
+                block_size,
+            } => draw_mosaic(
+                &mut pixmap,
+                scale,
+                ScenePoint { x: *x, y: *y },
+                (*width, *height),
+                *block_size,
+            )?,
+        }
+    }
+    Ok(pixmap_to_image(&pixmap))
+}
+
+#[tauri::command]
+pub async fn render_annotation_scene(
+    blob_data: Vec<u8>,
+    scene: AnnotationScene,
+) -> Result<tauri::ipc::Response, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let image = image::load_from_memory(&blob_data)
+            .map_err(|e| format!("Failed to decode image: {}", e))?
+            .to_rgba8();
+        let rendered = render(&image, &scene)?;
+        crate::encode_rgba_png_fast(&rendered).map(tauri::ipc::Response::new)
+    })
+    .await
+    .map_err(|e| format!("Annotation task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn scene(version: u32, annotations: Vec<Annotation>) -> AnnotationScene {
+        AnnotationScene {
+            version,
+            width: 60.0,
+            height: 40.0,
+            annotations,
+        }
+    }
+
+    fn checkerboard() -> RgbaImage {
+        RgbaImage::from_fn(120, 80, |x, y| {
+            if (x + y) % 2 == 0 {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        })
+    }
+
+    #[test]
+    fn annotations_round_trip_through_json() {
+        let point = |x, y| ScenePoint { x, y };
+        let scene = scene(
+            SCENE_VERSION,
+            vec![
+                Annotation::Rect {
+                    x: 1.0,
+                    y: 2.0,
+                    width: 3.0,
+                    height: 4.0,
+                    color: "#ff0000".into(),
+                    stroke_width: 2.0,
+                },
+                Annotation::Arrow {
+                    from: point(0.0, 0.0),
+                    to: point(10.0, 5.5),
+                    color: "rgb(0, 128, 255)".into(),
+                    stroke_width: 3.0,
+                },
+                Annotation::Pen {
+                    points: vec![point(1.0, 1.0), point(2.0, 3.0)],
+                    color: "#000".into(),
+                    stroke_width: 1.5,
+                },
+                Annotation::Marker {
+                    center: point(20.0, 20.0),
+                    number: 3,
+                    color: "#00ff00".into(),
+                },
+                Annotation::Text {
+                    x: 5.0,
+                    y: 6.0,
+                    text: "hello\nworld".into(),
+                    color: "#123456".into(),
+                    font_size: 18.0,
+                    width: None,
+                    background: Some("rgba(0, 0, 0, 0.5)".into()),
+                    line_height: None,
+                },
+                Annotation::Mosaic {
+                    x: 7.0,
+                    y: 8.0,
+                    width: 9.0,
+                    height: 10.0,
+                    block_size: Some(12.0),
+                },
+            ],
+        );
+        let json = serde_json::to_string(&scene).unwrap();
+        assert!(json.contains(r#""type":"arrow""#));
+        assert!(json.contains(r#""strokeWidth":3.0"#));
+        assert!(json.contains(r#""blockSize":12.0"#));
+        assert!(!json.contains("lineHeight"));
+        assert_eq!(
+            serde_json::from_str::<AnnotationScene>(&json).unwrap(),
+            scene
+        );
+
+        let editor = r##"{"type":"text","x":1,"y":2,"text":"a","color":"#fff","fontSize":12}"##;
+        assert!(matches!(
+            serde_json::from_str::<Annotation>(editor).unwrap(),
+            Annotation::Text {
+                width: None,
+                background: None,
+                ..
+            }
+        ));
+        assert!(serde_json::from_str::<Annotation>(r#"{"type":"circle"}"#).is_err());
+    }
+
+    #[test]
+    fn parses_editor_colors() {
+        let rgba = |value: &str| parse_color(value).map(|color| color.to_color_u8());
+        let expected = |r, g, b, a| Ok(tiny_skia::ColorU8::from_rgba(r, g, b, a));
+        assert_eq!(rgba("#fa0"), expected(0xff, 0xaa, 0x00, 255));
+        assert_eq!(rgba("#336699"), expected(0x33, 0x66, 0x99, 255));
+        assert_eq!(rgba("#33669980"), expected(0x33, 0x66, 0x99, 0x80));
+        assert_eq!(rgba(" rgb(1, 2, 300) "), expected(1, 2, 255, 255));
+        assert_eq!(rgba("rgba(10,20,30,0.5)"), expected(10, 20, 30, 128));
+        for invalid in ["#12", "#12345g", "rgb(1, 2)", "rgba(1, 2, x, 1)", "red", ""] {
+            assert!(parse_color(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn mosaic_leaves_no_original_pixels() {
+        let image = checkerboard();
+        // Scaled by 2, the mosaic spans device x 20.6..61.2 and y 11.4..42.
+        let mosaic = Annotation::Mosaic {
+            x: 10.3,
+            y: 5.7,
+            width: 20.3,
+            height: 15.3,
+            block_size: Some(10.0),
+        };
+        let rendered = render(&image, &scene(SCENE_VERSION, vec![mosaic])).unwrap();
+        for (x, y, pixel) in rendered.enumerate_pixels() {
+            let covered = (20..62).contains(&x) && (11..42).contains(&y);
+            if covered {
+                assert!(
+                    (100..=155).contains(&pixel[0]),
+                    "pixel {x},{y} kept {pixel:?}"
+                );
+            } else {
+                assert_eq!(pixel, image.get_pixel(x, y), "pixel {x},{y}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_versions() {
+        let image = checkerboard();
+        for version in [0, SCENE_VERSION + 1] {
+            let error = render(&image, &scene(version, Vec::new())).unwrap_err();
+            assert!(error.contains("Unsupported"), "{error}");
+        }
+        let mut empty = scene(SCENE_VERSION, Vec::new());
+        empty.width = 0.0;
+        assert!(render(&image, &empty).is_err());
+    }
+}
@@ -0,0 +1,494 @@
+use crate::annotation_scene::{self, AnnotationScene};
+use crate::ocr::{self, OcrScanResult};
+use crate::ocr_export::{self, OcrExportFormat};
+use crate::pdf_export::{self, PageSize, PdfOptions};
+use crate::project;
+use crate::visible_watermark::{self, LogoOptions, Placement, QrOptions, VisibleWatermarkOptions};
+use crate::watermark;
+use crate::watermark_scan::{self, ReportFormat};
+use crate::watermark_signing::{self, SignatureScheme, SigningKeys, Verifier};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+const USAGE: &str = "Usage:
+  xshot scan-watermarks <directory> [--report <file>] [--format json|csv] [--no-recursive]
+                        [--deep] [--hmac-secret-file <file>] [--trusted-key <base64 public key>]...
+
+  xshot watermark <input> <output> [--text <template>] [--placement <placement>]
+                  [--opacity <0.08-0.35>] [--rotation <degrees>] [--logo <file>]
+                  [--logo-scale <fraction>] [--logo-opacity <0-1>] [--logo-placement <placement>]
+                  [--qr <capture id>] [--hidden <text>] [--sign ed25519|hmac]
+                  [--signing-keys <file>]
+
+  xshot render <image> <scene.json> <output>
+
+  xshot export <project.xshot> <output> [--signing-keys <file>]
+
+  xshot pdf <output.pdf> <image>... [--page a4|letter|fit] [--landscape] [--margin <mm>] [--ocr]
+
+  xshot ocr <image|project.xshot|result.json> [--format markdown|html|hocr|alto|json]
+            [--output <file>]
+
+Without --report the report is printed to stdout. Progress goes to stderr.
+--deep also finds watermarks in rescaled or cropped copies, at a much slower pace.
+Signatures are checked against the given Ed25519 public keys and the HMAC secret read from
+--hmac-secret-file, or from the XSHOT_HMAC_SECRET environment variable without it.
+
+Watermark text may use {user}, {date} and {hostname}; use \\n for a line break. Placements are
+repeat-diagonal, repeat-horizontal, top-left, top-right, bottom-left and bottom-right.
+--sign signs the hidden watermark with this user's keys from the app, or with --signing-keys.
+
+render draws an annotation scene over the image, scaled from the scene's size to the image's.
+export renders a saved project: cropped, annotated and watermarked like the editor's export,
+signing the hidden watermark when the project asks for it.
+pdf combines images into one PDF, splitting long screenshots across pages; --ocr adds a
+searchable text layer (macOS only).
+ocr writes recognized text in reading order with its layout. Images are recognized on macOS only;
+projects and saved OCR results work anywhere. Without --output the document goes to stdout, and
+without --format the format follows the output extension (Markdown by default).";
+
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::Failed(message)
+    }
+}
+
+/// Runs a subcommand when the first argument names one. Returns `None` for anything else so a
+/// normal launch (including OS-provided arguments) still starts the app.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    let command: fn(&[String]) -> Result<(), CliError> = match command.as_str() {
+        "scan-watermarks" => scan_watermarks,
+        "watermark" => apply_watermark,
+        "render" => render_scene,
+        "export" => export_project,
+        "pdf" => export_pdf,
+        "ocr" => export_ocr,
+        "help" | "--help" | "-h" => print_usage,
+        _ => return None,
+    };
+    attach_parent_console();
+
+    Some(match command(rest) {
+        Ok(()) => 0,
+        Err(CliError::Usage(message)) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            2
+        }
+        Err(CliError::Failed(message)) => {
+            eprintln!("{}", message);
+            1
+        }
+    })
+}
+
+/// Release builds use the Windows GUI subsystem, so a CLI run has no console to print to until
+/// it attaches to the one of the shell that started it. Redirected output already has handles
+/// and is left alone.
+#[cfg(target_os = "windows")]
+fn attach_parent_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_parent_console() {}
+
+fn print_usage(_args: &[String]) -> Result<(), CliError> {
+    println!("{}", USAGE);
+    Ok(())
+}
+
+fn usage(message: impl Into<String>) -> CliError {
+    CliError::Usage(message.into())
+}
+
+fn scan_watermarks(args: &[String]) -> Result<(), CliError> {
+    let mut directory = None;
+    let mut report_path = None;
+    let mut format = None;
+    let mut recursive = true;
+    let mut deep = false;
+    let mut hmac_secret = None;
+    let mut trusted_keys = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--report" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| usage("--report needs a file path"))?;
+                report_path = Some(PathBuf::from(value));
+            }
+            "--format" => {
+                format = Some(match args.next().map(String::as_str) {
+                    Some("json") => ReportFormat::Json,
+                    Some("csv") => ReportFormat::Csv,
+                    _ => return Err(usage("--format must be json or csv")),
+                });
+            }
+            "--no-recursive" => recursive = false,
+            "--deep" => deep = true,
+            "--hmac-secret-file" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| usage("--hmac-secret-file needs a file path"))?;
+                let secret = std::fs::read_to_string(value)
+                    .map_err(|e| format!("Failed to read HMAC secret {}: {}", value, e))?;
+                hmac_secret = Some(secret.trim_end_matches(['\r', '\n']).to_string());
+            }
+            "--trusted-key" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| usage("--trusted-key needs a public key"))?;
+                trusted_keys.push(value.clone());
+            }
+            flag if flag.starts_with("--") => {
+                return Err(usage(format!("Unknown option {}", flag)));
+            }
+            path if directory.is_none() => directory = Some(PathBuf::from(path)),
+            extra => return Err(usage(format!("Unexpected argument {}", extra))),
+        }
+    }
+    let directory = directory.ok_or_else(|| usage("Missing directory"))?;
+    let format = format.unwrap_or_else(|| {
+        report_path
+            .as_deref()
+            .map(ReportFormat::from_path)
+            .unwrap_or_default()
+    });
+
+    // Kept off the command line so the secret does not show up in process listings.
+    let hmac_secret = hmac_secret.or_else(|| std::env::var("XSHOT_HMAC_SECRET").ok());
+    let verifier = Verifier::new(hmac_secret.as_deref(), &trusted_keys).map_err(usage)?;
+    let never_cancelled = AtomicBool::new(false);
+    let report = watermark_scan::scan_directory(
+        &directory,
+        recursive,
+        deep,
+        &verifier,
+        &never_cancelled,
+        |progress| {
+            eprintln!(
+                "[{}/{}] found={} {}",
+                progress.processed, progress.total, progress.found, progress.path
+            );
+        },
+    )?;
+
+    match report_path.as_deref() {
+        Some(path) => {
+            watermark_scan::write_report(&report, path, format)?;
+            eprintln!("Report written to {}", path.display());
+        }
+        None => print!("{}", watermark_scan::render_report(&report, format)?),
+    }
+    Ok(())
+}
+
+fn parse_number(flag: &str, value: Option<&String>) -> Result<f32, CliError> {
+    value
+        .and_then(|value| value.parse::<f32>().ok())
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| usage(format!("{} needs a number", flag)))
+}
+
+fn parse_placement(flag: &str, value: Option<&String>) -> Result<Placement, CliError> {
+    value
+        .and_then(|value| Placement::parse(value))
+        .ok_or_else(|| usage(format!("{} needs a placement", flag)))
+}
+
+fn parse_path(flag: &str, value: Option<&String>) -> Result<PathBuf, CliError> {
+    value
+        .map(PathBuf::from)
+        .ok_or_else(|| usage(format!("{} needs a file path", flag)))
+}
+
+/// This user's signing keys from `path`, or from the app's own signing file.
+fn signing_keys(path: Option<PathBuf>) -> Result<SigningKeys, CliError> {
+    let path = path
+        .or_else(watermark_signing::default_signing_file_path)
+        .ok_or_else(|| usage("Cannot locate the app's signing keys; pass --signing-keys"))?;
+    Ok(watermark_signing::signing_keys_at(&path)?)
+}
+
+fn apply_watermark(args: &[String]) -> Result<(), CliError> {
+    let mut paths = Vec::new();
+    let mut options = VisibleWatermarkOptions::default();
+    let mut logo = LogoOptions::default();
+    let mut capture_id = None;
+    let mut hidden_text = None;
+    let mut signature = None;
+    let mut signing_keys_path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--text" => {
+                let value = args.next().ok_or_else(|| usage("--text needs a value"))?;
+                options.text = value.replace("\\n", "\n");
+            }
+            "--placement" => options.placement = parse_placement(arg, args.next())?,
+            "--opacity" => options.opacity = parse_number(arg, args.next())?,
+            "--rotation" => options.rotation = Some(parse_number(arg, args.next())?),
+            "--logo" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| usage("--logo needs a file path"))?;
+                logo.path = value.clone();
+            }
+            "--logo-scale" => logo.scale = parse_number(arg, args.next())?,
+            "--logo-opacity" => logo.opacity = parse_number(arg, args.next())?,
+            "--logo-placement" => logo.placement = parse_placement(arg, args.next())?,
+            "--qr" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| usage("--qr needs a capture ID"))?;
+                capture_id = Some(value.clone());
+                options.qr = Some(QrOptions::default());
+            }
+            "--hidden" => {
+                let value = args.next().ok_or_else(|| usage("--hidden needs a value"))?;
+                hidden_text = Some(value.clone());
+            }
+            "--sign" => {
+                signature = Some(
+                    args.next()
+                        .and_then(|value| SignatureScheme::parse(value))
+                        .ok_or_else(|| usage("--sign must be ed25519 or hmac"))?,
+                );
+            }
+            "--signing-keys" => signing_keys_path = Some(parse_path(arg, args.next())?),
+            flag if flag.starts_with("--") => {
+                return Err(usage(format!("Unknown option {}", flag)));
+            }
+            path if paths.len() < 2 => paths.push(PathBuf::from(path)),
+            extra => return Err(usage(format!("Unexpected argument {}", extra))),
+        }
+    }
+    let [input, output] =
+        <[PathBuf; 2]>::try_from(paths).map_err(|_| usage("Missing input or output path"))?;
+    if !logo.path.is_empty() {
+        options.logo = Some(logo);
+    }
+    if signature.is_some() && hidden_text.is_none() {
+        return Err(usage("--sign needs --hidden"));
+    }
+    let keys = signature
+        .map(|_| signing_keys(signing_keys_path))
+        .transpose()?;
+
+    let mut image = image::open(&input)
+        .map_err(|e| format!("Failed to open {}: {}", input.display(), e))?
+        .to_rgba8();
+    let now = crate::unix_epoch_ms();
+    let drawn = visible_watermark::apply(&mut image, &options, capture_id.as_deref(), now)?;
+    if let Some(text) = hidden_text {
+        if !watermark::embed(&mut image, &text, now, keys.as_ref().zip(signature))? {
+            return Err(CliError::Failed(
+                "Image is too small to hold the hidden watermark".to_string(),
+            ));
+        }
+    } else if !drawn {
+        return Err(usage(
+            "Nothing to draw; pass --text, --logo, --qr or --hidden",
+        ));
+    }
+    save_image(image, &output)?;
+    eprintln!("Watermarked image written to {}", output.display());
+    Ok(())
+}
+
+fn save_image(image: image::RgbaImage, output: &Path) -> Result<(), CliError> {
+    let is_jpeg = output
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| matches!(extension.to_ascii_lowercase().as_str(), "jpg" | "jpeg"));
+    // JPEG has no alpha channel, so the encoder rejects RGBA input.
+    let saved = if is_jpeg {
+        image::DynamicImage::ImageRgba8(image)
+            .to_rgb8()
+            .save(output)
+    } else {
+        image.save(output)
+    };
+    saved.map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+    Ok(())
+}
+
+fn render_scene(args: &[String]) -> Result<(), CliError> {
+    if let Some(flag) = args.iter().find(|arg| arg.starts_with("--")) {
+        return Err(usage(format!("Unknown option {}", flag)));
+    }
+    let [input, scene_path, output] = <[&String; 3]>::try_from(args.iter().collect::<Vec<_>>())
+        .map_err(|_| usage("render needs an image, a scene and an output path"))?;
+    let image = image::open(input)
+        .map_err(|e| format!("Failed to open {}: {}", input, e))?
+        .to_rgba8();
+    let scene_json = std::fs::read_to_string(scene_path)
+        .map_err(|e| format!("Failed to read {}: {}", scene_path, e))?;
+    let scene = serde_json::from_str::<AnnotationScene>(&scene_json)
+        .map_err(|e| format!("Failed to parse {}: {}", scene_path, e))?;
+    let rendered = annotation_scene::render(&image, &scene)?;
+    save_image(rendered, Path::new(output))?;
+    eprintln!("Annotated image written to {}", output);
+    Ok(())
+}
+
+fn export_project(args: &[String]) -> Result<(), CliError> {
+    let mut paths = Vec::new();
+    let mut signing_keys_path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--signing-keys" => signing_keys_path = Some(parse_path(arg, args.next())?),
+            flag if flag.starts_with("--") => {
+                return Err(usage(format!("Unknown option {}", flag)));
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let [input, output] = <[&String; 2]>::try_from(paths)
+        .map_err(|_| usage("export needs a project and an output path"))?;
+    let project = project::load(Path::new(input))?;
+    let keys = project
+        .signature()
+        .map(|_| signing_keys(signing_keys_path))
+        .transpose()?;
+    let image = project.export(keys.as_ref())?;
+    save_image(image, Path::new(output))?;
+    eprintln!("Project exported to {}", output);
+    Ok(())
+}
+
+fn export_pdf(args: &[String]) -> Result<(), CliError> {
+    let mut paths = Vec::new();
+    let mut options = PdfOptions::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--page" => {
+                options.page_size = args
+                    .next()
+                    .and_then(|value| PageSize::parse(value))
+                    .ok_or_else(|| usage("--page must be a4, letter or fit"))?;
+            }
+            "--landscape" => options.landscape = true,
+            "--margin" => options.margin = parse_number(arg, args.next())?.max(0.0),
+            "--ocr" => options.text_layer = true,
+            flag if flag.starts_with("--") => {
+                return Err(usage(format!("Unknown option {}", flag)));
+            }
+            path => paths.push(PathBuf::from(path)),
+        }
+    }
+    if paths.len() < 2 {
+        return Err(usage("pdf needs an output path and at least one image"));
+    }
+    let output = paths.remove(0);
+    let images = paths
+        .iter()
+        .map(|path| {
+            image::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let summary = pdf_export::export(&images, &output, &options)?;
+    eprintln!(
+        "PDF written to {} ({} pages, {} text blocks)",
+        summary.path, summary.pages, summary.text_blocks
+    );
+    Ok(())
+}
+
+fn load_ocr_result(input: &Path) -> Result<OcrScanResult, CliError> {
+    let extension = input
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+    let parse = |value: serde_json::Value| {
+        serde_json::from_value::<OcrScanResult>(value)
+            .map_err(|e| format!("Failed to parse OCR result in {}: {}", input.display(), e))
+    };
+    match extension.as_deref() {
+        Some("json") => {
+            let json = std::fs::read_to_string(input)
+                .map_err(|e| format!("Failed to read {}: {}", input.display(), e))?;
+            let value = serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse {}: {}", input.display(), e))?;
+            Ok(parse(value)?)
+        }
+        Some(project::PROJECT_EXTENSION) => {
+            let value = project::load(input)?.document.ocr.ok_or_else(|| {
+                format!(
+                    "{} has no OCR result; run OCR in the editor first",
+                    input.display()
+                )
+            })?;
+            Ok(parse(value)?)
+        }
+        _ => {
+            let bytes = std::fs::read(input)
+                .map_err(|e| format!("Failed to read {}: {}", input.display(), e))?;
+            Ok(ocr::recognize(bytes)?)
+        }
+    }
+}
+
+fn export_ocr(args: &[String]) -> Result<(), CliError> {
+    let mut input = None;
+    let mut output = None;
+    let mut format = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = Some(
+                    args.next()
+                        .and_then(|value| OcrExportFormat::parse(value))
+                        .ok_or_else(|| {
+                            usage("--format must be markdown, html, hocr, alto or json")
+                        })?,
+                );
+            }
+            "--output" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| usage("--output needs a file path"))?;
+                output = Some(PathBuf::from(value));
+            }
+            flag if flag.starts_with("--") => {
+                return Err(usage(format!("Unknown option {}", flag)));
+            }
+            path if input.is_none() => input = Some(PathBuf::from(path)),
+            extra => return Err(usage(format!("Unexpected argument {}", extra))),
+        }
+    }
+    let input = input.ok_or_else(|| usage("Missing input path"))?;
+    let format = format
+        .or_else(|| output.as_deref().and_then(OcrExportFormat::from_path))
+        .unwrap_or(OcrExportFormat::Markdown);
+
+    let result = load_ocr_result(&input)?;
+    let document = ocr_export::render(&result, format, ocr_export::document_title(&input))?;
+    match output {
+        Some(output) => {
+            std::fs::write(&output, document)
+                .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+            eprintln!("OCR document written to {}", output.display());
+        }
+        None => print!("{}", document),
+    }
+    Ok(())
+}
@@ -0,0 +1,421 @@
+use base64::Engine;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// Extra representations written next to the bitmap.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClipboardFormats {
+    png: bool,
+    file: bool,
+    html: bool,
+    text: Option<String>,
+}
+
+struct ClipboardPayload {
+    image: image::RgbaImage,
+    png: Option<Vec<u8>>,
+    file_path: Option<PathBuf>,
+    html: Option<String>,
+    text: Option<String>,
+}
+
+pub fn clipboard_temp_dir() -> PathBuf {
+    std::env::temp_dir().join("xshot-clipboard")
+}
+
+fn encode_png_bytes(image: &image::RgbaImage) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    image
+        .write_to(
+            &mut std::io::Cursor::new(&mut bytes),
+            image::ImageFormat::Png,
+        )
+        .map_err(|e| format!("Failed to encode clipboard PNG: {}", e))?;
+    Ok(bytes)
+}
+
+fn write_clipboard_temp_file(png: &[u8]) -> Result<PathBuf, String> {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let dir = clipboard_temp_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to prepare clipboard directory: {}", e))?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_millis();
+    let path = dir.join(format!("xshot-{}.png", timestamp));
+    std::fs::write(&path, png).map_err(|e| format!("Failed to write clipboard file: {}", e))?;
+    Ok(path)
+}
+
+fn prepare_payload(
+    blob_data: &[u8],
+    formats: ClipboardFormats,
+) -> Result<ClipboardPayload, String> {
+    let image = image::load_from_memory(blob_data)
+        .map_err(|e| format!("Failed to decode image: {}", e))?
+        .to_rgba8();
+
+    let needs_png = formats.png || formats.file || formats.html;
+    let png = if !needs_png {
+        None
+    } else if image::guess_format(blob_data).ok() == Some(image::ImageFormat::Png) {
+        Some(blob_data.to_vec())
+    } else {
+        Some(encode_png_bytes(&image)?)
+    };
+
+    let file_path = match png.as_deref() {
+        Some(png) if formats.file => Some(write_clipboard_temp_file(png)?),
+        _ => None,
+    };
+    let html = match png.as_deref() {
+        Some(png) if formats.html => Some(format!(
+            "<img src=\"data:image/png;base64,{}\" width=\"{}\" height=\"{}\" alt=\"xshot\">",
+            base64::engine::general_purpose::STANDARD.encode(png),
+            image.width(),
+            image.height(),
+        )),
+        _ => None,
+    };
+
+    Ok(ClipboardPayload {
+        image,
+        png: png.filter(|_| formats.png),
+        file_path,
+        html,
+        text: formats.text.filter(|text| !text.trim().is_empty()),
+    })
+}
+
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let encoded = path
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' | b':' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect::<String>();
+
+    if encoded.starts_with('/') {
+        format!("file://{}", encoded)
+    } else {
+        format!("file:///{}", encoded)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn write_payload(_app: &AppHandle, payload: ClipboardPayload) -> Result<(), String> {
+    use objc2::rc::autoreleasepool;
+    use objc2_app_kit::{
+        NSPasteboard, NSPasteboardTypeFileURL, NSPasteboardTypeHTML, NSPasteboardTypePNG,
+        NSPasteboardTypeString, NSPasteboardTypeTIFF,
+    };
+    use objc2_foundation::{NSArray, NSData, NSString};
+
+    let mut tiff = Vec::new();
+    payload
+        .image
+        .write_to(
+            &mut std::io::Cursor::new(&mut tiff),
+            image::ImageFormat::Tiff,
+        )
+        .map_err(|e| format!("Failed to encode clipboard TIFF: {}", e))?;
+
+    autoreleasepool(|_| {
+        let pasteboard = NSPasteboard::generalPasteboard();
+        let mut types = vec![unsafe { NSPasteboardTypeTIFF }];
+        if payload.png.is_some() {
+            types.push(unsafe { NSPasteboardTypePNG });
+        }
+        if payload.file_path.is_some() {
+            types.push(unsafe { NSPasteboardTypeFileURL });
+        }
+        if payload.html.is_some() {
+            types.push(unsafe { NSPasteboardTypeHTML });
+        }
+        if payload.text.is_some() {
+            types.push(unsafe { NSPasteboardTypeString });
+        }
+
+        pasteboard.clearContents();
+        unsafe { pasteboard.declareTypes_owner(&NSArray::from_slice(&types), None) };
+
+        let mut written = pasteboard.setData_forType(Some(&NSData::with_bytes(&tiff)), unsafe {
+            NSPasteboardTypeTIFF
+        });
+        if let Some(png) = payload.png.as_deref() {
+            written &= pasteboard.setData_forType(Some(&NSData::with_bytes(png)), unsafe {
+                NSPasteboardTypePNG
+            });
+        }
+        if let Some(path) = payload.file_path.as_deref() {
+            written &= pasteboard.setString_forType(&NSString::from_str(&file_uri(path)), unsafe {
+                NSPasteboardTypeFileURL
+            });
+        }
+        if let Some(html) = payload.html.as_deref() {
+            written &= pasteboard
+                .setString_forType(&NSString::from_str(html), unsafe { NSPasteboardTypeHTML });
+        }
+        if let Some(text) = payload.text.as_deref() {
+            written &= pasteboard
+                .setString_forType(&NSString::from_str(text), unsafe { NSPasteboardTypeString });
+        }
+
+        if written {
+            Ok(())
+        } else {
+            Err("Failed to write to clipboard".to_string())
+        }
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn write_payload(_app: &AppHandle, payload: ClipboardPayload) -> Result<(), String> {
+    use clipboard_win::options::NoClear;
+    use clipboard_win::{raw, register_format, Clipboard};
+
+    // CF_BITMAP only carries RGB reliably, so transparency travels in the "PNG" format.
+    let mut bitmap = Vec::new();
+    image::DynamicImage::ImageRgba8(payload.image)
+        .to_rgb8()
+        .write_to(
+            &mut std::io::Cursor::new(&mut bitmap),
+            image::ImageFormat::Bmp,
+        )
+        .map_err(|e| format!("Failed to encode clipboard bitmap: {}", e))?;
+
+    let _clipboard =
+        Clipboard::new_attempts(10).map_err(|e| format!("Failed to open clipboard: {}", e))?;
+    raw::empty().map_err(|e| format!("Failed to clear clipboard: {}", e))?;
+    raw::set_bitmap_with(&bitmap, NoClear)
+        .map_err(|e| format!("Failed to write to clipboard: {}", e))?;
+
+    if let Some(png) = payload.png.as_deref() {
+        let format = register_format("PNG").ok_or("Failed to register PNG clipboard format")?;
+        raw::set_without_clear(format.get(), png)
+            .map_err(|e| format!("Failed to write PNG to clipboard: {}", e))?;
+    }
+    if let Some(path) = payload.file_path.as_deref() {
+        raw::set_file_list_with(&[path.to_string_lossy()], NoClear)
+            .map_err(|e| format!("Failed to write file to clipboard: {}", e))?;
+    }
+    if let Some(html) = payload.html.as_deref() {
+        let format =
+            register_format("HTML Format").ok_or("Failed to register HTML clipboard format")?;
+        raw::set_html_with(format.get(), html, NoClear)
+            .map_err(|e| format!("Failed to write HTML to clipboard: {}", e))?;
+    }
+    if let Some(text) = payload.text.as_deref() {
+        raw::set_string_with(text, NoClear)
+            .map_err(|e| format!("Failed to write text to clipboard: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Selection targets and the bytes served for each. The bitmap goes out as PNG, the only image
+/// target X11 and Wayland applications agree on.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn selection_targets(payload: ClipboardPayload) -> Result<Vec<(&'static str, Vec<u8>)>, String> {
+    let png = match payload.png {
+        Some(png) => png,
+        None => encode_png_bytes(&payload.image)?,
+    };
+    let mut targets = vec![("image/png", png)];
+    if let Some(path) = payload.file_path.as_deref() {
+        let uri = file_uri(path);
+        targets.push(("text/uri-list", format!("{}\r\n", uri).into_bytes()));
+        targets.push((
+            "x-special/gnome-copied-files",
+            format!("copy\n{}", uri).into_bytes(),
+        ));
+    }
+    if let Some(html) = payload.html {
+        targets.push(("text/html", html.into_bytes()));
+    }
+    if let Some(text) = payload.text {
+        for target in ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"] {
+            targets.push((target, text.clone().into_bytes()));
+        }
+    }
+    Ok(targets)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn write_payload(app: &AppHandle, payload: ClipboardPayload) -> Result<(), String> {
+    use gtk::{gdk, TargetEntry, TargetFlags};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let targets = selection_targets(payload)?;
+    let (sender, receiver) = mpsc::channel();
+    app.run_on_main_thread(move || {
+        let entries = targets
+            .iter()
+            .enumerate()
+            .map(|(index, (target, _))| {
+                TargetEntry::new(target, TargetFlags::empty(), index as u32)
+            })
+            .collect::<Vec<_>>();
+        let clipboard = gtk::Clipboard::get(&gdk::SELECTION_CLIPBOARD);
+        // The clipboard keeps the closure, and with it the data, until another owner takes over.
+        let written = clipboard.set_with_data(&entries, move |_, selection, info| {
+            if let Some((_, data)) = targets.get(info as usize) {
+                selection.set(&selection.target(), 8, data);
+            }
+        });
+        let _ = sender.send(written);
+    })
+    .map_err(|e| e.to_string())?;
+
+    match receiver.recv_timeout(Duration::from_millis(500)) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Failed to write to clipboard".to_string()),
+        Err(_) => Err("Timed out while writing to clipboard".to_string()),
+    }
+}
+
+pub fn write_image_formats(
+    app: &AppHandle,
+    blob_data: &[u8],
+    formats: ClipboardFormats,
+) -> Result<(), String> {
+    let payload = prepare_payload(blob_data, formats)?;
+    write_payload(app, payload)
+}
+
+pub fn read_image(app: &AppHandle) -> Result<image::RgbaImage, String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let image = app
+        .clipboard()
+        .read_image()
+        .map_err(|e| format!("No image found in clipboard: {}", e))?;
+    image::RgbaImage::from_raw(image.width(), image.height(), image.rgba().to_vec())
+        .ok_or_else(|| "Clipboard image has an unexpected size".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> image::RgbaImage {
+        image::RgbaImage::from_fn(3, 2, |x, y| {
+            image::Rgba([x as u8 * 80, y as u8 * 120, 9, 200])
+        })
+    }
+
+    fn formats(png: bool, file: bool, html: bool, text: Option<&str>) -> ClipboardFormats {
+        ClipboardFormats {
+            png,
+            file,
+            html,
+            text: text.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn file_uris_are_percent_encoded() {
+        assert_eq!(
+            file_uri(Path::new("/tmp/a b/shot #1.png")),
+            "file:///tmp/a%20b/shot%20%231.png"
+        );
+        assert_eq!(
+            file_uri(Path::new("/tmp/caf\u{e9}.png")),
+            "file:///tmp/caf%C3%A9.png"
+        );
+        assert_eq!(
+            file_uri(Path::new("C:\\Users\\me\\x.png")),
+            "file:///C:/Users/me/x.png"
+        );
+    }
+
+    #[test]
+    fn bitmap_only_payload_skips_extra_formats() {
+        let png = encode_png_bytes(&image()).unwrap();
+        let payload = prepare_payload(&png, formats(false, false, false, Some("  \n"))).unwrap();
+        assert_eq!(payload.image, image());
+        assert!(payload.png.is_none());
+        assert!(payload.file_path.is_none());
+        assert!(payload.html.is_none());
+        assert!(payload.text.is_none());
+    }
+
+    #[test]
+    fn png_input_is_reused_and_other_formats_are_reencoded() {
+        let png = encode_png_bytes(&image()).unwrap();
+        let payload = prepare_payload(&png, formats(true, false, false, Some("caption"))).unwrap();
+        assert_eq!(payload.png.as_deref(), Some(png.as_slice()));
+        assert_eq!(payload.text.as_deref(), Some("caption"));
+
+        let mut qoi = Vec::new();
+        image()
+            .write_to(&mut std::io::Cursor::new(&mut qoi), image::ImageFormat::Qoi)
+            .unwrap();
+        let payload = prepare_payload(&qoi, formats(true, false, false, None)).unwrap();
+        let reencoded = payload.png.unwrap();
+        assert_eq!(
+            image::guess_format(&reencoded).unwrap(),
+            image::ImageFormat::Png
+        );
+        assert_eq!(
+            image::load_from_memory(&reencoded).unwrap().to_rgba8(),
+            image()
+        );
+    }
+
+    #[test]
+    fn file_and_html_formats_carry_the_png() {
+        let png = encode_png_bytes(&image()).unwrap();
+        let payload = prepare_payload(&png, formats(false, true, true, None)).unwrap();
+        assert!(payload.png.is_none());
+
+        let path = payload.file_path.unwrap();
+        assert!(path.starts_with(clipboard_temp_dir()));
+        assert_eq!(std::fs::read(&path).unwrap(), png);
+        std::fs::remove_file(&path).unwrap();
+
+        let html = payload.html.unwrap();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&png);
+        assert!(html.starts_with(&format!("<img src=\"data:image/png;base64,{}\"", encoded)));
+        assert!(html.contains("width=\"3\" height=\"2\""));
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[test]
+    fn linux_offers_one_target_per_format() {
+        let png = encode_png_bytes(&image()).unwrap();
+        let payload = prepare_payload(&png, formats(false, true, true, Some("caption"))).unwrap();
+        let path = payload.file_path.clone().unwrap();
+        let targets = selection_targets(payload).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let names = targets.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "image/png",
+                "text/uri-list",
+                "x-special/gnome-copied-files",
+                "text/html",
+                "text/plain;charset=utf-8",
+                "UTF8_STRING",
+                "text/plain",
+            ]
+        );
+        assert_eq!(targets[0].1, png);
+        assert_eq!(
+            targets[1].1,
+            format!("{}\r\n", file_uri(&path)).into_bytes()
+        );
+        assert_eq!(targets[6].1, b"caption");
+    }
+}
@@ -0,0 +1,246 @@
+use serde::Serialize;
+
+/// Logical coordinates are device-independent (points on macOS, DIPs on Windows); physical
+/// coordinates are device pixels, the space captured images are in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CoordinateSpace {
+    Logical,
+    Physical,
+}
+
+impl CoordinateSpace {
+    /// The space xcap reports monitor and window geometry in on this platform.
+    pub fn native() -> Self {
+        if cfg!(target_os = "macos") {
+            CoordinateSpace::Logical
+        } else {
+            CoordinateSpace::Physical
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width.max(0.0) * self.height.max(0.0)
+    }
+
+    pub fn translate(&self, dx: f64, dy: f64) -> Self {
+        Self::new(self.x + dx, self.y + dy, self.width, self.height)
+    }
+
+    pub fn scale(&self, factor: f64) -> Self {
+        Self::new(
+            self.x * factor,
+            self.y * factor,
+            self.width * factor,
+            self.height * factor,
+        )
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Option<Self> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        (right > left && bottom > top).then(|| Self::new(left, top, right - left, bottom - top))
+    }
+
+    pub fn union(&self, other: &Rect) -> Self {
+        let left = self.x.min(other.x);
+        let top = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        Self::new(left, top, right - left, bottom - top)
+    }
+}
+
+/// A monitor's bounds in both coordinate spaces.
+///
+/// On Windows and Linux the logical origin follows the per-monitor convention (physical origin
+/// divided by that monitor's scale), so global logical rectangles are only meaningful against
+/// the monitor they were derived from. On macOS the logical space is the global point space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonitorSpace {
+    pub logical: Rect,
+    pub physical: Rect,
+    pub scale_factor: f64,
+}
+
+impl MonitorSpace {
+    pub fn new(bounds: Rect, scale_factor: f64, space: CoordinateSpace) -> Self {
+        let scale_factor = if scale_factor.is_finite() && scale_factor > 0.0 {
+            scale_factor
+        } else {
+            1.0
+        };
+
+        match space {
+            CoordinateSpace::Logical => Self {
+                logical: bounds,
+                physical: bounds.scale(scale_factor),
+                scale_factor,
+            },
+            CoordinateSpace::Physical => Self {
+                logical: bounds.scale(1.0 / scale_factor),
+                physical: bounds,
+                scale_factor,
+            },
+        }
+    }
+
+    pub fn bounds(&self, space: CoordinateSpace) -> Rect {
+        match space {
+            CoordinateSpace::Logical => self.logical,
+            CoordinateSpace::Physical => self.physical,
+        }
+    }
+
+    /// Converts a monitor-local rectangle between spaces.
+    pub fn convert_local(&self, rect: Rect, from: CoordinateSpace, to: CoordinateSpace) -> Rect {
+        match (from, to) {
+            (CoordinateSpace::Logical, CoordinateSpace::Physical) => rect.scale(self.scale_factor),
+            (CoordinateSpace::Physical, CoordinateSpace::Logical) => {
+                rect.scale(1.0 / self.scale_factor)
+            }
+            _ => rect,
+        }
+    }
+
+    /// Clips a global rectangle to this monitor and returns the visible part in local
+    /// coordinates, both in `space`.
+    pub fn clip_local(&self, rect: Rect, space: CoordinateSpace) -> Option<Rect> {
+        let bounds = self.bounds(space);
+        rect.intersect(&bounds)
+            .map(|clipped| clipped.translate(-bounds.x, -bounds.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rect(actual: Rect, expected: Rect) {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(
+            close(actual.x, expected.x)
+                && close(actual.y, expected.y)
+                && close(actual.width, expected.width)
+                && close(actual.height, expected.height),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn logical_monitor_left_of_primary_scales_origin() {
+        let space = MonitorSpace::new(
+            Rect::new(-1920.0, -200.0, 1920.0, 1080.0),
+            1.25,
+            CoordinateSpace::Logical,
+        );
+        assert_rect(space.logical, Rect::new(-1920.0, -200.0, 1920.0, 1080.0));
+        assert_rect(space.physical, Rect::new(-2400.0, -250.0, 2400.0, 1350.0));
+    }
+
+    #[test]
+    fn physical_monitor_above_primary_derives_per_monitor_logical() {
+        let space = MonitorSpace::new(
+            Rect::new(-2880.0, -1620.0, 2880.0, 1620.0),
+            1.5,
+            CoordinateSpace::Physical,
+        );
+        assert_rect(space.physical, Rect::new(-2880.0, -1620.0, 2880.0, 1620.0));
+        assert_rect(space.logical, Rect::new(-1920.0, -1080.0, 1920.0, 1080.0));
+    }
+
+    #[test]
+    fn local_conversion_round_trips_at_common_scales() {
+        for scale_factor in [1.25, 1.5, 2.0] {
+            let space = MonitorSpace::new(
+                Rect::new(-1280.0, 0.0, 1280.0, 800.0),
+                scale_factor,
+                CoordinateSpace::Logical,
+            );
+            let logical = Rect::new(10.0, 20.0, 300.0, 150.0);
+            let physical =
+                space.convert_local(logical, CoordinateSpace::Logical, CoordinateSpace::Physical);
+            assert_rect(physical, logical.scale(scale_factor));
+            assert_rect(
+                space.convert_local(
+                    physical,
+                    CoordinateSpace::Physical,
+                    CoordinateSpace::Logical,
+                ),
+                logical,
+            );
+        }
+    }
+
+    #[test]
+    fn clip_local_is_relative_to_negative_origin() {
+        let space = MonitorSpace::new(
+            Rect::new(-3840.0, -400.0, 3840.0, 2160.0),
+            2.0,
+            CoordinateSpace::Physical,
+        );
+        let clipped = space
+            .clip_local(
+                Rect::new(-100.0, -300.0, 400.0, 400.0),
+                CoordinateSpace::Physical,
+            )
+            .unwrap();
+        assert_rect(clipped, Rect::new(3740.0, 100.0, 100.0, 400.0));
+        let logical = space
+            .clip_local(
+                Rect::new(-1000.0, -200.0, 500.0, 100.0),
+                CoordinateSpace::Logical,
+            )
+            .unwrap();
+        assert_rect(logical, Rect::new(920.0, 0.0, 500.0, 100.0));
+        assert!(space
+            .clip_local(Rect::new(10.0, 10.0, 50.0, 50.0), CoordinateSpace::Physical)
+            .is_none());
+    }
+
+    #[test]
+    fn invalid_scale_factor_falls_back_to_one() {
+        for scale_factor in [0.0, -1.5, f64::NAN] {
+            let bounds = Rect::new(-100.0, -100.0, 640.0, 480.0);
+            let space = MonitorSpace::new(bounds, scale_factor, CoordinateSpace::Physical);
+            assert_eq!(space.scale_factor, 1.0);
+            assert_rect(space.logical, bounds);
+        }
+    }
+}
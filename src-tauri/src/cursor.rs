@@ -0,0 +1,432 @@
+use crate::coordinates::{CoordinateSpace, Rect};
+use crate::CaptureMonitor;
+use tauri::AppHandle;
+
+// Arrow used when the platform cursor shape is unavailable, drawn at 2x with its tip at (2, 2).
+const FALLBACK_ARROW_PNG: &[u8] = include_bytes!("../assets/cursor-arrow.png");
+const FALLBACK_ARROW_SCALE: f64 = 2.0;
+const FALLBACK_ARROW_HOTSPOT: f64 = 2.0;
+
+#[derive(Debug, Clone)]
+pub struct CursorSnapshot {
+    image: image::RgbaImage,
+    /// Hotspot in cursor image pixels.
+    hotspot_x: f64,
+    hotspot_y: f64,
+    /// Cursor image pixels per logical unit, or `None` when the image is already in device
+    /// pixels (Windows hands out cursors sized for the monitor's DPI).
+    logical_scale: Option<f64>,
+    /// Cursor position in the platform's native coordinate space.
+    x: f64,
+    y: f64,
+}
+
+fn fallback_arrow() -> image::RgbaImage {
+    image::load_from_memory_with_format(FALLBACK_ARROW_PNG, image::ImageFormat::Png)
+        .map(|image| image.to_rgba8())
+        .unwrap_or_else(|_| image::RgbaImage::new(1, 1))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_cursor_position(_app: &AppHandle) -> Option<(f64, f64)> {
+    use core_graphics::event::CGEvent;
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+    CGEventSource::new(CGEventSourceStateID::CombinedSessionState)
+        .ok()
+        .and_then(|source| CGEvent::new(source).ok())
+        .map(|event| event.location())
+        .map(|location| (location.x, location.y))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_cursor(app: &AppHandle) -> Option<CursorSnapshot> {
+    use objc2::rc::autoreleasepool;
+    use objc2_app_kit::NSCursor;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let (x, y) = platform_cursor_position(app)?;
+
+    let (sender, receiver) = mpsc::channel();
+    let _ = app.run_on_main_thread(move || {
+        let shape = autoreleasepool(|_| {
+            let cursor = NSCursor::currentSystemCursor()?;
+            let ns_image = cursor.image();
+            let size = ns_image.size();
+            let hotspot = cursor.hotSpot();
+            let tiff = ns_image.TIFFRepresentation()?;
+            Some((tiff.to_vec(), size.width, hotspot.x, hotspot.y))
+        });
+        let _ = sender.send(shape);
+    });
+
+    let shape = receiver.recv_timeout(Duration::from_millis(200)).ok()?;
+    let (image, logical_scale, hotspot_x, hotspot_y) = shape
+        .and_then(|(tiff, logical_width, hotspot_x, hotspot_y)| {
+            let image = image::load_from_memory(&tiff).ok()?.to_rgba8();
+            let logical_scale = image.width() as f64 / logical_width.max(1.0);
+            Some((
+                image,
+                logical_scale,
+                hotspot_x * logical_scale,
+                hotspot_y * logical_scale,
+            ))
+        })
+        .unwrap_or_else(|| {
+            (
+                fallback_arrow(),
+                FALLBACK_ARROW_SCALE,
+                FALLBACK_ARROW_HOTSPOT,
+                FALLBACK_ARROW_HOTSPOT,
+            )
+        });
+
+    Some(CursorSnapshot {
+        image,
+        hotspot_x,
+        hotspot_y,
+        logical_scale: Some(logical_scale),
+        x,
+        y,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn platform_cursor_position(_app: &AppHandle) -> Option<(f64, f64)> {
+    use windows_sys::Win32::Foundation::POINT;
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+    let mut point = POINT { x: 0, y: 0 };
+    (unsafe { GetCursorPos(&mut point) } != 0).then_some((point.x as f64, point.y as f64))
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn read_bitmap_bgra(
+    hdc: windows_sys::Win32::Graphics::Gdi::HDC,
+    bitmap: windows_sys::Win32::Graphics::Gdi::HBITMAP,
+) -> Option<(u32, u32, Vec<u8>)> {
+    use windows_sys::Win32::Graphics::Gdi::{
+        GetDIBits, GetObjectW, BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+
+    let mut info: BITMAP = std::mem::zeroed();
+    if GetObjectW(
+        bitmap,
+        std::mem::size_of::<BITMAP>() as i32,
+        &mut info as *mut BITMAP as *mut _,
+    ) == 0
+    {
+        return None;
+    }
+    let width = info.bmWidth.max(0) as u32;
+    let height = info.bmHeight.max(0) as u32;
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let mut header: BITMAPINFO = std::mem::zeroed();
+    header.bmiHeader = BITMAPINFOHEADER {
+        biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+        biWidth: width as i32,
+        // Negative height asks for a top-down bitmap.
+        biHeight: -(height as i32),
+        biPlanes: 1,
+        biBitCount: 32,
+        biCompression: BI_RGB,
+        ..std::mem::zeroed()
+    };
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    let lines = GetDIBits(
+        hdc,
+        bitmap,
+        0,
+        height,
+        pixels.as_mut_ptr() as *mut _,
+        &mut header,
+        DIB_RGB_COLORS,
+    );
+    (lines > 0).then_some((width, height, pixels))
+}
+
+#[cfg(target_os = "windows")]
+fn platform_cursor(_app: &AppHandle) -> Option<CursorSnapshot> {
+    use windows_sys::Win32::Graphics::Gdi::{DeleteObject, GetDC, ReleaseDC};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetCursorInfo, GetIconInfo, CURSORINFO, CURSOR_SHOWING, ICONINFO,
+    };
+
+    unsafe {
+        let mut cursor_info: CURSORINFO = std::mem::zeroed();
+        cursor_info.cbSize = std::mem::size_of::<CURSORINFO>() as u32;
+        if GetCursorInfo(&mut cursor_info) == 0 || cursor_info.flags & CURSOR_SHOWING == 0 {
+            return None;
+        }
+        let x = cursor_info.ptScreenPos.x as f64;
+        let y = cursor_info.ptScreenPos.y as f64;
+
+        let mut icon_info: ICONINFO = std::mem::zeroed();
+        let shape = if GetIconInfo(cursor_info.hCursor, &mut icon_info) != 0 {
+            let hdc = GetDC(std::ptr::null_mut());
+            let color = (!icon_info.hbmColor.is_null())
+                .then(|| read_bitmap_bgra(hdc, icon_info.hbmColor))
+                .flatten();
+            let mask = read_bitmap_bgra(hdc, icon_info.hbmMask);
+            ReleaseDC(std::ptr::null_mut(), hdc);
+            if !icon_info.hbmColor.is_null() {
+                DeleteObject(icon_info.hbmColor);
+            }
+            DeleteObject(icon_info.hbmMask);
+
+            cursor_image_from_bitmaps(color, mask)
+                .map(|image| (image, icon_info.xHotspot as f64, icon_info.yHotspot as f64))
+        } else {
+            None
+        };
+
+        let snapshot = match shape {
+            Some((image, hotspot_x, hotspot_y)) => CursorSnapshot {
+                image,
+                hotspot_x,
+                hotspot_y,
+                logical_scale: None,
+                x,
+                y,
+            },
+            None => CursorSnapshot {
+                image: fallback_arrow(),
+                hotspot_x: FALLBACK_ARROW_HOTSPOT,
+                hotspot_y: FALLBACK_ARROW_HOTSPOT,
+                logical_scale: Some(FALLBACK_ARROW_SCALE),
+                x,
+                y,
+            },
+        };
+        Some(snapshot)
+    }
+}
+
+/// Builds an RGBA cursor from GDI bitmaps. Colour cursors without an alpha channel take their
+/// transparency from the AND mask; monochrome cursors stack the AND and XOR masks vertically.
+#[cfg(target_os = "windows")]
+fn cursor_image_from_bitmaps(
+    color: Option<(u32, u32, Vec<u8>)>,
+    mask: Option<(u32, u32, Vec<u8>)>,
+) -> Option<image::RgbaImage> {
+    let mask_opaque = |mask: &[u8], index: usize| mask[index * 4] == 0;
+
+    if let Some((width, height, bgra)) = color {
+        let has_alpha = bgra.chunks_exact(4).any(|pixel| pixel[3] != 0);
+        let mut image = image::RgbaImage::new(width, height);
+        for (index, pixel) in bgra.chunks_exact(4).enumerate() {
+            let alpha = if has_alpha {
+                pixel[3]
+            } else {
+                match mask.as_ref() {
+                    Some((_, _, mask)) if mask.len() >= bgra.len() && !mask_opaque(mask, index) => {
+                        0
+                    }
+                    _ => 255,
+                }
+            };
+            image.put_pixel(
+                index as u32 % width,
+                index as u32 / width,
+                image::Rgba([pixel[2], pixel[1], pixel[0], alpha]),
+            );
+        }
+        return Some(image);
+    }
+
+    let (width, double_height, mask) = mask?;
+    let height = double_height / 2;
+    if height == 0 {
+        return None;
+    }
+    let plane = (width * height) as usize;
+    let mut image = image::RgbaImage::new(width, height);
+    for index in 0..plane {
+        let and_bit = !mask_opaque(&mask, index);
+        let xor_bit = mask[(plane + index) * 4] != 0;
+        let pixel = match (and_bit, xor_bit) {
+            (false, false) => image::Rgba([0, 0, 0, 255]),
+            (false, true) => image::Rgba([255, 255, 255, 255]),
+            (true, false) => continue,
+            // Screen-inverting pixels cannot be reproduced on a still image; draw them dark.
+            (true, true) => image::Rgba([0, 0, 0, 255]),
+        };
+        image.put_pixel(index as u32 % width, index as u32 / width, pixel);
+    }
+    Some(image)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn platform_cursor_position(app: &AppHandle) -> Option<(f64, f64)> {
+    app.cursor_position()
+        .ok()
+        .map(|position| (position.x, position.y))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn platform_cursor(app: &AppHandle) -> Option<CursorSnapshot> {
+    // Reading the cursor shape needs XFixes/compositor support, so only the position is real.
+    let (x, y) = platform_cursor_position(app)?;
+    Some(CursorSnapshot {
+        image: fallback_arrow(),
+        hotspot_x: FALLBACK_ARROW_HOTSPOT,
+        hotspot_y: FALLBACK_ARROW_HOTSPOT,
+        logical_scale: Some(FALLBACK_ARROW_SCALE),
+        x,
+        y,
+    })
+}
+
+pub fn snapshot_cursor(app: &AppHandle) -> Option<CursorSnapshot> {
+    platform_cursor(app)
+}
+
+/// Cursor position in the platform's native coordinate space, without reading the cursor image.
+pub fn cursor_position(app: &AppHandle) -> Option<(f64, f64)> {
+    platform_cursor_position(app)
+}
+
+/// Draws the cursor into a monitor's prepared capture, keeping its frame format. Captures the
+/// cursor is not over are returned unchanged.
+pub fn composite_cursor(
+    bytes: Vec<u8>,
+    monitor: &CaptureMonitor,
+    cursor: &CursorSnapshot,
+) -> Result<Vec<u8>, String> {
+    let bounds = monitor.space().bounds(CoordinateSpace::native());
+    let reach = cursor.image.width().max(cursor.image.height()) as f64;
+    let cursor_area = Rect::new(cursor.x - reach, cursor.y - reach, reach * 2.0, reach * 2.0);
+    if bounds.intersect(&cursor_area).is_none() {
+        return Ok(bytes);
+    }
+
+    let mut capture = crate::frame::decode_frame(&bytes)?;
+    let pixels_per_unit = capture.width() as f64 / bounds.width.max(1.0);
+    let pixels_per_logical = capture.width() as f64 / monitor.width.max(1.0);
+    let cursor_scale = match cursor.logical_scale {
+        Some(logical_scale) => pixels_per_logical / logical_scale.max(0.1),
+        None => 1.0,
+    };
+
+    let cursor_image = if (cursor_scale - 1.0).abs() > 0.01 {
+        image::imageops::resize(
+            &cursor.image,
+            ((cursor.image.width() as f64 * cursor_scale).round() as u32).max(1),
+            ((cursor.image.height() as f64 * cursor_scale).round() as u32).max(1),
+            image::imageops::FilterType::Triangle,
+        )
+    } else {
+        cursor.image.clone()
+    };
+    let left = (cursor.x - bounds.x) * pixels_per_unit - cursor.hotspot_x * cursor_scale;
+    let top = (cursor.y - bounds.y) * pixels_per_unit - cursor.hotspot_y * cursor_scale;
+    image::imageops::overlay(
+        &mut capture,
+        &cursor_image,
+        left.round() as i64,
+        top.round() as i64,
+    );
+
+    crate::frame::encode_frame(&capture, crate::frame::detect_format(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: image::Rgba<u8> = image::Rgba([255, 255, 255, 255]);
+    const RED: image::Rgba<u8> = image::Rgba([255, 0, 0, 255]);
+
+    fn monitor(scale_factor: f64) -> CaptureMonitor {
+        CaptureMonitor::new(
+            1,
+            "screen_1".into(),
+            Rect::new(100.0, 0.0, 200.0, 100.0),
+            CoordinateSpace::native(),
+            scale_factor,
+            true,
+            String::new(),
+        )
+    }
+
+    fn cursor(x: f64, y: f64, logical_scale: Option<f64>) -> CursorSnapshot {
+        CursorSnapshot {
+            image: image::RgbaImage::from_pixel(4, 4, RED),
+            hotspot_x: 2.0,
+            hotspot_y: 2.0,
+            logical_scale,
+            x,
+            y,
+        }
+    }
+
+    fn capture(monitor: &CaptureMonitor) -> Vec<u8> {
+        let image = image::RgbaImage::from_pixel(
+            monitor.physical_width as u32,
+            monitor.physical_height as u32,
+            WHITE,
+        );
+        crate::frame::encode_frame(&image, crate::frame::FrameFormat::Rgba).unwrap()
+    }
+
+    /// Bounding box of the red pixels as `(left, top, right, bottom)`, exclusive.
+    fn red_area(bytes: &[u8]) -> (u32, u32, u32, u32) {
+        let image = crate::frame::decode_frame(bytes).unwrap();
+        let red = image
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel[1] < 128)
+            .map(|(x, y, _)| (x, y))
+            .collect::<Vec<_>>();
+        assert!(!red.is_empty());
+        (
+            red.iter().map(|(x, _)| *x).min().unwrap(),
+            red.iter().map(|(_, y)| *y).min().unwrap(),
+            red.iter().map(|(x, _)| *x).max().unwrap() + 1,
+            red.iter().map(|(_, y)| *y).max().unwrap() + 1,
+        )
+    }
+
+    #[test]
+    fn device_pixel_cursors_are_placed_by_their_hotspot() {
+        let monitor = monitor(1.0);
+        let bytes = composite_cursor(capture(&monitor), &monitor, &cursor(150.0, 30.0, None));
+        assert_eq!(red_area(&bytes.unwrap()), (48, 28, 52, 32));
+    }
+
+    #[test]
+    fn logical_cursors_are_scaled_to_the_capture() {
+        // A 2x cursor image on a 1x display is drawn at half size, hotspot included.
+        let standard = monitor(1.0);
+        let bytes = composite_cursor(
+            capture(&standard),
+            &standard,
+            &cursor(150.0, 30.0, Some(2.0)),
+        );
+        assert_eq!(red_area(&bytes.unwrap()), (49, 29, 51, 31));
+
+        // On a 2x display it keeps its size, at the cursor's position in capture pixels.
+        let retina = monitor(2.0);
+        let pixels_per_unit = retina.physical_width / 200.0;
+        let left = (50.0 * pixels_per_unit) as u32 - 2;
+        let top = (30.0 * pixels_per_unit) as u32 - 2;
+        let bytes = composite_cursor(capture(&retina), &retina, &cursor(150.0, 30.0, Some(2.0)));
+        assert_eq!(red_area(&bytes.unwrap()), (left, top, left + 4, top + 4));
+    }
+
+    #[test]
+    fn cursors_off_the_monitor_leave_the_capture_untouched() {
+        let monitor = monitor(1.0);
+        let bytes = capture(&monitor);
+        let composited =
+            composite_cursor(bytes.clone(), &monitor, &cursor(20.0, 30.0, None)).unwrap();
+        assert_eq!(composited, bytes);
+
+        // A cursor just past the edge still overlaps it and is clipped.
+        let bytes = composite_cursor(capture(&monitor), &monitor, &cursor(99.0, 30.0, None));
+        assert_eq!(red_area(&bytes.unwrap()), (0, 28, 1, 32));
+    }
+}
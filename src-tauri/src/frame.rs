@@ -0,0 +1,199 @@
+use image::ImageEncoder;
+use serde::Deserialize;
+
+// Layout, little endian: magic[4] version:u8 format:u8 reserved:u16 width:u32 height:u32
+// stride:u32, followed by the payload. Mirrored by `src/logic/frame.ts`.
+const FRAME_MAGIC: &[u8; 4] = b"XSRF";
+const FRAME_VERSION: u8 = 1;
+const FRAME_HEADER_LEN: usize = 20;
+const FRAME_FORMAT_RGBA: u8 = 1;
+const FRAME_FORMAT_QOI: u8 = 2;
+
+/// How prepared captures travel to the webview. PNG can be handed to an `<img>` directly; the
+/// framed formats skip the PNG encode/decode round trip that dominates on 4K/5K displays.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameFormat {
+    #[default]
+    Png,
+    Rgba,
+    Qoi,
+}
+
+struct FrameHeader {
+    format: u8,
+    width: u32,
+    height: u32,
+    stride: u32,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+fn parse_header(bytes: &[u8]) -> Option<FrameHeader> {
+    if bytes.len() < FRAME_HEADER_LEN || &bytes[..4] != FRAME_MAGIC || bytes[4] != FRAME_VERSION {
+        return None;
+    }
+
+    Some(FrameHeader {
+        format: bytes[5],
+        width: read_u32(bytes, 8),
+        height: read_u32(bytes, 12),
+        stride: read_u32(bytes, 16),
+    })
+}
+
+fn write_header(format: u8, width: u32, height: u32, stride: u32, capacity: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(FRAME_HEADER_LEN + capacity);
+    bytes.extend_from_slice(FRAME_MAGIC);
+    bytes.push(FRAME_VERSION);
+    bytes.push(format);
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    bytes.extend_from_slice(&width.to_le_bytes());
+    bytes.extend_from_slice(&height.to_le_bytes());
+    bytes.extend_from_slice(&stride.to_le_bytes());
+    bytes
+}
+
+pub fn detect_format(bytes: &[u8]) -> FrameFormat {
+    match parse_header(bytes).map(|header| header.format) {
+        Some(FRAME_FORMAT_RGBA) => FrameFormat::Rgba,
+        Some(FRAME_FORMAT_QOI) => FrameFormat::Qoi,
+        _ => FrameFormat::Png,
+    }
+}
+
+pub fn encode_frame(image: &image::RgbaImage, format: FrameFormat) -> Result<Vec<u8>, String> {
+    let (width, height) = image.dimensions();
+    match format {
+        FrameFormat::Png => crate::encode_rgba_png_fast(image),
+        FrameFormat::Rgba => {
+            let mut bytes = write_header(
+                FRAME_FORMAT_RGBA,
+                width,
+                height,
+                width * 4,
+                image.as_raw().len(),
+            );
+            bytes.extend_from_slice(image.as_raw());
+            Ok(bytes)
+        }
+        FrameFormat::Qoi => {
+            let mut bytes = write_header(FRAME_FORMAT_QOI, width, height, width * 4, 0);
+            image::codecs::qoi::QoiEncoder::new(&mut bytes)
+                .write_image(
+                    image.as_raw(),
+                    width,
+                    height,
+                    image::ExtendedColorType::Rgba8,
+                )
+                .map_err(|e| format!("Failed to encode QOI frame: {}", e))?;
+            Ok(bytes)
+        }
+    }
+}
+
+fn packed_image(
+    rows: &[u8],
+    width: u32,
+    height: u32,
+    stride: u32,
+) -> Result<image::RgbaImage, String> {
+    let row_len = width as usize * 4;
+    if (stride as usize) < row_len {
+        return Err("Frame stride is smaller than its width".into());
+    }
+    let mut pixels = Vec::with_capacity(row_len * height as usize);
+    for row in rows.chunks(stride as usize).take(height as usize) {
+        pixels.extend_from_slice(row.get(..row_len).ok_or("Frame row is truncated")?);
+    }
+    image::RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| "Frame buffer is smaller than its dimensions".to_string())
+}
+
+/// Decodes either a framed capture or any encoded image the `image` crate understands.
+pub fn decode_frame(bytes: &[u8]) -> Result<image::RgbaImage, String> {
+    let Some(header) = parse_header(bytes) else {
+        return image::load_from_memory(bytes)
+            .map(|image| image.to_rgba8())
+            .map_err(|e| format!("Failed to decode image: {}", e));
+    };
+    let payload = &bytes[FRAME_HEADER_LEN..];
+
+    match header.format {
+        FRAME_FORMAT_RGBA => packed_image(payload, header.width, header.height, header.stride),
+        FRAME_FORMAT_QOI => image::load_from_memory_with_format(payload, image::ImageFormat::Qoi)
+            .map(|image| image.to_rgba8())
+            .map_err(|e| format!("Failed to decode QOI frame: {}", e)),
+        format => Err(format!("Unsupported frame format {}", format)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> image::RgbaImage {
+        image::RgbaImage::from_fn(3, 2, |x, y| {
+            image::Rgba([x as u8 * 60, y as u8 * 90, 200, 255 - x as u8])
+        })
+    }
+
+    #[test]
+    fn header_layout_matches_the_webview_reader() {
+        let bytes = encode_frame(&image(), FrameFormat::Rgba).unwrap();
+        assert_eq!(
+            &bytes[..FRAME_HEADER_LEN],
+            &[b'X', b'S', b'R', b'F', 1, 1, 0, 0, 3, 0, 0, 0, 2, 0, 0, 0, 12, 0, 0, 0]
+        );
+        assert_eq!(&bytes[FRAME_HEADER_LEN..], image().as_raw().as_slice());
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        for format in [FrameFormat::Png, FrameFormat::Rgba, FrameFormat::Qoi] {
+            let bytes = encode_frame(&image(), format).unwrap();
+            assert_eq!(detect_format(&bytes), format);
+            assert_eq!(decode_frame(&bytes).unwrap(), image(), "{:?}", format);
+        }
+        let qoi = encode_frame(&image(), FrameFormat::Qoi).unwrap();
+        assert_eq!(qoi[5], FRAME_FORMAT_QOI);
+        assert_eq!(&qoi[FRAME_HEADER_LEN..FRAME_HEADER_LEN + 4], b"qoif");
+    }
+
+    #[test]
+    fn padded_rows_are_repacked() {
+        let mut bytes = write_header(FRAME_FORMAT_RGBA, 3, 2, 16, 32);
+        for row in image().as_raw().chunks(12) {
+            bytes.extend_from_slice(row);
+            bytes.extend_from_slice(&[0xAA; 4]);
+        }
+        assert_eq!(decode_frame(&bytes).unwrap(), image());
+    }
+
+    #[test]
+    fn rejects_inconsistent_frames() {
+        let narrow = write_header(FRAME_FORMAT_RGBA, 3, 2, 8, 0);
+        assert!(decode_frame(&narrow).is_err());
+
+        let mut truncated = encode_frame(&image(), FrameFormat::Rgba).unwrap();
+        truncated.truncate(truncated.len() - 1);
+        assert!(decode_frame(&truncated).is_err());
+
+        let unknown = write_header(9, 3, 2, 12, 0);
+        assert_eq!(detect_format(&unknown), FrameFormat::Png);
+        assert!(decode_frame(&unknown).is_err());
+
+        // A different version is not a frame, so it is left to the image decoders.
+        let mut version = encode_frame(&image(), FrameFormat::Rgba).unwrap();
+        version[4] = 2;
+        assert_eq!(detect_format(&version), FrameFormat::Png);
+        assert!(decode_frame(&version).is_err());
+    }
+}
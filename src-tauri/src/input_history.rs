@@ -0,0 +1,815 @@
+use crate::coordinates::CoordinateSpace;
+use crate::visible_watermark;
+use crate::CaptureMonitor;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+const HISTORY_CAPACITY: usize = 24;
+/// Events older than this when a capture starts are not part of the step being captured.
+const HISTORY_WINDOW_MS: f64 = 10_000.0;
+const MAX_KEY_BADGES: usize = 6;
+const MAX_CLICK_MARKERS: usize = 5;
+/// Input this close to the capture trigger is the capture shortcut or tray click itself.
+const TRIGGER_GUARD_MS: f64 = 150.0;
+
+static EVENTS: Mutex<VecDeque<InputEvent>> = Mutex::new(VecDeque::new());
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InputEventKind {
+    Key,
+    Click,
+}
+
+/// A key press or mouse click seen shortly before a capture. `x`/`y` is the pointer position in
+/// the platform's native coordinate space.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputEvent {
+    kind: InputEventKind,
+    label: String,
+    x: f64,
+    y: f64,
+    coordinate_space: CoordinateSpace,
+    timestamp_ms: f64,
+}
+
+fn record(kind: InputEventKind, label: String, x: f64, y: f64) {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return;
+    }
+    if let Ok(mut events) = EVENTS.lock() {
+        if events.len() == HISTORY_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(InputEvent {
+            kind,
+            label,
+            x,
+            y,
+            coordinate_space: CoordinateSpace::native(),
+            timestamp_ms: crate::unix_epoch_ms(),
+        });
+    }
+}
+
+/// Events from the few seconds before `triggered_at_ms`, oldest first. Empty while history is
+/// off.
+pub fn recent(triggered_at_ms: f64) -> Vec<InputEvent> {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return Vec::new();
+    }
+    let since = triggered_at_ms - HISTORY_WINDOW_MS;
+    let until = triggered_at_ms - TRIGGER_GUARD_MS;
+    EVENTS
+        .lock()
+        .map(|events| {
+            events
+                .iter()
+                .filter(|event| event.timestamp_ms >= since && event.timestamp_ms <= until)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Only shortcuts and named keys are kept. A character typed on its own or with Shift is text
+/// the user entered, so it is never recorded.
+fn key_label(modifiers: &[&str], key: &str) -> Option<String> {
+    let is_text = key.chars().count() == 1 || key == "Space";
+    if is_text && modifiers.iter().all(|modifier| *modifier == "Shift") {
+        return None;
+    }
+    let mut parts = modifiers.to_vec();
+    parts.push(key);
+    Some(parts.join("+"))
+}
+
+#[cfg(target_os = "macos")]
+thread_local! {
+    static INPUT_TAP: std::cell::RefCell<Option<core_graphics::event::CGEventTap<'static>>> =
+        const { std::cell::RefCell::new(None) };
+    static INPUT_TAP_SOURCE: std::cell::RefCell<Option<core_foundation::runloop::CFRunLoopSource>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(target_os = "macos")]
+fn mac_key_name(keycode: i64) -> Option<&'static str> {
+    // kVK_* virtual key codes, named after the US layout.
+    Some(match keycode {
+        0 => "A",
+        1 => "S",
+        2 => "D",
+        3 => "F",
+        4 => "H",
+        5 => "G",
+        6 => "Z",
+        7 => "X",
+        8 => "C",
+        9 => "V",
+        11 => "B",
+        12 => "Q",
+        13 => "W",
+        14 => "E",
+        15 => "R",
+        16 => "Y",
+        17 => "T",
+        18 => "1",
+        19 => "2",
+        20 => "3",
+        21 => "4",
+        22 => "6",
+        23 => "5",
+        24 => "=",
+        25 => "9",
+        26 => "7",
+        27 => "-",
+        28 => "8",
+        29 => "0",
+        30 => "]",
+        31 => "O",
+        32 => "U",
+        33 => "[",
+        34 => "I",
+        35 => "P",
+        36 => "Enter",
+        37 => "L",
+        38 => "J",
+        39 => "'",
+        40 => "K",
+        41 => ";",
+        42 => "\\",
+        43 => ",",
+        44 => "/",
+        45 => "N",
+        46 => "M",
+        47 => ".",
+        48 => "Tab",
+        49 => "Space",
+        50 => "`",
+        51 => "Backspace",
+        53 => "Esc",
+        96 => "F5",
+        97 => "F6",
+        98 => "F7",
+        99 => "F3",
+        100 => "F8",
+        101 => "F9",
+        103 => "F11",
+        109 => "F10",
+        111 => "F12",
+        115 => "Home",
+        116 => "PgUp",
+        117 => "Del",
+        118 => "F4",
+        119 => "End",
+        120 => "F2",
+        121 => "PgDn",
+        122 => "F1",
+        123 => "Left",
+        124 => "Right",
+        125 => "Down",
+        126 => "Up",
+        _ => return None,
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn start_platform_hook(app: &AppHandle) -> Result<(), String> {
+    use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop};
+    use core_graphics::event::{
+        CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement,
+        CGEventType, CallbackResult, EventField,
+    };
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let (sender, receiver) = mpsc::channel();
+    app.run_on_main_thread(move || {
+        let result = INPUT_TAP.with(|tap_cell| -> Result<(), String> {
+            if tap_cell.borrow().is_some() {
+                return Ok(());
+            }
+
+            let tap = CGEventTap::new(
+                CGEventTapLocation::Session,
+                CGEventTapPlacement::TailAppendEventTap,
+                CGEventTapOptions::ListenOnly,
+                vec![
+                    CGEventType::KeyDown,
+                    CGEventType::LeftMouseDown,
+                    CGEventType::RightMouseDown,
+                    CGEventType::OtherMouseDown,
+                ],
+                |_proxy, event_type, event| {
+                    let location = event.location();
+                    match event_type {
+                        CGEventType::KeyDown => {
+                            if event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT)
+                                != 0
+                            {
+                                return CallbackResult::Keep;
+                            }
+                            let keycode =
+                                event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE);
+                            let Some(key) = mac_key_name(keycode) else {
+                                return CallbackResult::Keep;
+                            };
+                            let flags = event.get_flags();
+                            let modifiers = [
+                                (CGEventFlags::CGEventFlagControl, "Ctrl"),
+                                (CGEventFlags::CGEventFlagAlternate, "Opt"),
+                                (CGEventFlags::CGEventFlagShift, "Shift"),
+                                (CGEventFlags::CGEventFlagCommand, "Cmd"),
+                            ]
+                            .into_iter()
+                            .filter(|(flag, _)| flags.contains(*flag))
+                            .map(|(_, name)| name)
+                            .collect::<Vec<_>>();
+                            if let Some(label) = key_label(&modifiers, key) {
+                                record(InputEventKind::Key, label, location.x, location.y);
+                            }
+                        }
+                        CGEventType::LeftMouseDown => {
+                            record(InputEventKind::Click, "Left".into(), location.x, location.y)
+                        }
+                        CGEventType::RightMouseDown => record(
+                            InputEventKind::Click,
+                            "Right".into(),
+                            location.x,
+                            location.y,
+                        ),
+                        CGEventType::OtherMouseDown => record(
+                            InputEventKind::Click,
+                            "Middle".into(),
+                            location.x,
+                            location.y,
+                        ),
+                        _ => {}
+                    }
+                    CallbackResult::Keep
+                },
+            )
+            .map_err(|_| {
+                "Failed to create input monitor; check Input Monitoring permission".to_string()
+            })?;
+
+            let source = tap
+                .mach_port()
+                .create_runloop_source(0)
+                .map_err(|_| "Failed to create input monitor run loop source".to_string())?;
+            CFRunLoop::get_current().add_source(&source, unsafe { kCFRunLoopCommonModes });
+            tap.enable();
+
+            INPUT_TAP_SOURCE.with(|source_cell| {
+                *source_cell.borrow_mut() = Some(source);
+            });
+            *tap_cell.borrow_mut() = Some(tap);
+            Ok(())
+        });
+        let _ = sender.send(result);
+    })
+    .map_err(|error| error.to_string())?;
+
+    receiver
+        .recv_timeout(Duration::from_millis(500))
+        .map_err(|_| "Timed out while starting input monitor".to_string())?
+}
+
+#[cfg(target_os = "macos")]
+fn stop_platform_hook(app: &AppHandle) {
+    use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop};
+
+    let _ = app.run_on_main_thread(|| {
+        INPUT_TAP_SOURCE.with(|source_cell| {
+            if let Some(source) = source_cell.borrow_mut().take() {
+                CFRunLoop::get_current().remove_source(&source, unsafe { kCFRunLoopCommonModes });
+            }
+        });
+        INPUT_TAP.with(|tap_cell| {
+            let _ = tap_cell.borrow_mut().take();
+        });
+    });
+}
+
+#[cfg(target_os = "windows")]
+static HOOK_THREAD_ID: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+#[cfg(target_os = "windows")]
+static MODIFIERS: std::sync::atomic::AtomicU8 = std::sync::atomic::AtomicU8::new(0);
+
+#[cfg(target_os = "windows")]
+const WINDOWS_MODIFIERS: [(u8, &[u32], &str); 4] = [
+    (1, &[0x11, 0xA2, 0xA3], "Ctrl"),
+    (2, &[0x12, 0xA4, 0xA5], "Alt"),
+    (4, &[0x10, 0xA0, 0xA1], "Shift"),
+    (8, &[0x5B, 0x5C], "Win"),
+];
+
+#[cfg(target_os = "windows")]
+fn windows_key_name(vk: u32) -> Option<String> {
+    let name = match vk {
+        0x30..=0x39 | 0x41..=0x5A => return char::from_u32(vk).map(String::from),
+        0x70..=0x7B => return Some(format!("F{}", vk - 0x6F)),
+        0x08 => "Backspace",
+        0x09 => "Tab",
+        0x0D => "Enter",
+        0x1B => "Esc",
+        0x20 => "Space",
+        0x21 => "PgUp",
+        0x22 => "PgDn",
+        0x23 => "End",
+        0x24 => "Home",
+        0x25 => "Left",
+        0x26 => "Up",
+        0x27 => "Right",
+        0x28 => "Down",
+        0x2C => "PrtSc",
+        0x2E => "Del",
+        0xBA => ";",
+        0xBB => "=",
+        0xBC => ",",
+        0xBD => "-",
+        0xBE => ".",
+        0xBF => "/",
+        0xC0 => "`",
+        0xDB => "[",
+        0xDC => "\\",
+        0xDD => "]",
+        0xDE => "'",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn windows_cursor_position() -> (f64, f64) {
+    use windows_sys::Win32::Foundation::POINT;
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+    let mut point = POINT { x: 0, y: 0 };
+    unsafe { GetCursorPos(&mut point) };
+    (point.x as f64, point.y as f64)
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn keyboard_hook(code: i32, wparam: usize, lparam: isize) -> isize {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, HC_ACTION, KBDLLHOOKSTRUCT, WM_KEYDOWN, WM_KEYUP, WM_SYSKEYDOWN,
+        WM_SYSKEYUP,
+    };
+
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam as *const KBDLLHOOKSTRUCT);
+        let message = wparam as u32;
+        let pressed = message == WM_KEYDOWN || message == WM_SYSKEYDOWN;
+        let released = message == WM_KEYUP || message == WM_SYSKEYUP;
+        let modifier = WINDOWS_MODIFIERS
+            .iter()
+            .find(|(_, codes, _)| codes.contains(&info.vkCode));
+
+        match modifier {
+            Some((bit, _, _)) if pressed => {
+                MODIFIERS.fetch_or(*bit, Ordering::SeqCst);
+            }
+            Some((bit, _, _)) if released => {
+                MODIFIERS.fetch_and(!*bit, Ordering::SeqCst);
+            }
+            None if pressed => {
+                if let Some(key) = windows_key_name(info.vkCode) {
+                    let held = MODIFIERS.load(Ordering::SeqCst);
+                    let modifiers = WINDOWS_MODIFIERS
+                        .iter()
+                        .filter(|(bit, _, _)| held & bit != 0)
+                        .map(|(_, _, name)| *name)
+                        .collect::<Vec<_>>();
+                    if let Some(label) = key_label(&modifiers, &key) {
+                        let (x, y) = windows_cursor_position();
+                        record(InputEventKind::Key, label, x, y);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam)
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn mouse_hook(code: i32, wparam: usize, lparam: isize) -> isize {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, HC_ACTION, MSLLHOOKSTRUCT, WM_LBUTTONDOWN, WM_MBUTTONDOWN, WM_RBUTTONDOWN,
+    };
+
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam as *const MSLLHOOKSTRUCT);
+        let button = match wparam as u32 {
+            WM_LBUTTONDOWN => Some("Left"),
+            WM_RBUTTONDOWN => Some("Right"),
+            WM_MBUTTONDOWN => Some("Middle"),
+            _ => None,
+        };
+        if let Some(button) = button {
+            record(
+                InputEventKind::Click,
+                button.into(),
+                info.pt.x as f64,
+                info.pt.y as f64,
+            );
+        }
+    }
+    CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam)
+}
+
+#[cfg(target_os = "windows")]
+fn start_platform_hook(_app: &AppHandle) -> Result<(), String> {
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use windows_sys::Win32::System::Threading::GetCurrentThreadId;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        DispatchMessageW, GetMessageW, SetWindowsHookExW, TranslateMessage, UnhookWindowsHookEx,
+        MSG, WH_KEYBOARD_LL, WH_MOUSE_LL,
+    };
+
+    if HOOK_THREAD_ID.load(Ordering::SeqCst) != 0 {
+        return Ok(());
+    }
+
+    // Low-level hooks are serviced by the message loop of the thread that installed them.
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || unsafe {
+        let keyboard =
+            SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook), std::ptr::null_mut(), 0);
+        let mouse = SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook), std::ptr::null_mut(), 0);
+        if keyboard.is_null() || mouse.is_null() {
+            if !keyboard.is_null() {
+                UnhookWindowsHookEx(keyboard);
+            }
+            if !mouse.is_null() {
+                UnhookWindowsHookEx(mouse);
+            }
+            let _ = sender.send(Err("Failed to install input hooks".to_string()));
+            return;
+        }
+        HOOK_THREAD_ID.store(GetCurrentThreadId(), Ordering::SeqCst);
+        let _ = sender.send(Ok(()));
+
+        let mut message: MSG = std::mem::zeroed();
+        while GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) > 0 {
+            TranslateMessage(&message);
+            DispatchMessageW(&message);
+        }
+        UnhookWindowsHookEx(keyboard);
+        UnhookWindowsHookEx(mouse);
+        MODIFIERS.store(0, Ordering::SeqCst);
+    });
+
+    receiver
+        .recv_timeout(Duration::from_millis(500))
+        .map_err(|_| "Timed out while starting input monitor".to_string())?
+}
+
+#[cfg(target_os = "windows")]
+fn stop_platform_hook(_app: &AppHandle) {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{PostThreadMessageW, WM_QUIT};
+
+    let thread_id = HOOK_THREAD_ID.swap(0, Ordering::SeqCst);
+    if thread_id != 0 {
+        unsafe { PostThreadMessageW(thread_id, WM_QUIT, 0, 0) };
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn start_platform_hook(_app: &AppHandle) -> Result<(), String> {
+    Err("Input history is only implemented on macOS and Windows".into())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn stop_platform_hook(_app: &AppHandle) {}
+
+/// Starts or stops keeping the last few key presses and clicks in memory. Nothing is written to
+/// disk, and the history is dropped when turned off.
+#[tauri::command]
+pub async fn set_input_history(app: AppHandle, enabled: bool) -> Result<(), String> {
+    if enabled {
+        start_platform_hook(&app)?;
+        ENABLED.store(true, Ordering::SeqCst);
+    } else {
+        ENABLED.store(false, Ordering::SeqCst);
+        stop_platform_hook(&app);
+        if let Ok(mut events) = EVENTS.lock() {
+            events.clear();
+        }
+    }
+    Ok(())
+}
+
+fn blend(image: &mut image::RgbaImage, x: i64, y: i64, color: image::Rgba<u8>) {
+    if x < 0 || y < 0 || x as u32 >= image.width() || y as u32 >= image.height() {
+        return;
+    }
+    let alpha = color[3] as u32;
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    for channel in 0..3 {
+        pixel[channel] =
+            ((color[channel] as u32 * alpha + pixel[channel] as u32 * (255 - alpha)) / 255) as u8;
+    }
+    pixel[3] = pixel[3].max(color[3]);
+}
+
+fn fill_rounded_rect(
+    image: &mut image::RgbaImage,
+    left: i64,
+    top: i64,
+    width: i64,
+    height: i64,
+    radius: i64,
+    color: image::Rgba<u8>,
+) {
+    for y in top..top + height {
+        for x in left..left + width {
+            let dx = (left + radius - x)
+                .max(x - (left + width - 1 - radius))
+                .max(0);
+            let dy = (top + radius - y)
+                .max(y - (top + height - 1 - radius))
+                .max(0);
+            if dx * dx + dy * dy <= radius * radius {
+                blend(image, x, y, color);
+            }
+        }
+    }
+}
+
+fn draw_ring(
+    image: &mut image::RgbaImage,
+    center_x: f64,
+    center_y: f64,
+    radius: f64,
+    thickness: f64,
+    color: image::Rgba<u8>,
+) {
+    let reach = (radius + thickness).ceil() as i64;
+    let (cx, cy) = (center_x.round() as i64, center_y.round() as i64);
+    for y in cy - reach..=cy + reach {
+        for x in cx - reach..=cx + reach {
+            let distance = ((x as f64 - center_x).powi(2) + (y as f64 - center_y).powi(2)).sqrt();
+            if (distance - radius).abs() <= thickness / 2.0 {
+                blend(image, x, y, color);
+            }
+        }
+    }
+}
+
+/// Marks recent clicks with numbered rings and lists recent key presses as badges along the
+/// bottom of the monitor the last key press happened on. Keeps the capture's frame format.
+pub fn composite_input(
+    bytes: Vec<u8>,
+    monitor: &CaptureMonitor,
+    events: &[InputEvent],
+) -> Result<Vec<u8>, String> {
+    let bounds = monitor.space().bounds(CoordinateSpace::native());
+    let contains = |event: &InputEvent| {
+        event.x >= bounds.x
+            && event.x < bounds.right()
+            && event.y >= bounds.y
+            && event.y < bounds.bottom()
+    };
+    let clicks = events
+        .iter()
+        .filter(|event| event.kind == InputEventKind::Click)
+        .collect::<Vec<_>>();
+    let clicks = &clicks[clicks.len().saturating_sub(MAX_CLICK_MARKERS)..];
+    let keys = events
+        .iter()
+        .filter(|event| event.kind == InputEventKind::Key)
+        .collect::<Vec<_>>();
+    let keys = &keys[keys.len().saturating_sub(MAX_KEY_BADGES)..];
+    let show_keys = keys.last().is_some_and(|event| contains(event));
+    if !show_keys && !clicks.iter().any(|event| contains(event)) {
+        return Ok(bytes);
+    }
+
+    let mut capture = crate::frame::decode_frame(&bytes)?;
+    let pixels_per_unit = capture.width() as f64 / bounds.width.max(1.0);
+    let pixels_per_logical = capture.width() as f64 / monitor.width.max(1.0);
+    let scale = (2.0 * pixels_per_logical).round().max(1.0) as u32;
+    let font_size = (13.0 * pixels_per_logical) as f32;
+
+    for (index, click) in clicks.iter().enumerate() {
+        if !contains(click) {
+            continue;
+        }
+        let x = (click.x - bounds.x) * pixels_per_unit;
+        let y = (click.y - bounds.y) * pixels_per_unit;
+        let color = match click.label.as_str() {
+            "Right" => image::Rgba([59, 130, 246, 220]),
+            _ => image::Rgba([239, 68, 68, 220]),
+        };
+        let radius = 14.0 * pixels_per_logical;
+        draw_ring(&mut capture, x, y, radius, 3.0 * pixels_per_logical, color);
+
+        let number = (index + 1).to_string();
+        let label_x = x + radius * 0.8;
+        let label_y = y - radius * 1.6;
+        let size = font_size.ceil() as i64 + 4 * scale as i64;
+        fill_rounded_rect(
+            &mut capture,
+            label_x.round() as i64,
+            label_y.round() as i64,
+            size,
+            size,
+            size / 2,
+            color,
+        );
+        let text_width = visible_watermark::measure_line(&number, font_size).round() as i64;
+        visible_watermark::draw_line(
+            &mut capture,
+            &number,
+            font_size,
+            label_x.round() as i64 + (size - text_width) / 2,
+            label_y.round() as i64 + 2 * scale as i64,
+            [255, 255, 255],
+        );
+    }
+
+    if show_keys {
+        let padding = 4 * scale as i64;
+        let gap = 3 * scale as i64;
+        let height = font_size.ceil() as i64 + padding * 2;
+        let widths = keys
+            .iter()
+            .map(|key| {
+                visible_watermark::measure_line(&key.label, font_size).ceil() as i64 + padding * 2
+            })
+            .collect::<Vec<_>>();
+        let total = widths.iter().sum::<i64>() + gap * (widths.len() as i64 - 1);
+        let mut left = (capture.width() as i64 - total) / 2;
+        let top = capture.height() as i64 - height - 24 * pixels_per_logical.round() as i64;
+        for (key, width) in keys.iter().zip(widths) {
+            fill_rounded_rect(
+                &mut capture,
+                left,
+                top,
+                width,
+                height,
+                3 * scale as i64,
+                image::Rgba([17, 24, 39, 215]),
+            );
+            visible_watermark::draw_line(
+                &mut capture,
+                &key.label,
+                font_size,
+                left + padding,
+                top + padding,
+                [255, 255, 255],
+            );
+            left += width + gap;
+        }
+    }
+
+    crate::frame::encode_frame(&capture, crate::frame::detect_format(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinates::Rect;
+    use crate::frame::FrameFormat;
+
+    fn event(kind: InputEventKind, label: &str, x: f64, y: f64, timestamp_ms: f64) -> InputEvent {
+        InputEvent {
+            kind,
+            label: label.into(),
+            x,
+            y,
+            coordinate_space: CoordinateSpace::native(),
+            timestamp_ms,
+        }
+    }
+
+    fn click(x: f64, y: f64) -> InputEvent {
+        event(InputEventKind::Click, "Left", x, y, 0.0)
+    }
+
+    fn key(label: &str) -> InputEvent {
+        event(InputEventKind::Key, label, 200.0, 50.0, 0.0)
+    }
+
+    fn monitor() -> CaptureMonitor {
+        CaptureMonitor::new(
+            1,
+            "Display 2".into(),
+            Rect::new(100.0, 0.0, 200.0, 100.0),
+            CoordinateSpace::native(),
+            1.0,
+            false,
+            String::new(),
+        )
+    }
+
+    fn capture() -> Vec<u8> {
+        let image = image::RgbaImage::from_pixel(200, 100, image::Rgba([255, 255, 255, 255]));
+        crate::frame::encode_frame(&image, FrameFormat::Rgba).unwrap()
+    }
+
+    fn composite(events: &[InputEvent]) -> image::RgbaImage {
+        let bytes = composite_input(capture(), &monitor(), events).unwrap();
+        crate::frame::decode_frame(&bytes).unwrap()
+    }
+
+    #[test]
+    fn records_shortcuts_and_named_keys_only() {
+        assert_eq!(key_label(&[], "A"), None);
+        assert_eq!(key_label(&["Shift"], "7"), None);
+        assert_eq!(key_label(&[], "Space"), None);
+        assert_eq!(key_label(&["Ctrl"], "C").as_deref(), Some("Ctrl+C"));
+        assert_eq!(
+            key_label(&["Ctrl", "Shift"], "S").as_deref(),
+            Some("Ctrl+Shift+S")
+        );
+        assert_eq!(key_label(&[], "Enter").as_deref(), Some("Enter"));
+        assert_eq!(key_label(&["Shift"], "Tab").as_deref(), Some("Shift+Tab"));
+    }
+
+    #[test]
+    fn recent_keeps_the_window_before_the_trigger() {
+        let triggered_at = 50_000.0;
+        ENABLED.store(true, Ordering::SeqCst);
+        {
+            let mut events = EVENTS.lock().unwrap();
+            events.clear();
+            for (label, timestamp_ms) in [
+                ("too-old", triggered_at - HISTORY_WINDOW_MS - 1.0),
+                ("oldest", triggered_at - HISTORY_WINDOW_MS),
+                ("middle", triggered_at - 2_000.0),
+                ("newest", triggered_at - TRIGGER_GUARD_MS),
+                ("trigger", triggered_at - 40.0),
+                ("later", triggered_at + 500.0),
+            ] {
+                events.push_back(event(InputEventKind::Key, label, 0.0, 0.0, timestamp_ms));
+            }
+        }
+        let labels = recent(triggered_at)
+            .into_iter()
+            .map(|event| event.label)
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["oldest", "middle", "newest"]);
+
+        ENABLED.store(false, Ordering::SeqCst);
+        assert!(recent(triggered_at).is_empty());
+        EVENTS.lock().unwrap().clear();
+    }
+
+    #[test]
+    fn input_off_the_monitor_leaves_the_capture_untouched() {
+        let bytes = capture();
+        let events = [
+            click(50.0, 50.0),
+            event(InputEventKind::Key, "Esc", 350.0, 50.0, 0.0),
+        ];
+        assert_eq!(
+            composite_input(bytes.clone(), &monitor(), &events).unwrap(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn clicks_are_drawn_relative_to_the_monitor() {
+        let image = composite(&[click(150.0, 50.0)]);
+        let ring = image.get_pixel(64, 50);
+        assert!(ring[0] > 200 && ring[1] < 150, "{:?}", ring);
+        assert_eq!(image.get_pixel(50, 50), &image::Rgba([255, 255, 255, 255]));
+        assert_eq!(image.get_pixel(150, 50), &image::Rgba([255, 255, 255, 255]));
+
+        // A click just off the monitor's left edge would reach into it, but is skipped.
+        let with_outside = composite(&[click(150.0, 50.0), click(95.0, 50.0)]);
+        assert_eq!(with_outside, image);
+        // Markers at the edge are clipped rather than wrapped.
+        let edge = composite(&[click(299.0, 99.0)]);
+        assert_eq!(edge.dimensions(), (200, 100));
+        assert_eq!(edge.get_pixel(0, 0), &image::Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn only_the_latest_badges_and_markers_are_drawn() {
+        let keys = [
+            "Ctrl+A", "Ctrl+B", "Ctrl+C", "Ctrl+D", "Ctrl+E", "Ctrl+F", "Ctrl+G",
+        ];
+        let all = keys.iter().map(|label| key(label)).collect::<Vec<_>>();
+        assert_eq!(
+            composite(&all),
+            composite(&all[keys.len() - MAX_KEY_BADGES..])
+        );
+
+        let clicks = (0..7)
+            .map(|index| click(120.0 + index as f64 * 25.0, 30.0))
+            .collect::<Vec<_>>();
+        let latest = &clicks[clicks.len() - MAX_CLICK_MARKERS..];
+        assert_eq!(composite(&clicks), composite(latest));
+        assert_ne!(composite(&clicks), composite(&clicks[..MAX_CLICK_MARKERS]));
+    }
+}
@@ -1,8 +1,8 @@
-#[cfg(not(target_os = "macos"))]
+use coordinates::{CoordinateSpace, MonitorSpace, Rect};
 use image::ImageEncoder;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::TrayIconBuilder;
 use tauri::{
@@ -14,10 +14,33 @@ use xcap::Monitor;
 #[cfg(not(target_os = "macos"))]
 use xcap::Window;
 
+mod annotation_scene;
+mod cli;
+mod clipboard;
+mod coordinates;
+mod cursor;
+mod frame;
+mod input_history;
+mod monitor_watcher;
 mod ocr;
+mod ocr_export;
+mod pdf_export;
+mod pin_store;
+mod prewarm;
+mod project;
+mod protocol;
+mod recording;
+mod redaction;
 mod translation;
+mod virtual_desktop;
+mod visible_watermark;
+mod watermark;
+mod watermark_scan;
+mod watermark_signing;
+mod watermark_sync;
 
 const SCREENSHOT_WINDOW_PREFIX: &str = "screenshot_window";
+const IMPORTED_CAPTURE_MAX_SIDE: u32 = 16384;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -31,6 +54,82 @@ struct CaptureMonitor {
     scale_factor: f64,
     is_primary: bool,
     name: String,
+    physical_x: f64,
+    physical_y: f64,
+    physical_width: f64,
+    physical_height: f64,
+}
+
+// `x`/`y`/`width`/`height` are logical, the physical_* fields are device pixels.
+impl CaptureMonitor {
+    fn new(
+        id: u32,
+        label: String,
+        bounds: Rect,
+        space: CoordinateSpace,
+        scale_factor: f64,
+        is_primary: bool,
+        name: String,
+    ) -> Self {
+        let monitor_space = MonitorSpace::new(bounds, scale_factor, space);
+        let logical = monitor_space.logical;
+        let physical = monitor_space.physical;
+
+        Self {
+            id,
+            label,
+            x: logical.x,
+            y: logical.y,
+            width: logical.width,
+            height: logical.height,
+            scale_factor: monitor_space.scale_factor,
+            is_primary,
+            name,
+            physical_x: physical.x,
+            physical_y: physical.y,
+            physical_width: physical.width,
+            physical_height: physical.height,
+        }
+    }
+
+    fn space(&self) -> MonitorSpace {
+        MonitorSpace {
+            logical: Rect::new(self.x, self.y, self.width, self.height),
+            physical: Rect::new(
+                self.physical_x,
+                self.physical_y,
+                self.physical_width,
+                self.physical_height,
+            ),
+            scale_factor: self.scale_factor,
+        }
+    }
+
+    // Per-monitor logical origins on Windows/Linux overlap on mixed-DPI desktops, so windows
+    // are placed in physical pixels there.
+    fn window_position(&self) -> tauri::Position {
+        if CoordinateSpace::native() == CoordinateSpace::Logical {
+            LogicalPosition::new(self.x, self.y).into()
+        } else {
+            tauri::PhysicalPosition::new(
+                self.physical_x.round() as i32,
+                self.physical_y.round() as i32,
+            )
+            .into()
+        }
+    }
+
+    fn window_size(&self) -> tauri::Size {
+        if CoordinateSpace::native() == CoordinateSpace::Logical {
+            LogicalSize::new(self.width, self.height).into()
+        } else {
+            tauri::PhysicalSize::new(
+                self.physical_width.round().max(1.0) as u32,
+                self.physical_height.round().max(1.0) as u32,
+            )
+            .into()
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -48,6 +147,9 @@ struct CaptureWindowRegion {
     is_focused: bool,
     title: String,
     app_name: String,
+    // Space of the rectangle and monitor size above; the frontend only relies on their ratio.
+    coordinate_space: CoordinateSpace,
+    scale_factor: f64,
 }
 
 #[derive(Debug, Serialize)]
@@ -71,18 +173,42 @@ struct LongCaptureScrollEvent {
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct PinWindowPayload {
-    image_path: String,
+    image_url: String,
+    #[serde(skip)]
+    image: Arc<protocol::ServedImage>,
     image_width: f64,
     image_height: f64,
     initial_width: f64,
     initial_height: f64,
+    opacity: f64,
+    click_through: bool,
+    locked: bool,
+    always_on_top: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PinWindowInfo {
+    label: String,
+    image_width: f64,
+    image_height: f64,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    opacity: f64,
+    click_through: bool,
+    locked: bool,
+    always_on_top: bool,
+    visible: bool,
+    persisted: bool,
 }
 
 #[derive(Default)]
 struct PinWindowStore(Mutex<HashMap<String, PinWindowPayload>>);
 
 #[derive(Default)]
-struct PreparedCaptureStore(Mutex<HashMap<(String, String), Vec<u8>>>);
+struct PreparedCaptureStore(Mutex<HashMap<(String, String), Arc<protocol::ServedImage>>>);
 
 #[derive(Default)]
 struct PreparedCaptureWindowStore(Mutex<HashMap<(String, String), Vec<CaptureWindowRegion>>>);
@@ -107,6 +233,20 @@ struct CaptureStartPayload {
     capture_id: String,
     source: String,
     triggered_at_ms: f64,
+    #[serde(flatten)]
+    frame: prewarm::FrameStamp,
+    input_events: Vec<input_history::InputEvent>,
+}
+
+/// How `start_capture` grabs the screens; anything left out takes its default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct CaptureOptions {
+    span_monitors: bool,
+    include_cursor: bool,
+    frame_format: frame::FrameFormat,
+    max_frame_age_ms: Option<f64>,
+    input_overlay: bool,
 }
 
 #[derive(Deserialize)]
@@ -204,17 +344,21 @@ fn capture_monitors() -> Result<Vec<CaptureMonitor>, String> {
         .into_iter()
         .map(|monitor| {
             let id = monitor.id().map_err(|e| e.to_string())?;
-            Ok(CaptureMonitor {
+            let bounds = Rect::new(
+                monitor.x().map_err(|e| e.to_string())? as f64,
+                monitor.y().map_err(|e| e.to_string())? as f64,
+                monitor.width().map_err(|e| e.to_string())? as f64,
+                monitor.height().map_err(|e| e.to_string())? as f64,
+            );
+            Ok(CaptureMonitor::new(
                 id,
-                label: screenshot_window_label(id),
-                x: monitor.x().map_err(|e| e.to_string())? as f64,
-                y: monitor.y().map_err(|e| e.to_string())? as f64,
-                width: monitor.width().map_err(|e| e.to_string())? as f64,
-                height: monitor.height().map_err(|e| e.to_string())? as f64,
-                scale_factor: monitor.scale_factor().unwrap_or(1.0) as f64,
-                is_primary: monitor.is_primary().unwrap_or(false),
-                name: monitor.name().unwrap_or_else(|_| format!("Display {}", id)),
-            })
+                screenshot_window_label(id),
+                bounds,
+                CoordinateSpace::native(),
+                monitor.scale_factor().unwrap_or(1.0) as f64,
+                monitor.is_primary().unwrap_or(false),
+                monitor.name().unwrap_or_else(|_| format!("Display {}", id)),
+            ))
         })
         .collect::<Result<Vec<_>, String>>()?;
 
@@ -271,10 +415,10 @@ fn configure_screenshot_window(
     monitor: &CaptureMonitor,
 ) -> Result<(), String> {
     window
-        .set_position(LogicalPosition::new(monitor.x, monitor.y))
+        .set_position(monitor.window_position())
         .map_err(|error| error.to_string())?;
     window
-        .set_size(LogicalSize::new(monitor.width, monitor.height))
+        .set_size(monitor.window_size())
         .map_err(|error| error.to_string())?;
     let _ = window.set_always_on_top(true);
     let _ = window.set_visible_on_all_workspaces(true);
@@ -475,59 +619,63 @@ fn log_capture_failure(
     );
 }
 
+fn encode_rgba_png_fast(image: &image::RgbaImage) -> Result<Vec<u8>, String> {
+    let mut bytes: Vec<u8> = Vec::new();
+
+    // 使用快速压缩以提高性能
+    let encoder = image::codecs::png::PngEncoder::new_with_quality(
+        &mut bytes,
+        image::codecs::png::CompressionType::Fast,
+        image::codecs::png::FilterType::Paeth,
+    );
+
+    encoder
+        .write_image(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            image::ColorType::Rgba8.into(),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
 #[cfg(target_os = "macos")]
 fn capture_monitor_image(
     monitor: &CaptureMonitor,
+    format: frame::FrameFormat,
     capture_id: &str,
     source: &str,
+    log: bool,
 ) -> Result<Vec<u8>, String> {
-    use std::fs;
-    use std::process::Command;
-
     let start_time = std::time::Instant::now();
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map_err(|e| e.to_string())?
-        .as_nanos();
-    let temp_file = std::env::temp_dir().join(format!(
-        "xshot_capture_screen_{}_{}_{}.png",
-        std::process::id(),
-        monitor.id,
-        timestamp
-    ));
-    let rect = format!(
-        "{},{},{},{}",
-        monitor.x.round() as i64,
-        monitor.y.round() as i64,
-        monitor.width.round().max(1.0) as i64,
-        monitor.height.round().max(1.0) as i64
-    );
-
-    let output = Command::new("screencapture")
-        .arg("-x")
-        .arg("-R")
-        .arg(&rect)
-        .arg(&temp_file)
-        .output()
-        .map_err(|e| format!("Failed to execute screencapture: {}", e))?;
+    let (bytes, method) = match capture_display_in_process(monitor.id, format) {
+        Ok(bytes) => (bytes, "coregraphics"),
+        Err(error) => {
+            println!(
+                "[xshot][capture][rust] capture_id={} source={} stage=capture_monitor_image_in_process_failed monitor={} error={:?}",
+                capture_id, source, monitor.label, error
+            );
+            // The frontend accepts PNG for any requested format.
+            let bytes = capture_rect_with_screencapture(
+                monitor.x,
+                monitor.y,
+                monitor.width,
+                monitor.height,
+            )?;
+            (bytes, "screencapture")
+        }
+    };
 
-    if !output.status.success() {
-        let _ = fs::remove_file(&temp_file);
-        return Err(format!(
-            "screencapture failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+    if !log {
+        return Ok(bytes);
     }
-
-    let bytes = fs::read(&temp_file).map_err(|e| format!("Failed to read capture file: {}", e))?;
-    let _ = fs::remove_file(temp_file);
-
     println!(
-        "[xshot][capture][rust] capture_id={} source={} stage=capture_monitor_image_detail monitor={} rect={} bytes={} elapsed_ms={:.1}",
+        "[xshot][capture][rust] capture_id={} source={} stage=capture_monitor_image_detail monitor={} method={} bytes={} elapsed_ms={:.1}",
         capture_id,
         source,
         monitor.label,
-        rect,
+        method,
         bytes.len(),
         start_time.elapsed().as_secs_f64() * 1000.0,
     );
@@ -537,8 +685,10 @@ fn capture_monitor_image(
 #[cfg(not(target_os = "macos"))]
 fn capture_monitor_image(
     monitor: &CaptureMonitor,
+    format: frame::FrameFormat,
     capture_id: &str,
     source: &str,
+    log: bool,
 ) -> Result<Vec<u8>, String> {
     let start_time = std::time::Instant::now();
     let target_monitor = Monitor::all()
@@ -547,27 +697,17 @@ fn capture_monitor_image(
         .find(|candidate| candidate.id().ok() == Some(monitor.id))
         .ok_or("No monitor found")?;
     let image = target_monitor.capture_image().map_err(|e| e.to_string())?;
-    let mut bytes: Vec<u8> = Vec::new();
-    let encoder = image::codecs::png::PngEncoder::new_with_quality(
-        &mut bytes,
-        image::codecs::png::CompressionType::Fast,
-        image::codecs::png::FilterType::Paeth,
-    );
-
-    encoder
-        .write_image(
-            image.as_raw(),
-            image.width(),
-            image.height(),
-            image::ColorType::Rgba8.into(),
-        )
-        .map_err(|e| e.to_string())?;
+    let bytes = frame::encode_frame(&image, format)?;
 
+    if !log {
+        return Ok(bytes);
+    }
     println!(
-        "[xshot][capture][rust] capture_id={} source={} stage=capture_monitor_image_detail monitor={} bytes={} elapsed_ms={:.1}",
+        "[xshot][capture][rust] capture_id={} source={} stage=capture_monitor_image_detail monitor={} format={:?} bytes={} elapsed_ms={:.1}",
         capture_id,
         source,
         monitor.label,
+        format,
         bytes.len(),
         start_time.elapsed().as_secs_f64() * 1000.0,
     );
@@ -581,52 +721,118 @@ async fn ensure_screenshot_window(app: AppHandle) -> Result<(), String> {
         return Err("No monitor found".into());
     }
 
+    sync_screenshot_windows(&app, &monitors)
+}
+
+// Windows of monitors that are gone are destroyed rather than hidden so a later monitor that
+// reuses the id gets a freshly placed window; the virtual desktop window is only hidden, and
+// left alone while a spanning capture is in progress.
+fn sync_screenshot_windows(app: &AppHandle, monitors: &[CaptureMonitor]) -> Result<(), String> {
+    // Capture start and the monitor watcher can race to create the same window.
+    static SYNC_LOCK: Mutex<()> = Mutex::new(());
+    let _guard = SYNC_LOCK
+        .lock()
+        .map_err(|_| "Failed to lock screenshot windows".to_string())?;
+
     let active_labels = monitors
         .iter()
         .map(|monitor| monitor.label.clone())
         .collect::<HashSet<_>>();
+    let virtual_capture_active = app
+        .state::<virtual_desktop::VirtualDesktopStore>()
+        .is_active();
 
-    for (label, window) in screenshot_windows(&app) {
-        if !active_labels.contains(&label) {
-            let _ = window.hide();
+    for (label, window) in screenshot_windows(app) {
+        if active_labels.contains(&label) {
+            continue;
+        }
+        if label == virtual_desktop::virtual_desktop_label() {
+            if !virtual_capture_active {
+                let _ = window.hide();
+            }
+        } else {
+            println!("Destroying screenshot window {}...", label);
+            let _ = window.destroy();
         }
     }
 
     for monitor in monitors {
-        let window = if let Some(window) = app.get_webview_window(&monitor.label) {
-            window
-        } else {
-            println!("Creating screenshot window {}...", monitor.label);
-            WebviewWindowBuilder::new(&app, &monitor.label, WebviewUrl::App("/screenshot".into()))
-                .title("Screenshot")
-                .visible(false)
-                .decorations(false)
-                .resizable(false)
-                .minimizable(false)
-                .maximizable(false)
-                .always_on_top(true)
-                .visible_on_all_workspaces(true)
-                .skip_taskbar(true)
-                .transparent(true)
-                .position(monitor.x, monitor.y)
-                .inner_size(monitor.width, monitor.height)
-                .build()
-                .map_err(|e| e.to_string())?
-        };
-
-        configure_screenshot_window(&app, &window, &monitor)?;
+        prepare_screenshot_window(app, monitor)?;
     }
 
     Ok(())
 }
 
+fn prepare_screenshot_window(
+    app: &AppHandle,
+    monitor: &CaptureMonitor,
+) -> Result<WebviewWindow, String> {
+    let window = if let Some(window) = app.get_webview_window(&monitor.label) {
+        window
+    } else {
+        println!("Creating screenshot window {}...", monitor.label);
+        WebviewWindowBuilder::new(app, &monitor.label, WebviewUrl::App("/screenshot".into()))
+            .title("Screenshot")
+            .visible(false)
+            .decorations(false)
+            .resizable(false)
+            .minimizable(false)
+            .maximizable(false)
+            .always_on_top(true)
+            .visible_on_all_workspaces(true)
+            .skip_taskbar(true)
+            .transparent(true)
+            .position(monitor.x, monitor.y)
+            .inner_size(monitor.width, monitor.height)
+            .build()
+            .map_err(|e| e.to_string())?
+    };
+
+    configure_screenshot_window(app, &window, monitor)?;
+    Ok(window)
+}
+
+type PreparedCaptures = (
+    Vec<CaptureMonitor>,
+    HashMap<(String, String), Vec<u8>>,
+    HashMap<String, Vec<CaptureWindowRegion>>,
+);
+
+// A single window spanning the whole desktop replaces the per-monitor overlays. On macOS this
+// relies on "Displays have separate Spaces" being off, otherwise the window is clipped.
+fn prepare_virtual_desktop_capture(
+    app: &AppHandle,
+    monitors: &[CaptureMonitor],
+    prepared: HashMap<(String, String), Vec<u8>>,
+    window_regions: HashMap<String, Vec<CaptureWindowRegion>>,
+    capture_id: &str,
+    frame_format: frame::FrameFormat,
+) -> Result<PreparedCaptures, String> {
+    let (layout, bytes) =
+        virtual_desktop::stitch_prepared_captures(monitors, prepared, capture_id, frame_format)?;
+    let monitor = layout.monitor();
+    prepare_screenshot_window(app, &monitor)?;
+    let regions = layout.merge_window_regions(window_regions);
+    app.state::<virtual_desktop::VirtualDesktopStore>()
+        .insert(capture_id.to_string(), layout);
+
+    Ok((
+        vec![monitor.clone()],
+        HashMap::from([((capture_id.to_string(), monitor.label.clone()), bytes)]),
+        HashMap::from([(monitor.label, regions)]),
+    ))
+}
+
 #[tauri::command]
 async fn start_capture(
     app: AppHandle,
     capture_id: Option<String>,
     source: Option<String>,
     triggered_at_ms: Option<f64>,
+    options: Option<CaptureOptions>,
 ) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+    let frame_format = options.frame_format;
     let capture_id = capture_id.unwrap_or_else(native_capture_id);
     let source = source.unwrap_or_else(|| "native".to_string());
     let triggered_at_ms = triggered_at_ms.unwrap_or_else(unix_epoch_ms);
@@ -737,23 +943,70 @@ async fn start_capture(
         (result, started.elapsed())
     });
 
+    let cursor = options
+        .include_cursor
+        .then(|| cursor::snapshot_cursor(&app))
+        .flatten()
+        .map(Arc::new);
+    let input_events = Arc::new(input_history::recent(triggered_at_ms));
+    let input_overlay = options.input_overlay && !input_events.is_empty();
+
+    let mut prewarmed = prewarm::take_fresh(
+        &app,
+        &monitors,
+        frame_format,
+        options
+            .max_frame_age_ms
+            .unwrap_or(prewarm::DEFAULT_MAX_FRAME_AGE_MS),
+    );
     let capture_handles = monitors
         .iter()
         .cloned()
         .map(|monitor| {
             let capture_id = capture_id.clone();
             let source = source.clone();
+            let cursor = cursor.clone();
+            let input_events = input_events.clone();
+            let prewarmed = prewarmed.remove(&monitor.label);
             std::thread::spawn(move || {
                 let started = std::time::Instant::now();
-                let result = capture_monitor_image(&monitor, &capture_id, &source);
-                (monitor, result, started.elapsed())
+                let (result, stamp) = match prewarmed {
+                    Some((bytes, stamp)) => (Ok(bytes), stamp),
+                    None => {
+                        let result = capture_monitor_image(
+                            &monitor,
+                            frame_format,
+                            &capture_id,
+                            &source,
+                            true,
+                        );
+                        let stamp = prewarm::FrameStamp {
+                            captured_at_ms: unix_epoch_ms(),
+                            prewarmed: false,
+                        };
+                        (result, stamp)
+                    }
+                };
+                let result = result.and_then(|bytes| match cursor.as_deref() {
+                    Some(cursor) => cursor::composite_cursor(bytes, &monitor, cursor),
+                    None => Ok(bytes),
+                });
+                let result = result.and_then(|bytes| {
+                    if input_overlay {
+                        input_history::composite_input(bytes, &monitor, &input_events)
+                    } else {
+                        Ok(bytes)
+                    }
+                });
+                (monitor, result, stamp, started.elapsed())
             })
         })
         .collect::<Vec<_>>();
 
     let mut prepared = HashMap::new();
+    let mut frame_stamps = HashMap::new();
     for handle in capture_handles {
-        let (monitor, result, elapsed) = match handle.join() {
+        let (monitor, result, stamp, elapsed) = match handle.join() {
             Ok(result) => result,
             Err(_) => {
                 let error = "Capture monitor worker panicked".to_string();
@@ -793,8 +1046,15 @@ async fn start_capture(
             total,
             elapsed.as_secs_f64() * 1000.0,
             "capture_monitor_image_parallel",
-            &format!(" monitor={} bytes={}", monitor.label, size),
+            &format!(
+                " monitor={} bytes={} prewarmed={} frame_age_ms={:.1}",
+                monitor.label,
+                size,
+                stamp.prewarmed,
+                unix_epoch_ms() - stamp.captured_at_ms
+            ),
         );
+        frame_stamps.insert(monitor.label.clone(), stamp);
     }
 
     let (window_regions_result, window_regions_elapsed) = match window_regions_handle.join() {
@@ -835,6 +1095,45 @@ async fn start_capture(
         }
     };
 
+    let (monitors, prepared, prepared_window_regions) = if options.span_monitors {
+        let stage = std::time::Instant::now();
+        match prepare_virtual_desktop_capture(
+            &app,
+            &monitors,
+            prepared,
+            prepared_window_regions,
+            &capture_id,
+            frame_format,
+        ) {
+            Ok(result) => {
+                log_capture_stage(
+                    &capture_id,
+                    &source,
+                    triggered_at_ms,
+                    total,
+                    stage,
+                    "stitch_virtual_desktop",
+                    "",
+                );
+                result
+            }
+            Err(error) => {
+                log_capture_failure(
+                    &capture_id,
+                    &source,
+                    triggered_at_ms,
+                    total,
+                    stage,
+                    "stitch_virtual_desktop",
+                    &error,
+                );
+                return Err(error);
+            }
+        }
+    } else {
+        (monitors, prepared, prepared_window_regions)
+    };
+
     let stage = std::time::Instant::now();
     {
         let store = app.state::<PreparedCaptureStore>();
@@ -854,7 +1153,11 @@ async fn start_capture(
                 return Err(error);
             }
         };
-        store.extend(prepared);
+        store.extend(
+            prepared
+                .into_iter()
+                .map(|(key, bytes)| (key, protocol::ServedImage::new(bytes))),
+        );
     }
     log_capture_stage(
         &capture_id,
@@ -889,6 +1192,14 @@ async fn start_capture(
         "",
     );
 
+    // The stitched virtual desktop is as old as its oldest segment.
+    let oldest_stamp = prewarm::FrameStamp {
+        captured_at_ms: frame_stamps
+            .values()
+            .map(|stamp| stamp.captured_at_ms)
+            .fold(unix_epoch_ms(), f64::min),
+        prewarmed: frame_stamps.values().any(|stamp| stamp.prewarmed),
+    };
     let stage = std::time::Instant::now();
     for monitor in &monitors {
         let payload = CaptureStartPayload {
@@ -896,6 +1207,11 @@ async fn start_capture(
             capture_id: capture_id.clone(),
             source: source.clone(),
             triggered_at_ms,
+            frame: frame_stamps
+                .get(&monitor.label)
+                .copied()
+                .unwrap_or(oldest_stamp),
+            input_events: input_events.to_vec(),
         };
         if let Err(error) = app.emit("start-capture", payload) {
             let error = error.to_string();
@@ -960,16 +1276,241 @@ async fn finish_capture(app: AppHandle) -> Result<(), String> {
 
     {
         let store = app.state::<PreparedCaptureStore>();
-        let _ = store.0.lock().map(|mut captures| captures.clear());
+        let _ = store.0.lock().map(|mut captures| {
+            captures.retain(|(capture_id, _), _| capture_id == prewarm::PREWARM_CAPTURE_ID)
+        });
     }
     {
         let store = app.state::<PreparedCaptureWindowStore>();
         let _ = store.0.lock().map(|mut windows| windows.clear());
     }
+    app.state::<virtual_desktop::VirtualDesktopStore>().clear();
 
     hide_screenshot_windows(&app)
 }
 
+fn monitor_under_cursor(app: &AppHandle) -> Result<CaptureMonitor, String> {
+    let monitors = capture_monitors()?;
+    let space = CoordinateSpace::native();
+    cursor::cursor_position(app)
+        .and_then(|(x, y)| {
+            monitors
+                .iter()
+                .find(|monitor| monitor.space().bounds(space).contains(x, y))
+                .cloned()
+        })
+        .or_else(|| monitors.iter().find(|monitor| monitor.is_primary).cloned())
+        .or_else(|| monitors.into_iter().next())
+        .ok_or_else(|| "No monitor found".to_string())
+}
+
+fn compose_imported_capture(
+    monitor: &CaptureMonitor,
+    image: &image::RgbaImage,
+) -> (image::RgbaImage, CaptureWindowRegion, project::CropRect) {
+    // The editor canvas is backed by device pixels, like a real capture. Images larger than
+    // the monitor get a proportionally larger frame instead of being resampled; the editor
+    // scales the frame to the window, so only the view is zoomed out.
+    let space = monitor.space();
+    let monitor_width = space.physical.width.max(1.0);
+    let monitor_height = space.physical.height.max(1.0);
+    let frame_scale = f64::max(
+        1.0,
+        f64::max(
+            image.width() as f64 / (monitor_width * 0.9),
+            image.height() as f64 / (monitor_height * 0.9),
+        ),
+    );
+    let limit_scale = f64::min(
+        IMPORTED_CAPTURE_MAX_SIDE as f64 / (monitor_width * frame_scale),
+        IMPORTED_CAPTURE_MAX_SIDE as f64 / (monitor_height * frame_scale),
+    );
+    let fitted = if limit_scale < 1.0 {
+        // Past the webview's canvas limit the image has to be resampled after all.
+        image::imageops::resize(
+            image,
+            ((image.width() as f64 * limit_scale).round() as u32).max(1),
+            ((image.height() as f64 * limit_scale).round() as u32).max(1),
+            image::imageops::FilterType::Lanczos3,
+        )
+    } else {
+        image.clone()
+    };
+    let frame_scale = frame_scale * limit_scale.min(1.0);
+    let canvas_width = ((monitor_width * frame_scale).round() as u32).max(fitted.width());
+    let canvas_height = ((monitor_height * frame_scale).round() as u32).max(fitted.height());
+    let left = (canvas_width - fitted.width()) / 2;
+    let top = (canvas_height - fitted.height()) / 2;
+
+    let mut canvas =
+        image::RgbaImage::from_pixel(canvas_width, canvas_height, image::Rgba([30, 30, 30, 255]));
+    image::imageops::overlay(&mut canvas, &fitted, left as i64, top as i64);
+
+    let bounds = space.convert_local(
+        Rect::new(
+            left as f64,
+            top as f64,
+            fitted.width() as f64,
+            fitted.height() as f64,
+        )
+        .scale(1.0 / frame_scale),
+        CoordinateSpace::Physical,
+        CoordinateSpace::Logical,
+    );
+    let region = CaptureWindowRegion {
+        id: 0,
+        pid: std::process::id(),
+        x: bounds.x,
+        y: bounds.y,
+        width: bounds.width,
+        height: bounds.height,
+        monitor_width: monitor.width,
+        monitor_height: monitor.height,
+        is_fullscreen_like: false,
+        is_overlay_candidate: false,
+        is_focused: true,
+        title: "Imported image".to_string(),
+        app_name: "xshot".to_string(),
+        coordinate_space: CoordinateSpace::Logical,
+        scale_factor: monitor.scale_factor,
+    };
+    let placed = project::CropRect {
+        x: left,
+        y: top,
+        width: fitted.width(),
+        height: fitted.height(),
+    };
+
+    (canvas, region, placed)
+}
+
+async fn start_imported_capture(
+    app: AppHandle,
+    image: image::RgbaImage,
+    source: &str,
+    project: Option<project::ProjectDocument>,
+) -> Result<(), String> {
+    let capture_id = native_capture_id();
+    let triggered_at_ms = unix_epoch_ms();
+    let start_time = std::time::Instant::now();
+
+    ensure_screenshot_window(app.clone()).await?;
+    #[cfg(target_os = "macos")]
+    {
+        let _ = stop_long_capture_scroll_monitor(app.clone()).await;
+        let _ = set_screenshot_window_ignores_mouse_events(&app, None, false);
+    }
+    hide_screenshot_windows(&app)?;
+
+    let monitor = monitor_under_cursor(&app)?;
+    let (canvas, region, placed) = compose_imported_capture(&monitor, &image);
+    let bytes = encode_rgba_png_fast(&canvas)?;
+    let size = bytes.len();
+
+    app.state::<PreparedCaptureStore>()
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock prepared capture store".to_string())?
+        .insert(
+            (capture_id.clone(), monitor.label.clone()),
+            protocol::ServedImage::new(bytes),
+        );
+    app.state::<PreparedCaptureWindowStore>()
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock prepared capture window store".to_string())?
+        .insert((capture_id.clone(), monitor.label.clone()), vec![region]);
+    if let Some(document) = project {
+        let frame_crop = project::frame_crop(&document, &image, placed);
+        app.state::<project::PreparedProjectStore>().insert(
+            capture_id.clone(),
+            project::PreparedProject {
+                document,
+                frame_crop,
+            },
+        )?;
+    }
+    let (image_width, image_height) = image.dimensions();
+    app.state::<project::ImportedOriginalStore>()
+        .replace(capture_id.clone(), image, placed)?;
+
+    app.emit(
+        "start-capture",
+        CaptureStartPayload {
+            monitor: monitor.clone(),
+            capture_id: capture_id.clone(),
+            source: source.to_string(),
+            triggered_at_ms,
+            frame: prewarm::FrameStamp {
+                captured_at_ms: triggered_at_ms,
+                prewarmed: false,
+            },
+            input_events: Vec::new(),
+        },
+    )
+    .map_err(|error| error.to_string())?;
+
+    println!(
+        "[xshot][capture][rust] capture_id={} source={} stage=prepared_image_import monitor={} image={}x{} bytes={} elapsed_ms={:.1}",
+        capture_id,
+        source,
+        monitor.label,
+        image_width,
+        image_height,
+        size,
+        start_time.elapsed().as_secs_f64() * 1000.0,
+    );
+
+    #[cfg(target_os = "macos")]
+    start_capture_focus_follower(app, vec![monitor]);
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn open_clipboard_image_in_editor(app: AppHandle) -> Result<(), String> {
+    let image = clipboard::read_image(&app)?;
+    start_imported_capture(app, image, "clipboard", None).await
+}
+
+#[tauri::command]
+async fn open_image_file_in_editor(app: AppHandle, path: String) -> Result<(), String> {
+    let image = image::open(&path)
+        .map_err(|e| format!("Failed to open image {}: {}", path, e))?
+        .to_rgba8();
+    start_imported_capture(app, image, "file", None).await
+}
+
+#[tauri::command]
+async fn open_project_in_editor(app: AppHandle, path: String) -> Result<(), String> {
+    let project =
+        tauri::async_runtime::spawn_blocking(move || project::load(std::path::Path::new(&path)))
+            .await
+            .map_err(|e| format!("Project load task failed: {}", e))??;
+    start_imported_capture(app, project.original, "project", Some(project.document)).await
+}
+
+fn pick_project_file_to_edit(app: &AppHandle) {
+    use tauri_plugin_dialog::DialogExt;
+
+    let app_handle = app.clone();
+    app.dialog()
+        .file()
+        .set_title("Open Project")
+        .add_filter("xshot Project", &[project::PROJECT_EXTENSION])
+        .pick_file(move |path| {
+            let Some(path) = path.and_then(|path| path.into_path().ok()) else {
+                return;
+            };
+            tauri::async_runtime::spawn(async move {
+                let path = path.to_string_lossy().to_string();
+                if let Err(error) = open_project_in_editor(app_handle, path).await {
+                    eprintln!("Failed to open project: {}", error);
+                }
+            });
+        });
+}
+
 fn show_main_window(app: &AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.unminimize();
@@ -1019,18 +1560,24 @@ async fn capture_fullscreen(
     window_label: Option<String>,
     capture_id: Option<String>,
     source: Option<String>,
+    refresh: Option<bool>,
 ) -> Result<tauri::ipc::Response, String> {
     let capture_id = capture_id.unwrap_or_else(|| "legacy".to_string());
     let source = source.unwrap_or_else(|| "unknown".to_string());
     let start_time = std::time::Instant::now();
     if let Some(label) = window_label.as_deref() {
+        // A stitched virtual desktop has no single monitor to grab again.
+        let refresh = refresh.unwrap_or(false) && label != virtual_desktop::virtual_desktop_label();
         let prepared = {
             let store = app.state::<PreparedCaptureStore>();
             let mut captures = store
                 .0
                 .lock()
                 .map_err(|_| "Failed to lock prepared capture store".to_string())?;
-            captures.remove(&(capture_id.clone(), label.to_string()))
+            captures
+                .remove(&(capture_id.clone(), label.to_string()))
+                .filter(|_| !refresh)
+                .map(protocol::ServedImage::into_bytes)
         };
         if let Some(bytes) = prepared {
             println!(
@@ -1044,25 +1591,13 @@ async fn capture_fullscreen(
             return Ok(tauri::ipc::Response::new(bytes));
         }
         println!(
-            "[xshot][capture][rust] capture_id={} source={} stage=capture_fullscreen_prepared_miss monitor={}",
-            capture_id, source, label
+            "[xshot][capture][rust] capture_id={} source={} stage=capture_fullscreen_prepared_miss monitor={} refresh={}",
+            capture_id, source, label, refresh
         );
     }
 
     #[cfg(target_os = "macos")]
     {
-        use std::fs;
-        use std::process::Command;
-
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| e.to_string())?
-            .as_nanos();
-        let temp_file = std::env::temp_dir().join(format!(
-            "xshot_capture_screen_{}_{}.png",
-            std::process::id(),
-            timestamp
-        ));
         let fallback_label = first_screenshot_window_label(&app)
             .unwrap_or_else(|| SCREENSHOT_WINDOW_PREFIX.to_string());
         let target_label = window_label.unwrap_or(fallback_label);
@@ -1080,41 +1615,18 @@ async fn capture_fullscreen(
             .inner_size()
             .map_err(|e| e.to_string())?
             .to_logical::<f64>(scale_factor);
-        let rect = format!(
-            "{},{},{},{}",
-            position.x.round() as i64,
-            position.y.round() as i64,
-            size.width.round().max(1.0) as i64,
-            size.height.round().max(1.0) as i64
-        );
-
-        let output = Command::new("screencapture")
-            .arg("-x")
-            .arg("-R")
-            .arg(&rect)
-            .arg(&temp_file)
-            .output()
-            .map_err(|e| format!("Failed to execute screencapture: {}", e))?;
-
-        if !output.status.success() {
-            return Err(format!(
-                "screencapture failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
-
-        let bytes =
-            fs::read(&temp_file).map_err(|e| format!("Failed to read capture file: {}", e))?;
-
-        // 删除临时文件
-        let _ = fs::remove_file(temp_file);
+        let (bytes, method) = capture_rect_png(position.x, position.y, size.width, size.height)?;
 
         println!(
-            "[xshot][capture][rust] capture_id={} source={} stage=capture_fullscreen_fallback monitor={} rect={} bytes={} elapsed_ms={:.1}",
+            "[xshot][capture][rust] capture_id={} source={} stage=capture_fullscreen_fallback monitor={} rect={},{},{},{} method={} bytes={} elapsed_ms={:.1}",
             capture_id,
             source,
             target_label,
-            rect,
+            position.x.round() as i64,
+            position.y.round() as i64,
+            size.width.round().max(1.0) as i64,
+            size.height.round().max(1.0) as i64,
+            method,
             bytes.len(),
             start_time.elapsed().as_secs_f64() * 1000.0,
         );
@@ -1136,24 +1648,7 @@ async fn capture_fullscreen(
             .or_else(|| monitors.first())
             .ok_or("No monitor found")?;
         let image = monitor.capture_image().map_err(|e| e.to_string())?;
-
-        let mut bytes: Vec<u8> = Vec::new();
-
-        // 使用快速压缩以提高性能
-        let encoder = image::codecs::png::PngEncoder::new_with_quality(
-            &mut bytes,
-            image::codecs::png::CompressionType::Fast,
-            image::codecs::png::FilterType::Paeth,
-        );
-
-        encoder
-            .write_image(
-                image.as_raw(),
-                image.width(),
-                image.height(),
-                image::ColorType::Rgba8.into(),
-            )
-            .map_err(|e| e.to_string())?;
+        let bytes = encode_rgba_png_fast(&image)?;
 
         println!(
             "[xshot][capture][rust] capture_id={} source={} stage=capture_fullscreen_fallback monitor={} bytes={} elapsed_ms={:.1}",
@@ -1178,53 +1673,19 @@ async fn capture_screen_rect(
 
     #[cfg(target_os = "macos")]
     {
-        use std::fs;
-        use std::process::Command;
-
         if width <= 0.0 || height <= 0.0 {
             return Err("Invalid capture rectangle".into());
         }
 
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| e.to_string())?
-            .as_nanos();
-        let temp_file = std::env::temp_dir().join(format!(
-            "xshot_capture_rect_{}_{}.png",
-            std::process::id(),
-            timestamp
-        ));
-        let rect = format!(
-            "{},{},{},{}",
+        let (bytes, method) = capture_rect_png(x, y, width, height)?;
+
+        println!(
+            "Capture rect {},{},{},{} via {} finished in {:?}",
             x.round() as i64,
             y.round() as i64,
             width.round().max(1.0) as i64,
-            height.round().max(1.0) as i64
-        );
-
-        let output = Command::new("screencapture")
-            .arg("-x")
-            .arg("-R")
-            .arg(&rect)
-            .arg(&temp_file)
-            .output()
-            .map_err(|e| format!("Failed to execute screencapture: {}", e))?;
-
-        if !output.status.success() {
-            let _ = fs::remove_file(&temp_file);
-            return Err(format!(
-                "screencapture rect failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
-
-        let bytes =
-            fs::read(&temp_file).map_err(|e| format!("Failed to read capture file: {}", e))?;
-        let _ = fs::remove_file(temp_file);
-
-        println!(
-            "Capture rect {} finished in {:?}",
-            rect,
+            height.round().max(1.0) as i64,
+            method,
             start_time.elapsed()
         );
         Ok(tauri::ipc::Response::new(bytes))
@@ -1340,6 +1801,179 @@ fn encode_cg_image_to_png_bytes(image: &core_graphics::image::CGImage) -> Result
     }
 }
 
+#[cfg(target_os = "macos")]
+fn cg_image_to_rgba(image: &core_graphics::image::CGImage) -> Result<image::RgbaImage, String> {
+    let width = image.width();
+    let height = image.height();
+    let stride = image.bytes_per_row();
+    if image.bits_per_pixel() != 32 || stride < width * 4 {
+        return Err("Unsupported CoreGraphics pixel layout".into());
+    }
+
+    use core_graphics::base::{
+        kCGBitmapByteOrder32Little, kCGImageAlphaFirst, kCGImageAlphaLast,
+        kCGImageAlphaNoneSkipFirst, kCGImageAlphaPremultipliedFirst,
+        kCGImageAlphaPremultipliedLast,
+    };
+    use foreign_types::ForeignTypeRef;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    unsafe extern "C" {
+        fn CGImageGetBitmapInfo(image: core_graphics::sys::CGImageRef) -> u32;
+    }
+
+    const ALPHA_INFO_MASK: u32 = 0x1f;
+    const BYTE_ORDER_MASK: u32 = 0x7000;
+    let bitmap_info = unsafe { CGImageGetBitmapInfo(image.as_ptr()) };
+    let alpha_info = bitmap_info & ALPHA_INFO_MASK;
+    let little_endian = bitmap_info & BYTE_ORDER_MASK == kCGBitmapByteOrder32Little;
+    let alpha_first = matches!(
+        alpha_info,
+        kCGImageAlphaPremultipliedFirst | kCGImageAlphaFirst | kCGImageAlphaNoneSkipFirst
+    );
+    let premultiplied = matches!(
+        alpha_info,
+        kCGImageAlphaPremultipliedFirst | kCGImageAlphaPremultipliedLast
+    );
+    let has_alpha = premultiplied || matches!(alpha_info, kCGImageAlphaFirst | kCGImageAlphaLast);
+    // Byte offsets of R, G, B and A within a pixel. Display images are usually BGRX: the skipped
+    // byte is undefined, so it must not be read as alpha.
+    let [r, g, b, a] = match (little_endian, alpha_first) {
+        (true, true) => [2, 1, 0, 3],
+        (true, false) => [3, 2, 1, 0],
+        (false, true) => [1, 2, 3, 0],
+        (false, false) => [0, 1, 2, 3],
+    };
+
+    let data = image.data();
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for row in data.bytes().chunks(stride).take(height) {
+        for pixel in row[..width * 4].chunks_exact(4) {
+            let alpha = if has_alpha { pixel[a] } else { 255 };
+            let unpremultiply = |channel: u8| {
+                if premultiplied && alpha > 0 && alpha < 255 {
+                    ((channel as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8
+                } else {
+                    channel
+                }
+            };
+            pixels.extend_from_slice(&[
+                unpremultiply(pixel[r]),
+                unpremultiply(pixel[g]),
+                unpremultiply(pixel[b]),
+                alpha,
+            ]);
+        }
+    }
+    image::RgbaImage::from_raw(width as u32, height as u32, pixels)
+        .ok_or_else(|| "CoreGraphics image is truncated".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn capture_display_in_process(
+    display_id: u32,
+    format: frame::FrameFormat,
+) -> Result<Vec<u8>, String> {
+    let image = core_graphics::display::CGDisplay::new(display_id)
+        .image()
+        .ok_or("CoreGraphics returned no display image")?;
+    match format {
+        frame::FrameFormat::Png => encode_cg_image_to_png_bytes(&image),
+        format => frame::encode_frame(&cg_image_to_rgba(&image)?, format),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn capture_rect_in_process(x: f64, y: f64, width: f64, height: f64) -> Result<Vec<u8>, String> {
+    use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+    use core_graphics::window::{
+        create_image, kCGNullWindowID, kCGWindowImageDefault, kCGWindowListOptionOnScreenOnly,
+    };
+
+    let rect = CGRect::new(
+        &CGPoint::new(x.round(), y.round()),
+        &CGSize::new(width.round().max(1.0), height.round().max(1.0)),
+    );
+    let image = create_image(
+        rect,
+        kCGWindowListOptionOnScreenOnly,
+        kCGNullWindowID,
+        kCGWindowImageDefault,
+    )
+    .ok_or("CoreGraphics returned no screen image")?;
+    encode_cg_image_to_png_bytes(&image)
+}
+
+#[cfg(target_os = "macos")]
+fn capture_rect_with_screencapture(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) -> Result<Vec<u8>, String> {
+    use std::fs;
+    use std::process::Command;
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_nanos();
+    let temp_file = std::env::temp_dir().join(format!(
+        "xshot_capture_rect_{}_{}.png",
+        std::process::id(),
+        timestamp
+    ));
+    let rect = format!(
+        "{},{},{},{}",
+        x.round() as i64,
+        y.round() as i64,
+        width.round().max(1.0) as i64,
+        height.round().max(1.0) as i64
+    );
+
+    let output = Command::new("screencapture")
+        .arg("-x")
+        .arg("-R")
+        .arg(&rect)
+        .arg(&temp_file)
+        .output()
+        .map_err(|e| format!("Failed to execute screencapture: {}", e))?;
+
+    if !output.status.success() {
+        let _ = fs::remove_file(&temp_file);
+        return Err(format!(
+            "screencapture failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let bytes = fs::read(&temp_file).map_err(|e| format!("Failed to read capture file: {}", e))?;
+    let _ = fs::remove_file(temp_file);
+    Ok(bytes)
+}
+
+// `screencapture` stays as a fallback for systems where CoreGraphics window-list capture is
+// unavailable; it costs a process spawn plus a temp file round trip.
+#[cfg(target_os = "macos")]
+fn capture_rect_png(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) -> Result<(Vec<u8>, &'static str), String> {
+    match capture_rect_in_process(x, y, width, height) {
+        Ok(bytes) => Ok((bytes, "coregraphics")),
+        Err(error) => {
+            eprintln!(
+                "In-process capture failed, falling back to screencapture: {}",
+                error
+            );
+            capture_rect_with_screencapture(x, y, width, height)
+                .map(|bytes| (bytes, "screencapture"))
+        }
+    }
+}
+
 #[tauri::command]
 async fn capture_screen_rect_below_screenshot_window(
     app: AppHandle,
@@ -1528,13 +2162,12 @@ fn capture_window_regions(
     ];
     let mut regions_by_monitor = HashMap::with_capacity(monitors.len());
 
+    // Window snapshots come from xcap in the platform's native space, so regions are computed
+    // in that space too instead of mixing them with logical monitor bounds.
+    let space = CoordinateSpace::native();
     for monitor in monitors {
-        let monitor_x = monitor.x.round() as i32;
-        let monitor_y = monitor.y.round() as i32;
-        let monitor_width = monitor.width.round().max(1.0) as i32;
-        let monitor_height = monitor.height.round().max(1.0) as i32;
-        let monitor_right = monitor_x + monitor_width;
-        let monitor_bottom = monitor_y + monitor_height;
+        let monitor_space = monitor.space();
+        let monitor_bounds = monitor_space.bounds(space);
         let mut regions = Vec::new();
 
         for window in &windows {
@@ -1548,38 +2181,41 @@ fn capture_window_regions(
                 continue;
             }
 
-            let left = window.x.max(monitor_x);
-            let top = window.y.max(monitor_y);
-            let right = (window.x + window.width).min(monitor_right);
-            let bottom = (window.y + window.height).min(monitor_bottom);
-            let clipped_width = right - left;
-            let clipped_height = bottom - top;
-            if clipped_width < 40 || clipped_height < 40 {
+            let window_bounds = Rect::new(
+                window.x as f64,
+                window.y as f64,
+                window.width as f64,
+                window.height as f64,
+            );
+            let Some(clipped) = monitor_space.clip_local(window_bounds, space) else {
+                continue;
+            };
+            if clipped.width < 40.0 || clipped.height < 40.0 {
                 continue;
             }
 
-            let monitor_area = monitor_width as f64 * monitor_height as f64;
-            let window_area = clipped_width as f64 * clipped_height as f64;
-            let covers_whole_monitor = window_area / monitor_area > 0.92
-                && clipped_width as f64 >= monitor_width as f64 * 0.96
-                && clipped_height as f64 >= monitor_height as f64 * 0.92;
+            let covers_whole_monitor = clipped.area() / monitor_bounds.area().max(1.0) > 0.92
+                && clipped.width >= monitor_bounds.width * 0.96
+                && clipped.height >= monitor_bounds.height * 0.92;
             let is_overlay_candidate =
                 covers_whole_monitor && window.title.trim().is_empty() && !window.is_focused;
 
             regions.push(CaptureWindowRegion {
                 id: window.id,
                 pid: window.pid,
-                x: (left - monitor_x) as f64,
-                y: (top - monitor_y) as f64,
-                width: clipped_width as f64,
-                height: clipped_height as f64,
-                monitor_width: monitor_width as f64,
-                monitor_height: monitor_height as f64,
+                x: clipped.x,
+                y: clipped.y,
+                width: clipped.width,
+                height: clipped.height,
+                monitor_width: monitor_bounds.width.max(1.0),
+                monitor_height: monitor_bounds.height.max(1.0),
                 is_fullscreen_like: covers_whole_monitor,
                 is_overlay_candidate,
                 is_focused: window.is_focused,
                 title: window.title.clone(),
                 app_name: window.app_name.clone(),
+                coordinate_space: space,
+                scale_factor: monitor_space.scale_factor,
             });
         }
         regions_by_monitor.insert(monitor.label.clone(), regions);
@@ -1656,7 +2292,15 @@ fn record_capture_ui_timing(timing: CaptureUiTiming) {
 }
 
 #[tauri::command]
-async fn copy_to_clipboard(app: AppHandle, blob_data: Vec<u8>) -> Result<(), String> {
+async fn copy_to_clipboard(
+    app: AppHandle,
+    blob_data: Vec<u8>,
+    formats: Option<clipboard::ClipboardFormats>,
+) -> Result<(), String> {
+    if let Some(formats) = formats {
+        return clipboard::write_image_formats(&app, &blob_data, formats);
+    }
+
     // Decode the image from memory (detects format automatically, e.g. PNG)
     let img = image::load_from_memory(&blob_data)
         .map_err(|e| format!("Failed to decode image: {}", e))?;
@@ -1682,20 +2326,14 @@ async fn copy_text_to_clipboard(app: AppHandle, text: String) -> Result<(), Stri
         .map_err(|e| format!("Failed to write text to clipboard: {}", e))
 }
 
-#[tauri::command]
-async fn save_to_downloads(
-    blob_data: Vec<u8>,
-    directory: Option<String>,
-) -> Result<String, String> {
-    use std::fs;
+/// The configured save directory, falling back to ~/Downloads and then the temp directory.
+fn downloads_directory(directory: Option<String>) -> std::path::PathBuf {
     use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
 
-    let configured_dir = directory
+    directory
         .filter(|path| !path.trim().is_empty())
         .map(PathBuf::from)
-        .filter(|path| path.is_dir());
-    let downloads_dir = configured_dir
+        .filter(|path| path.is_dir())
         .or_else(|| {
             std::env::var_os("HOME")
                 .map(PathBuf::from)
@@ -1703,7 +2341,18 @@ async fn save_to_downloads(
                 .map(|home| home.join("Downloads"))
                 .filter(|path| path.is_dir())
         })
-        .unwrap_or_else(std::env::temp_dir);
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+#[tauri::command]
+async fn save_to_downloads(
+    blob_data: Vec<u8>,
+    directory: Option<String>,
+) -> Result<String, String> {
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let downloads_dir = downloads_directory(directory);
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| e.to_string())?
@@ -1714,10 +2363,6 @@ async fn save_to_downloads(
     Ok(path.to_string_lossy().to_string())
 }
 
-fn pin_window_temp_dir() -> std::path::PathBuf {
-    std::env::temp_dir().join("xshot-pins")
-}
-
 fn compute_pin_window_size(
     app: &AppHandle,
     image_width: f64,
@@ -1771,50 +2416,86 @@ fn compute_pin_window_size(
 
 fn cleanup_pin_payload(app: &AppHandle, label: &str) {
     let store = app.state::<PinWindowStore>();
-    let payload = store
-        .0
-        .lock()
-        .ok()
-        .and_then(|mut payloads| payloads.remove(label));
+    let _ = store.0.lock().map(|mut payloads| payloads.remove(label));
 
-    if let Some(payload) = payload {
-        let _ = std::fs::remove_file(payload.image_path);
+    if let Err(error) = app.state::<pin_store::PersistentPinStore>().remove(label) {
+        eprintln!("Failed to forget pinned window {}: {}", label, error);
     }
 }
 
-#[tauri::command]
-async fn show_pin_window(
-    app: AppHandle,
-    blob_data: Vec<u8>,
-    window_label: Option<String>,
+fn pin_windows(app: &AppHandle) -> Vec<(String, WebviewWindow)> {
+    app.webview_windows()
+        .into_iter()
+        .filter(|(label, _)| label.starts_with("pin_window_"))
+        .collect()
+}
+
+fn restored_pin_geometry(app: &AppHandle, record: &pin_store::PinRecord) -> Option<(f64, f64)> {
+    let center_x = record.x + record.width / 2.0;
+    let center_y = record.y + record.height / 2.0;
+    let on_screen = app.available_monitors().ok()?.iter().any(|monitor| {
+        let scale_factor = monitor.scale_factor();
+        let position = monitor.position().to_logical::<f64>(scale_factor);
+        let size = monitor.size().to_logical::<f64>(scale_factor);
+        center_x >= position.x
+            && center_x < position.x + size.width
+            && center_y >= position.y
+            && center_y < position.y + size.height
+    });
+
+    on_screen.then_some((record.x, record.y))
+}
+
+fn open_pin_window(
+    app: &AppHandle,
+    png_bytes: &[u8],
+    image_width: f64,
+    image_height: f64,
+    source_window_label: Option<&str>,
+    restored: Option<&pin_store::PinRecord>,
 ) -> Result<(), String> {
-    use std::fs;
     use std::time::{SystemTime, UNIX_EPOCH};
 
-    let image = image::load_from_memory(&blob_data)
-        .map_err(|e| format!("Failed to decode pinned image: {}", e))?;
-    let image_width = image.width() as f64;
-    let image_height = image.height() as f64;
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_err(|e| e.to_string())?
-        .as_millis();
-    let label = format!("pin_window_{}_{}", std::process::id(), timestamp);
-    let pin_dir = pin_window_temp_dir();
-    fs::create_dir_all(&pin_dir).map_err(|e| format!("Failed to prepare pin directory: {}", e))?;
-    let image_path = pin_dir.join(format!("{}.png", label));
-    fs::write(&image_path, &blob_data)
-        .map_err(|e| format!("Failed to write pinned image: {}", e))?;
-
-    let (initial_width, initial_height, x, y) =
-        compute_pin_window_size(&app, image_width, image_height, window_label.as_deref());
-    let payload = PinWindowPayload {
-        image_path: image_path.to_string_lossy().to_string(),
+    let label = match restored {
+        Some(record) => record.label.clone(),
+        None => {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| e.to_string())?
+                .as_millis();
+            format!("pin_window_{}_{}", std::process::id(), timestamp)
+        }
+    };
+
+    let (initial_width, initial_height, mut x, mut y) =
+        compute_pin_window_size(app, image_width, image_height, source_window_label);
+    let mut payload = PinWindowPayload {
+        image_url: protocol::pin_url(&label),
+        image: protocol::ServedImage::new(png_bytes.to_vec()),
         image_width,
         image_height,
         initial_width,
         initial_height,
+        opacity: 1.0,
+        click_through: false,
+        locked: false,
+        always_on_top: true,
     };
+    if let Some(record) = restored {
+        payload.initial_width = record.width.max(96.0);
+        payload.initial_height = record.height.max(64.0);
+        payload.opacity = record.opacity.clamp(0.1, 1.0);
+        payload.click_through = record.click_through;
+        payload.locked = record.locked;
+        payload.always_on_top = record.always_on_top;
+        if let Some((restored_x, restored_y)) = restored_pin_geometry(app, record) {
+            x = restored_x;
+            y = restored_y;
+        }
+    }
+    let (width, height) = (payload.initial_width, payload.initial_height);
+    let click_through = payload.click_through;
+    let always_on_top = payload.always_on_top;
 
     {
         let store = app.state::<PinWindowStore>();
@@ -1825,33 +2506,206 @@ async fn show_pin_window(
             .insert(label.clone(), payload);
     }
 
-    let build_result = WebviewWindowBuilder::new(&app, &label, WebviewUrl::App("/pin".into()))
+    let build_result = WebviewWindowBuilder::new(app, &label, WebviewUrl::App("/pin".into()))
         .title("Pinned Screenshot")
         .visible(false)
         .decorations(false)
         .resizable(false)
         .minimizable(false)
         .maximizable(false)
-        .always_on_top(true)
+        .always_on_top(always_on_top)
         .visible_on_all_workspaces(true)
         .skip_taskbar(true)
         .transparent(true)
         .shadow(true)
-        .inner_size(initial_width, initial_height)
+        .inner_size(width, height)
         .min_inner_size(96.0, 64.0)
         .position(x, y)
         .build();
 
-    match build_result {
-        Ok(window) => {
-            let _ = window.set_always_on_top(true);
-            Ok(())
-        }
+    let window = match build_result {
+        Ok(window) => window,
         Err(error) => {
-            cleanup_pin_payload(&app, &label);
-            Err(error.to_string())
+            cleanup_pin_payload(app, &label);
+            return Err(error.to_string());
+        }
+    };
+    let _ = window.set_always_on_top(always_on_top);
+    if click_through {
+        let _ = set_pin_window_ignores_mouse_events(app, &window, true);
+    }
+
+    if restored.is_none() {
+        let monitor = window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .and_then(|monitor| monitor.name().cloned());
+        let record = pin_store::PinRecord {
+            image_file: format!("{}.png", label),
+            label,
+            image_width,
+            image_height,
+            x,
+            y,
+            width,
+            height,
+            opacity: 1.0,
+            click_through: false,
+            locked: false,
+            always_on_top: true,
+            monitor,
+        };
+        if let Err(error) = app
+            .state::<pin_store::PersistentPinStore>()
+            .insert(record, png_bytes)
+        {
+            eprintln!("Failed to persist pinned window: {}", error);
         }
     }
+
+    Ok(())
+}
+
+fn restore_pin_windows(app: &AppHandle) {
+    let store = app.state::<pin_store::PersistentPinStore>();
+    if !store.is_enabled() {
+        return;
+    }
+
+    for record in store.records() {
+        let restored = std::fs::read(store.image_path(&record))
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| {
+                open_pin_window(
+                    app,
+                    &bytes,
+                    record.image_width,
+                    record.image_height,
+                    None,
+                    Some(&record),
+                )
+            });
+        if let Err(error) = restored {
+            eprintln!(
+                "Failed to restore pinned window {}: {}",
+                record.label, error
+            );
+            let _ = store.remove(&record.label);
+        }
+    }
+}
+
+fn track_pin_window_geometry(window: &tauri::Window) {
+    let app = window.app_handle();
+    let store = app.state::<pin_store::PersistentPinStore>();
+    if !store.is_enabled() {
+        return;
+    }
+
+    let Ok(scale_factor) = window.scale_factor() else {
+        return;
+    };
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+        return;
+    };
+    let position = position.to_logical::<f64>(scale_factor);
+    let size = size.to_logical::<f64>(scale_factor);
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned());
+
+    let updated = store.update(window.label(), |record| {
+        record.x = position.x;
+        record.y = position.y;
+        record.width = size.width;
+        record.height = size.height;
+        record.monitor = monitor;
+    });
+    if updated {
+        pin_store::schedule_save(app);
+    }
+}
+
+#[tauri::command]
+async fn show_pin_window(
+    app: AppHandle,
+    blob_data: Vec<u8>,
+    window_label: Option<String>,
+) -> Result<(), String> {
+    let image = image::load_from_memory(&blob_data)
+        .map_err(|e| format!("Failed to decode pinned image: {}", e))?;
+
+    open_pin_window(
+        &app,
+        &blob_data,
+        image.width() as f64,
+        image.height() as f64,
+        window_label.as_deref(),
+        None,
+    )
+}
+
+#[tauri::command]
+async fn pin_image_from_path(app: AppHandle, path: String) -> Result<(), String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Failed to decode pinned image: {}", e))?;
+    let png_bytes = if image::guess_format(&bytes).ok() == Some(image::ImageFormat::Png) {
+        bytes
+    } else {
+        encode_rgba_png_fast(&image.to_rgba8())?
+    };
+
+    open_pin_window(
+        &app,
+        &png_bytes,
+        image.width() as f64,
+        image.height() as f64,
+        None,
+        None,
+    )
+}
+
+#[tauri::command]
+async fn pin_image_from_clipboard(app: AppHandle) -> Result<(), String> {
+    let image = clipboard::read_image(&app)?;
+    let png_bytes = encode_rgba_png_fast(&image)?;
+
+    open_pin_window(
+        &app,
+        &png_bytes,
+        image.width() as f64,
+        image.height() as f64,
+        None,
+        None,
+    )
+}
+
+fn pick_image_file_to_pin(app: &AppHandle) {
+    use tauri_plugin_dialog::DialogExt;
+
+    let app_handle = app.clone();
+    app.dialog()
+        .file()
+        .set_title("Pin Image")
+        .add_filter(
+            "Images",
+            &["png", "jpg", "jpeg", "webp", "gif", "bmp", "tiff"],
+        )
+        .pick_file(move |path| {
+            let Some(path) = path.and_then(|path| path.into_path().ok()) else {
+                return;
+            };
+            tauri::async_runtime::spawn(async move {
+                let path = path.to_string_lossy().to_string();
+                if let Err(error) = pin_image_from_path(app_handle, path).await {
+                    eprintln!("Failed to pin image: {}", error);
+                }
+            });
+        });
 }
 
 #[tauri::command]
@@ -1878,6 +2732,245 @@ async fn close_pin_window(app: AppHandle, label: String) -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+fn list_pin_windows(app: AppHandle) -> Result<Vec<PinWindowInfo>, String> {
+    let payloads = app
+        .state::<PinWindowStore>()
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock pin window store".to_string())?
+        .clone();
+    let persisted = app
+        .state::<pin_store::PersistentPinStore>()
+        .records()
+        .into_iter()
+        .map(|record| record.label)
+        .collect::<HashSet<_>>();
+
+    let mut pins = pin_windows(&app)
+        .into_iter()
+        .filter_map(|(label, window)| {
+            let payload = payloads.get(&label)?;
+            let scale_factor = window.scale_factor().ok()?;
+            let position = window
+                .outer_position()
+                .ok()?
+                .to_logical::<f64>(scale_factor);
+            let size = window.inner_size().ok()?.to_logical::<f64>(scale_factor);
+            Some(PinWindowInfo {
+                image_width: payload.image_width,
+                image_height: payload.image_height,
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                opacity: payload.opacity,
+                click_through: payload.click_through,
+                locked: payload.locked,
+                always_on_top: payload.always_on_top,
+                visible: window.is_visible().unwrap_or(false),
+                persisted: persisted.contains(&label),
+                label,
+            })
+        })
+        .collect::<Vec<_>>();
+    pins.sort_by(|a, b| a.label.cmp(&b.label));
+
+    Ok(pins)
+}
+
+#[tauri::command]
+async fn focus_pin_window(app: AppHandle, label: String) -> Result<(), String> {
+    let window = app
+        .get_webview_window(&label)
+        .filter(|_| label.starts_with("pin_window_"))
+        .ok_or("Pinned window not found")?;
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn hide_all_pin_windows(app: AppHandle) -> Result<(), String> {
+    for (_, window) in pin_windows(&app) {
+        window.hide().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn show_all_pin_windows(app: AppHandle) -> Result<(), String> {
+    for (_, window) in pin_windows(&app) {
+        window.show().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn close_all_pin_windows(app: AppHandle) -> Result<(), String> {
+    for (label, window) in pin_windows(&app) {
+        cleanup_pin_payload(&app, &label);
+        window.close().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn update_pin_window_state<F: FnOnce(&mut PinWindowPayload)>(
+    app: &AppHandle,
+    label: &str,
+    update: F,
+) -> Result<(WebviewWindow, PinWindowPayload), String> {
+    let window = app
+        .get_webview_window(label)
+        .filter(|_| label.starts_with("pin_window_"))
+        .ok_or("Pinned window not found")?;
+    let payload = {
+        let store = app.state::<PinWindowStore>();
+        let mut payloads = store
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock pin window store".to_string())?;
+        let payload = payloads.get_mut(label).ok_or("Pinned image not found")?;
+        update(payload);
+        payload.clone()
+    };
+
+    let persisted = app
+        .state::<pin_store::PersistentPinStore>()
+        .update(label, |record| {
+            record.opacity = payload.opacity;
+            record.click_through = payload.click_through;
+            record.locked = payload.locked;
+            record.always_on_top = payload.always_on_top;
+        });
+    if persisted {
+        pin_store::schedule_save(app);
+    }
+
+    let _ = app.emit_to(label, "pin-window-state", payload.clone());
+    Ok((window, payload))
+}
+
+#[tauri::command]
+async fn set_pin_window_opacity(app: AppHandle, label: String, opacity: f64) -> Result<(), String> {
+    update_pin_window_state(&app, &label, |payload| {
+        payload.opacity = opacity.clamp(0.1, 1.0);
+    })?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_pin_window_click_through(
+    app: AppHandle,
+    label: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let (window, _) = update_pin_window_state(&app, &label, |payload| {
+        payload.click_through = enabled;
+    })?;
+    set_pin_window_ignores_mouse_events(&app, &window, enabled)
+}
+
+/// Click-through pins cannot open their own menu, so the tray turns it off for all of them.
+fn release_click_through_pins(app: &AppHandle) {
+    let labels = match app.state::<PinWindowStore>().0.lock() {
+        Ok(payloads) => payloads
+            .iter()
+            .filter(|(_, payload)| payload.click_through)
+            .map(|(label, _)| label.clone())
+            .collect::<Vec<_>>(),
+        Err(_) => return,
+    };
+
+    for label in labels {
+        let result = update_pin_window_state(app, &label, |payload| {
+            payload.click_through = false;
+        })
+        .and_then(|(window, _)| set_pin_window_ignores_mouse_events(app, &window, false));
+        if let Err(error) = result {
+            eprintln!("Failed to release click-through pin {}: {}", label, error);
+        }
+    }
+}
+
+#[tauri::command]
+async fn set_pin_window_locked(app: AppHandle, label: String, locked: bool) -> Result<(), String> {
+    update_pin_window_state(&app, &label, |payload| {
+        payload.locked = locked;
+    })?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_pin_window_always_on_top(
+    app: AppHandle,
+    label: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let (window, _) = update_pin_window_state(&app, &label, |payload| {
+        payload.always_on_top = enabled;
+    })?;
+    window
+        .set_always_on_top(enabled)
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+fn get_pin_persistence(app: AppHandle) -> bool {
+    app.state::<pin_store::PersistentPinStore>().is_enabled()
+}
+
+#[tauri::command]
+async fn set_pin_persistence(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let store = app.state::<pin_store::PersistentPinStore>();
+    store.set_enabled(enabled)?;
+    if !enabled {
+        return Ok(());
+    }
+
+    let payloads = app
+        .state::<PinWindowStore>()
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock pin window store".to_string())?
+        .clone();
+    for (label, window) in pin_windows(&app) {
+        let Some(payload) = payloads.get(&label) else {
+            continue;
+        };
+        let scale_factor = window.scale_factor().map_err(|e| e.to_string())?;
+        let position = window
+            .outer_position()
+            .map_err(|e| e.to_string())?
+            .to_logical::<f64>(scale_factor);
+        let size = window
+            .inner_size()
+            .map_err(|e| e.to_string())?
+            .to_logical::<f64>(scale_factor);
+        let record = pin_store::PinRecord {
+            image_file: format!("{}.png", label),
+            label,
+            image_width: payload.image_width,
+            image_height: payload.image_height,
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            opacity: payload.opacity,
+            click_through: payload.click_through,
+            locked: payload.locked,
+            always_on_top: payload.always_on_top,
+            monitor: window
+                .current_monitor()
+                .ok()
+                .flatten()
+                .and_then(|monitor| monitor.name().cloned()),
+        };
+        store.insert(record, payload.image.bytes())?;
+    }
+
+    Ok(())
+}
+
 fn macos_accessibility_trusted() -> bool {
     #[cfg(target_os = "macos")]
     {
@@ -2004,26 +3097,15 @@ fn macos_request_screen_recording_access() -> bool {
 }
 
 #[cfg(target_os = "macos")]
-fn set_screenshot_window_ignores_mouse_events(
+fn set_windows_ignore_mouse_events(
     app: &AppHandle,
-    window_label: Option<&str>,
+    windows: Vec<WebviewWindow>,
     ignores_mouse_events: bool,
 ) -> Result<(), String> {
     use objc2_app_kit::NSWindow;
     use std::sync::mpsc;
     use std::time::Duration;
 
-    let windows = if let Some(label) = window_label {
-        vec![app
-            .get_webview_window(label)
-            .ok_or("Screenshot window not found")?]
-    } else {
-        screenshot_windows(app)
-            .into_iter()
-            .map(|(_, window)| window)
-            .collect::<Vec<_>>()
-    };
-
     let (sender, receiver) = mpsc::channel();
 
     app.run_on_main_thread(move || {
@@ -2048,7 +3130,46 @@ fn set_screenshot_window_ignores_mouse_events(
 
     receiver
         .recv_timeout(Duration::from_millis(250))
-        .map_err(|_| "Timed out while updating screenshot mouse passthrough".to_string())?
+        .map_err(|_| "Timed out while updating mouse passthrough".to_string())?
+}
+
+#[cfg(target_os = "macos")]
+fn set_screenshot_window_ignores_mouse_events(
+    app: &AppHandle,
+    window_label: Option<&str>,
+    ignores_mouse_events: bool,
+) -> Result<(), String> {
+    let windows = if let Some(label) = window_label {
+        vec![app
+            .get_webview_window(label)
+            .ok_or("Screenshot window not found")?]
+    } else {
+        screenshot_windows(app)
+            .into_iter()
+            .map(|(_, window)| window)
+            .collect::<Vec<_>>()
+    };
+
+    set_windows_ignore_mouse_events(app, windows, ignores_mouse_events)
+}
+
+fn set_pin_window_ignores_mouse_events(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    ignores_mouse_events: bool,
+) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        set_windows_ignore_mouse_events(app, vec![window.clone()], ignores_mouse_events)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+        window
+            .set_ignore_cursor_events(ignores_mouse_events)
+            .map_err(|error| error.to_string())
+    }
 }
 
 #[tauri::command]
@@ -2386,6 +3507,12 @@ async fn stop_long_capture_scroll_monitor(app: AppHandle) -> Result<(), String>
     }
 }
 
+/// Handles command-line subcommands such as `scan-watermarks` without starting the app.
+/// Returns the exit code when `args` named a subcommand.
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    cli::run(args)
+}
+
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
@@ -2393,6 +3520,7 @@ pub fn run() {
         .plugin(tauri_plugin_autostart::Builder::new().build())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_clipboard_manager::init())
+        .register_asynchronous_uri_scheme_protocol(protocol::SCHEME, protocol::handle)
         .invoke_handler(tauri::generate_handler![
             greet,
             start_capture,
@@ -2400,18 +3528,58 @@ pub fn run() {
             capture_screen_rect,
             capture_screen_rect_below_screenshot_window,
             list_capture_windows,
+            virtual_desktop::map_virtual_desktop_selection,
+            prewarm::set_capture_prewarm,
+            input_history::set_input_history,
+            recording::start_recording,
+            recording::pause_recording,
+            recording::resume_recording,
+            recording::stop_recording,
+            recording::get_recording_status,
             record_capture_ui_timing,
             copy_to_clipboard,
             copy_text_to_clipboard,
             ocr::ocr_image,
+            ocr_export::export_ocr_result,
+            pdf_export::export_pdf,
+            pdf_export::export_pdf_from_files,
+            watermark::embed_hidden_watermark,
+            watermark::detect_hidden_watermark,
+            visible_watermark::render_visible_watermark,
+            redaction::redact_regions,
+            annotation_scene::render_annotation_scene,
+            project::save_project,
+            project::load_project,
+            project::export_project,
+            project::take_prepared_project,
+            watermark_scan::scan_hidden_watermarks,
+            watermark_scan::cancel_watermark_scan,
+            watermark_signing::get_watermark_signing,
+            watermark_signing::set_watermark_signing,
             translation::translate_texts,
             save_to_downloads,
             show_pin_window,
+            pin_image_from_path,
+            pin_image_from_clipboard,
             get_pin_window_payload,
             close_pin_window,
+            list_pin_windows,
+            focus_pin_window,
+            hide_all_pin_windows,
+            show_all_pin_windows,
+            close_all_pin_windows,
+            set_pin_window_opacity,
+            set_pin_window_click_through,
+            set_pin_window_locked,
+            set_pin_window_always_on_top,
+            get_pin_persistence,
+            set_pin_persistence,
             ensure_screenshot_window,
             set_dock_icon_visible,
             finish_capture,
+            open_clipboard_image_in_editor,
+            open_image_file_in_editor,
+            open_project_in_editor,
             open_devtools,
             open_screenshot_devtools,
             is_accessibility_trusted,
@@ -2424,10 +3592,17 @@ pub fn run() {
             stop_long_capture_scroll_monitor
         ])
         .setup(|app| {
-            let _ = std::fs::remove_dir_all(pin_window_temp_dir());
+            let _ = std::fs::remove_dir_all(clipboard::clipboard_temp_dir());
             app.manage(PinWindowStore::default());
+            app.manage(pin_store::PersistentPinStore::load(app.handle()));
             app.manage(PreparedCaptureStore::default());
             app.manage(PreparedCaptureWindowStore::default());
+            app.manage(project::PreparedProjectStore::default());
+            app.manage(project::ImportedOriginalStore::default());
+            app.manage(virtual_desktop::VirtualDesktopStore::default());
+            app.manage(prewarm::PrewarmState::default());
+            app.manage(recording::RecordingState::default());
+            app.manage(watermark_scan::WatermarkScanState::default());
             #[cfg(target_os = "macos")]
             app.manage(CaptureFocusFollowerState::default());
 
@@ -2435,13 +3610,71 @@ pub fn run() {
             tauri::async_runtime::spawn(async move {
                 ensure_screenshot_window(handle).await.unwrap();
             });
+            monitor_watcher::start(app.handle().clone());
+
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                restore_pin_windows(&handle);
+            });
 
             let capture_i = MenuItem::with_id(app, "capture", "Capture", true, None::<&str>)?;
+            let edit_clipboard_i = MenuItem::with_id(
+                app,
+                "edit_clipboard",
+                "Edit Clipboard Image",
+                true,
+                None::<&str>,
+            )?;
+            let pin_clipboard_i = MenuItem::with_id(
+                app,
+                "pin_clipboard",
+                "Pin Clipboard Image",
+                true,
+                None::<&str>,
+            )?;
+            let pin_file_i =
+                MenuItem::with_id(app, "pin_file", "Pin Image File...", true, None::<&str>)?;
+            let open_project_i =
+                MenuItem::with_id(app, "open_project", "Open Project...", true, None::<&str>)?;
+            let release_pins_i = MenuItem::with_id(
+                app,
+                "release_click_through_pins",
+                "Make Pins Clickable",
+                true,
+                None::<&str>,
+            )?;
+            let pause_recording_i = MenuItem::with_id(
+                app,
+                "pause_recording",
+                "Pause Recording",
+                false,
+                None::<&str>,
+            )?;
+            let stop_recording_i =
+                MenuItem::with_id(app, "stop_recording", "Stop Recording", false, None::<&str>)?;
+            app.manage(recording::RecordingTrayItems {
+                pause: pause_recording_i.clone(),
+                stop: stop_recording_i.clone(),
+            });
             let show_i = MenuItem::with_id(app, "show", "Settings", true, None::<&str>)?;
             let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&capture_i, &show_i, &quit_i])?;
-
-            let _tray = TrayIconBuilder::new()
+            let menu = Menu::with_items(
+                app,
+                &[
+                    &capture_i,
+                    &edit_clipboard_i,
+                    &pin_clipboard_i,
+                    &pin_file_i,
+                    &open_project_i,
+                    &release_pins_i,
+                    &pause_recording_i,
+                    &stop_recording_i,
+                    &show_i,
+                    &quit_i,
+                ],
+            )?;
+
+            let _tray = TrayIconBuilder::with_id(recording::TRAY_ID)
                 .icon(app.default_window_icon().unwrap().clone())
                 .menu(&menu)
                 .show_menu_on_left_click(true)
@@ -2450,16 +3683,55 @@ pub fn run() {
                         let app = app.clone();
                         tauri::async_runtime::spawn(async move {
                             if let Err(error) =
-                                start_capture(app, None, Some("tray".to_string()), None).await
+                                start_capture(app, None, Some("tray".to_string()), None, None).await
                             {
                                 eprintln!("Failed to start capture: {}", error);
                             }
                         });
                     }
+                    "edit_clipboard" => {
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(error) = open_clipboard_image_in_editor(app).await {
+                                eprintln!("Failed to open clipboard image: {}", error);
+                            }
+                        });
+                    }
+                    "pin_clipboard" => {
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(error) = pin_image_from_clipboard(app).await {
+                                eprintln!("Failed to pin clipboard image: {}", error);
+                            }
+                        });
+                    }
+                    "pin_file" => {
+                        pick_image_file_to_pin(app);
+                    }
+                    "open_project" => {
+                        pick_project_file_to_edit(app);
+                    }
+                    "release_click_through_pins" => {
+                        release_click_through_pins(app);
+                    }
+                    "pause_recording" => {
+                        if let Err(error) = recording::toggle_pause(app) {
+                            eprintln!("Failed to pause recording: {}", error);
+                        }
+                    }
+                    "stop_recording" => {
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(error) = recording::stop_recording(app).await {
+                                eprintln!("Failed to stop recording: {}", error);
+                            }
+                        });
+                    }
                     "show" => {
                         show_main_window(app);
                     }
                     "quit" => {
+                        app.state::<pin_store::PersistentPinStore>().mark_exiting();
                         app.exit(0);
                     }
                     _ => {}
@@ -2475,6 +3747,11 @@ pub fn run() {
                     api.prevent_close();
                 }
             }
+            if let tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) = event {
+                if window.label().starts_with("pin_window_") {
+                    track_pin_window_geometry(window);
+                }
+            }
             if let tauri::WindowEvent::Destroyed = event {
                 if window.label().starts_with("pin_window_") {
                     cleanup_pin_payload(window.app_handle(), window.label());
@@ -2487,5 +3764,87 @@ pub fn run() {
             if let tauri::RunEvent::Reopen { .. } = event {
                 show_main_window(app_handle);
             }
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                app_handle
+                    .state::<pin_store::PersistentPinStore>()
+                    .mark_exiting();
+            }
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(bounds: Rect, space: CoordinateSpace, scale_factor: f64) -> CaptureMonitor {
+        CaptureMonitor::new(
+            1,
+            "screenshot_window_1".to_string(),
+            bounds,
+            space,
+            scale_factor,
+            false,
+            "Test".to_string(),
+        )
+    }
+
+    #[test]
+    fn physical_monitor_reports_logical_geometry() {
+        // Windows and Linux hand out physical bounds; x/y/width/height are logical.
+        let monitor = monitor(
+            Rect::new(-2400.0, -1350.0, 2400.0, 1350.0),
+            CoordinateSpace::Physical,
+            1.25,
+        );
+        assert_eq!(
+            (monitor.x, monitor.y, monitor.width, monitor.height),
+            (-1920.0, -1080.0, 1920.0, 1080.0)
+        );
+        assert_eq!(
+            (
+                monitor.physical_x,
+                monitor.physical_y,
+                monitor.physical_width,
+                monitor.physical_height
+            ),
+            (-2400.0, -1350.0, 2400.0, 1350.0)
+        );
+    }
+
+    #[test]
+    fn logical_monitor_derives_physical_geometry() {
+        // macOS hands out points.
+        let monitor = monitor(
+            Rect::new(-1512.0, -200.0, 1512.0, 982.0),
+            CoordinateSpace::Logical,
+            2.0,
+        );
+        assert_eq!(
+            (monitor.x, monitor.y, monitor.width, monitor.height),
+            (-1512.0, -200.0, 1512.0, 982.0)
+        );
+        assert_eq!(
+            (
+                monitor.physical_x,
+                monitor.physical_y,
+                monitor.physical_width,
+                monitor.physical_height
+            ),
+            (-3024.0, -400.0, 3024.0, 1964.0)
+        );
+    }
+
+    #[test]
+    fn monitor_space_round_trips_at_fractional_scale() {
+        let monitor = monitor(
+            Rect::new(-2880.0, 0.0, 2880.0, 1620.0),
+            CoordinateSpace::Physical,
+            1.5,
+        );
+        let space = monitor.space();
+        assert_eq!(space.scale_factor, 1.5);
+        assert!((space.logical.x + 1920.0).abs() < 1e-9);
+        assert!((space.logical.width - 1920.0).abs() < 1e-9);
+        assert_eq!(space.physical, Rect::new(-2880.0, 0.0, 2880.0, 1620.0));
+    }
+}
@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Some(code) = xshot_lib::run_cli(&args) {
+        std::process::exit(code);
+    }
     xshot_lib::run()
 }
@@ -0,0 +1,324 @@
+use crate::{capture_monitors, sync_screenshot_windows, CaptureMonitor};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+// Displays briefly disappear while a configuration change is applied, and one change usually
+// arrives as a burst of notifications.
+const SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+struct MonitorWatcher {
+    app: AppHandle,
+    previous: Mutex<Vec<CaptureMonitor>>,
+    generation: AtomicU64,
+}
+
+static WATCHER: OnceLock<MonitorWatcher> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorsChangedPayload {
+    monitors: Vec<CaptureMonitor>,
+    added: Vec<String>,
+    removed: Vec<String>,
+    changed: Vec<String>,
+}
+
+pub fn same_geometry(a: &CaptureMonitor, b: &CaptureMonitor) -> bool {
+    a.x == b.x
+        && a.y == b.y
+        && a.width == b.width
+        && a.height == b.height
+        && a.physical_x == b.physical_x
+        && a.physical_y == b.physical_y
+        && a.physical_width == b.physical_width
+        && a.physical_height == b.physical_height
+        && a.scale_factor == b.scale_factor
+        && a.is_primary == b.is_primary
+}
+
+fn diff_monitors(
+    previous: &[CaptureMonitor],
+    current: Vec<CaptureMonitor>,
+) -> Option<MonitorsChangedPayload> {
+    let find = |monitors: &[CaptureMonitor], label: &str| {
+        monitors.iter().position(|monitor| monitor.label == label)
+    };
+
+    let added = current
+        .iter()
+        .filter(|monitor| find(previous, &monitor.label).is_none())
+        .map(|monitor| monitor.label.clone())
+        .collect::<Vec<_>>();
+    let removed = previous
+        .iter()
+        .filter(|monitor| find(&current, &monitor.label).is_none())
+        .map(|monitor| monitor.label.clone())
+        .collect::<Vec<_>>();
+    let changed = current
+        .iter()
+        .filter(|monitor| {
+            find(previous, &monitor.label)
+                .is_some_and(|index| !same_geometry(&previous[index], monitor))
+        })
+        .map(|monitor| monitor.label.clone())
+        .collect::<Vec<_>>();
+
+    if added.is_empty() && removed.is_empty() && changed.is_empty() {
+        return None;
+    }
+
+    Some(MonitorsChangedPayload {
+        monitors: current,
+        added,
+        removed,
+        changed,
+    })
+}
+
+impl MonitorWatcher {
+    fn refresh(&self) {
+        let current = match capture_monitors() {
+            Ok(monitors) if !monitors.is_empty() => monitors,
+            _ => return,
+        };
+        let Ok(mut previous) = self.previous.lock() else {
+            return;
+        };
+        let Some(payload) = diff_monitors(&previous, current) else {
+            return;
+        };
+
+        println!(
+            "[xshot][monitors] added={:?} removed={:?} changed={:?}",
+            payload.added, payload.removed, payload.changed
+        );
+        if let Err(error) = sync_screenshot_windows(&self.app, &payload.monitors) {
+            eprintln!("Failed to update screenshot windows: {}", error);
+            return;
+        }
+
+        *previous = payload.monitors.clone();
+        let _ = self.app.emit("monitors-changed", payload);
+    }
+}
+
+fn display_configuration_changed() {
+    let Some(watcher) = WATCHER.get() else {
+        return;
+    };
+    let generation = watcher.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    std::thread::spawn(move || {
+        std::thread::sleep(SETTLE_DELAY);
+        if watcher.generation.load(Ordering::SeqCst) == generation {
+            watcher.refresh();
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn subscribe(_app: &AppHandle) -> Result<(), String> {
+    use std::ffi::c_void;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    unsafe extern "C" {
+        fn CGDisplayRegisterReconfigurationCallback(
+            callback: extern "C" fn(u32, u32, *mut c_void),
+            user_info: *mut c_void,
+        ) -> i32;
+    }
+
+    extern "C" fn reconfigured(_display: u32, flags: u32, _user_info: *mut c_void) {
+        const BEGIN_CONFIGURATION_FLAG: u32 = 1;
+        if flags & BEGIN_CONFIGURATION_FLAG == 0 {
+            display_configuration_changed();
+        }
+    }
+
+    let error =
+        unsafe { CGDisplayRegisterReconfigurationCallback(reconfigured, std::ptr::null_mut()) };
+    if error == 0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "CGDisplayRegisterReconfigurationCallback failed: {}",
+            error
+        ))
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn display_window_proc(
+    hwnd: windows_sys::Win32::Foundation::HWND,
+    message: u32,
+    wparam: usize,
+    lparam: isize,
+) -> isize {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        DefWindowProcW, WM_DISPLAYCHANGE, WM_DPICHANGED, WM_SETTINGCHANGE,
+    };
+
+    if matches!(message, WM_DISPLAYCHANGE | WM_DPICHANGED | WM_SETTINGCHANGE) {
+        display_configuration_changed();
+    }
+    DefWindowProcW(hwnd, message, wparam, lparam)
+}
+
+#[cfg(target_os = "windows")]
+fn subscribe(_app: &AppHandle) -> Result<(), String> {
+    use std::sync::mpsc;
+    use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DispatchMessageW, GetMessageW, RegisterClassW, TranslateMessage, MSG,
+        WNDCLASSW, WS_OVERLAPPED,
+    };
+
+    // WM_DISPLAYCHANGE is only broadcast to top-level windows, so a hidden one (not a
+    // message-only window) listens on its own thread.
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || unsafe {
+        let class_name = "xshot_display_watcher\0".encode_utf16().collect::<Vec<_>>();
+        let instance = GetModuleHandleW(std::ptr::null());
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(display_window_proc),
+            hInstance: instance,
+            lpszClassName: class_name.as_ptr(),
+            ..std::mem::zeroed()
+        };
+        if RegisterClassW(&class) == 0 {
+            let _ = sender.send(Err("Failed to register display watcher class".to_string()));
+            return;
+        }
+        let hwnd = CreateWindowExW(
+            0,
+            class_name.as_ptr(),
+            class_name.as_ptr(),
+            WS_OVERLAPPED,
+            0,
+            0,
+            0,
+            0,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            instance,
+            std::ptr::null(),
+        );
+        if hwnd.is_null() {
+            let _ = sender.send(Err("Failed to create display watcher window".to_string()));
+            return;
+        }
+        let _ = sender.send(Ok(()));
+
+        let mut message: MSG = std::mem::zeroed();
+        while GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) > 0 {
+            TranslateMessage(&message);
+            DispatchMessageW(&message);
+        }
+    });
+
+    receiver
+        .recv()
+        .map_err(|_| "Display watcher thread exited".to_string())?
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn subscribe(app: &AppHandle) -> Result<(), String> {
+    app.run_on_main_thread(|| {
+        let Some(screen) = gtk::gdk::Screen::default() else {
+            eprintln!("Failed to watch displays: no default GDK screen");
+            return;
+        };
+        screen.connect_monitors_changed(|_| display_configuration_changed());
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Listens for display configuration changes and keeps one screenshot window per monitor, so
+/// the first capture after docking or undocking does not pay for window creation or placement.
+pub fn start(app: AppHandle) {
+    let watcher = MonitorWatcher {
+        app: app.clone(),
+        previous: Mutex::new(capture_monitors().unwrap_or_default()),
+        generation: AtomicU64::new(0),
+    };
+    if WATCHER.set(watcher).is_err() {
+        return;
+    }
+    if let Err(error) = subscribe(&app) {
+        eprintln!("Failed to watch display changes: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinates::{CoordinateSpace, Rect};
+
+    fn monitor(label: &str, x: f64, scale_factor: f64) -> CaptureMonitor {
+        CaptureMonitor::new(
+            1,
+            label.to_string(),
+            Rect::new(x, 0.0, 1920.0, 1080.0),
+            CoordinateSpace::Physical,
+            scale_factor,
+            x == 0.0,
+            label.to_string(),
+        )
+    }
+
+    #[test]
+    fn geometry_compares_bounds_scale_and_primary() {
+        let base = monitor("a", 0.0, 1.0);
+        assert!(same_geometry(&base, &base.clone()));
+
+        let renamed = CaptureMonitor {
+            id: 9,
+            name: "Renamed".into(),
+            ..base.clone()
+        };
+        assert!(same_geometry(&base, &renamed));
+        assert!(!same_geometry(&base, &monitor("a", 1920.0, 1.0)));
+        // The same physical bounds at a new scale move the logical ones.
+        assert!(!same_geometry(&base, &monitor("a", 0.0, 1.5)));
+        let promoted = CaptureMonitor {
+            is_primary: false,
+            ..base.clone()
+        };
+        assert!(!same_geometry(&base, &promoted));
+    }
+
+    #[test]
+    fn unchanged_monitors_produce_no_diff() {
+        let monitors = vec![monitor("a", 0.0, 1.0), monitor("b", 1920.0, 2.0)];
+        assert!(diff_monitors(&monitors, monitors.clone()).is_none());
+        // Order alone is not a change.
+        let reversed = monitors.iter().rev().cloned().collect();
+        assert!(diff_monitors(&monitors, reversed).is_none());
+    }
+
+    #[test]
+    fn diff_lists_added_removed_and_changed_labels() {
+        let previous = vec![
+            monitor("a", 0.0, 1.0),
+            monitor("b", 1920.0, 1.0),
+            monitor("c", 3840.0, 1.0),
+        ];
+        let current = vec![
+            monitor("a", 0.0, 1.0),
+            monitor("c", 3840.0, 2.0),
+            monitor("d", -1920.0, 1.0),
+        ];
+        let payload = diff_monitors(&previous, current.clone()).unwrap();
+        assert_eq!(payload.added, ["d"]);
+        assert_eq!(payload.removed, ["b"]);
+        assert_eq!(payload.changed, ["c"]);
+        let labels = payload
+            .monitors
+            .iter()
+            .map(|monitor| monitor.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["a", "c", "d"]);
+    }
+}
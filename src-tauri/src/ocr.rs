@@ -1,38 +1,38 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct OcrBounds {
-    x: f64,
-    y: f64,
-    width: f64,
-    height: f64,
+pub struct OcrBounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct OcrTextBlock {
-    text: String,
-    confidence: f32,
-    bounds: OcrBounds,
+pub struct OcrTextBlock {
+    pub text: String,
+    pub confidence: f32,
+    pub bounds: OcrBounds,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct OcrQrCode {
-    value: String,
-    url: Option<String>,
-    bounds: OcrBounds,
+pub struct OcrQrCode {
+    pub value: String,
+    pub url: Option<String>,
+    pub bounds: OcrBounds,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OcrScanResult {
-    text: String,
-    blocks: Vec<OcrTextBlock>,
-    qr_codes: Vec<OcrQrCode>,
-    image_width: u32,
-    image_height: u32,
+    pub text: String,
+    pub blocks: Vec<OcrTextBlock>,
+    pub qr_codes: Vec<OcrQrCode>,
+    pub image_width: u32,
+    pub image_height: u32,
 }
 
 #[cfg(target_os = "macos")]
@@ -215,13 +215,12 @@ fn perform_vision_ocr(blob_data: Vec<u8>) -> Result<OcrScanResult, String> {
     })
 }
 
-#[tauri::command]
-pub async fn ocr_image(blob_data: Vec<u8>) -> Result<OcrScanResult, String> {
+/// Recognizes text and QR codes in an encoded image. Blocking; bounds are normalized to the image
+/// with a top-left origin.
+pub fn recognize(blob_data: Vec<u8>) -> Result<OcrScanResult, String> {
     #[cfg(target_os = "macos")]
     {
-        tauri::async_runtime::spawn_blocking(move || perform_vision_ocr(blob_data))
-            .await
-            .map_err(|error| error.to_string())?
+        perform_vision_ocr(blob_data)
     }
 
     #[cfg(not(target_os = "macos"))]
@@ -230,3 +229,10 @@ pub async fn ocr_image(blob_data: Vec<u8>) -> Result<OcrScanResult, String> {
         Err("OCR is currently only implemented on macOS".into())
     }
 }
+
+#[tauri::command]
+pub async fn ocr_image(blob_data: Vec<u8>) -> Result<OcrScanResult, String> {
+    tauri::async_runtime::spawn_blocking(move || recognize(blob_data))
+        .await
+        .map_err(|error| error.to_string())?
+}
@@ -0,0 +1,826 @@
+// Structured exports of OCR results. Blocks are put into reading order with a recursive XY-cut
+// (split a region on blank column gutters first, then on blank horizontal bands), grouped into
+// lines and paragraphs, and written as Markdown, positioned HTML, hOCR, ALTO or JSON.
+use crate::ocr::{OcrBounds, OcrQrCode, OcrScanResult};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Column gutters narrower than this many median line heights do not split a region.
+const COLUMN_GAP: f64 = 1.5;
+/// Blank bands shorter than this many median line heights do not split a region.
+const BAND_GAP: f64 = 0.9;
+/// Vertical gap, in median line heights, that starts a new paragraph.
+const PARAGRAPH_GAP: f64 = 0.6;
+/// Height change between neighbouring lines that starts a new paragraph.
+const LINE_HEIGHT_CHANGE: f64 = 1.25;
+/// Line height, relative to the median, of level 1 and level 2 headings.
+const HEADING_RATIOS: [f64; 2] = [1.6, 1.25];
+const LIST_BULLETS: [char; 8] = ['•', '·', '◦', '▪', '‣', '●', '-', '*'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OcrExportFormat {
+    Markdown,
+    Html,
+    Hocr,
+    Alto,
+    Json,
+}
+
+impl OcrExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Some(OcrExportFormat::Markdown),
+            "html" | "htm" => Some(OcrExportFormat::Html),
+            "hocr" => Some(OcrExportFormat::Hocr),
+            "alto" | "xml" => Some(OcrExportFormat::Alto),
+            "json" => Some(OcrExportFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Self::parse)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PixelBox {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl PixelBox {
+    fn from_bounds(bounds: &OcrBounds, image_width: u32, image_height: u32) -> Self {
+        let to_pixels = |value: f64, size: u32| (value.clamp(0.0, 1.0) * f64::from(size)).round();
+        let x = to_pixels(bounds.x, image_width);
+        let y = to_pixels(bounds.y, image_height);
+        let right = to_pixels(bounds.x + bounds.width, image_width);
+        let bottom = to_pixels(bounds.y + bounds.height, image_height);
+        Self {
+            x: x as u32,
+            y: y as u32,
+            width: (right - x).max(1.0) as u32,
+            height: (bottom - y).max(1.0) as u32,
+        }
+    }
+
+    fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    fn union(self, other: Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Self {
+            x,
+            y,
+            width: self.right().max(other.right()) - x,
+            height: self.bottom().max(other.bottom()) - y,
+        }
+    }
+
+    fn bbox(&self) -> String {
+        format!(
+            "bbox {} {} {} {}",
+            self.x,
+            self.y,
+            self.right(),
+            self.bottom()
+        )
+    }
+}
+
+struct Item {
+    text: String,
+    confidence: f32,
+    bounds: PixelBox,
+}
+
+struct Word {
+    text: String,
+    bounds: PixelBox,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Line {
+    text: String,
+    confidence: f32,
+    bounds: PixelBox,
+    #[serde(skip)]
+    words: Vec<Word>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Paragraph {
+    bounds: PixelBox,
+    lines: Vec<Line>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    bounds: PixelBox,
+    paragraphs: Vec<Paragraph>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Layout {
+    image_width: u32,
+    image_height: u32,
+    regions: Vec<Region>,
+    qr_codes: Vec<OcrQrCode>,
+    #[serde(skip)]
+    line_height: f64,
+}
+
+impl Layout {
+    fn paragraphs(&self) -> impl Iterator<Item = &Paragraph> {
+        self.regions
+            .iter()
+            .flat_map(|region| region.paragraphs.iter())
+    }
+}
+
+fn is_cjk(character: char) -> bool {
+    matches!(
+        u32::from(character),
+        0x3000..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0xFF00..=0xFFEF
+    )
+}
+
+/// Appends `next` with a space, except between CJK characters. Across lines, a word hyphenated at
+/// the line end is joined back together.
+fn join_text(text: &mut String, next: &str, across_lines: bool) {
+    let mut tail = text.chars().rev();
+    let (Some(last), Some(first)) = (tail.next(), next.chars().next()) else {
+        text.push_str(next);
+        return;
+    };
+    let hyphenated = across_lines
+        && last == '-'
+        && first.is_lowercase()
+        && tail.next().is_some_and(char::is_alphabetic);
+    if hyphenated {
+        text.pop();
+    } else if !(is_cjk(last) && is_cjk(first)) {
+        text.push(' ');
+    }
+    text.push_str(next);
+}
+
+fn median_height(items: &[Item]) -> f64 {
+    let mut heights = items
+        .iter()
+        .map(|item| item.bounds.height)
+        .collect::<Vec<_>>();
+    heights.sort_unstable();
+    heights
+        .get(heights.len() / 2)
+        .map_or(1.0, |&height| f64::from(height.max(1)))
+}
+
+/// Splits `group` wherever the spans picked out by `span` leave a gap of at least `min_gap`.
+fn split_on_gaps(
+    items: &[Item],
+    group: &[usize],
+    min_gap: f64,
+    span: impl Fn(&PixelBox) -> (u32, u32),
+) -> Vec<Vec<usize>> {
+    let mut sorted = group.to_vec();
+    sorted.sort_by_key(|&index| span(&items[index].bounds).0);
+    let mut parts: Vec<Vec<usize>> = Vec::new();
+    let mut end = 0;
+    for index in sorted {
+        let (start, stop) = span(&items[index].bounds);
+        match parts.last_mut() {
+            Some(part) if f64::from(start) < f64::from(end) + min_gap => {
+                part.push(index);
+                end = end.max(stop);
+            }
+            _ => {
+                parts.push(vec![index]);
+                end = stop;
+            }
+        }
+    }
+    parts
+}
+
+fn xy_cut(items: &[Item], group: Vec<usize>, line_height: f64, leaves: &mut Vec<Vec<usize>>) {
+    if group.len() > 1 {
+        let mut parts = split_on_gaps(items, &group, COLUMN_GAP * line_height, |bounds| {
+            (bounds.x, bounds.right())
+        });
+        if parts.len() < 2 {
+            parts = split_on_gaps(items, &group, BAND_GAP * line_height, |bounds| {
+                (bounds.y, bounds.bottom())
+            });
+        }
+        if parts.len() > 1 {
+            for part in parts {
+                xy_cut(items, part, line_height, leaves);
+            }
+            return;
+        }
+    }
+    leaves.push(group);
+}
+
+fn same_line(line: PixelBox, bounds: PixelBox) -> bool {
+    let overlap = i64::from(line.bottom().min(bounds.bottom())) - i64::from(line.y.max(bounds.y));
+    overlap as f64 >= 0.5 * f64::from(line.height.min(bounds.height))
+}
+
+/// Word boxes spread over the block by character count; OCR reports positions per line only.
+fn words(item: &Item) -> Vec<Word> {
+    let characters = item.text.chars().count().max(1) as f64;
+    let bounds = item.bounds;
+    let mut offset = 0;
+    item.text
+        .split(' ')
+        .map(|word| {
+            let length = word.chars().count();
+            let x = |position: usize| {
+                bounds.x + (f64::from(bounds.width) * position as f64 / characters).round() as u32
+            };
+            let (left, right) = (x(offset), x(offset + length));
+            offset += length + 1;
+            Word {
+                text: word.to_string(),
+                bounds: PixelBox {
+                    x: left,
+                    y: bounds.y,
+                    width: (right - left).max(1),
+                    height: bounds.height,
+                },
+            }
+        })
+        .collect()
+}
+
+fn build_line(items: &[Item], mut members: Vec<usize>) -> Line {
+    members.sort_by_key(|&index| items[index].bounds.x);
+    let mut text = String::new();
+    let mut words_in_line = Vec::new();
+    for &index in &members {
+        join_text(&mut text, &items[index].text, false);
+        words_in_line.extend(words(&items[index]));
+    }
+    Line {
+        text,
+        confidence: members
+            .iter()
+            .map(|&index| items[index].confidence)
+            .sum::<f32>()
+            / members.len() as f32,
+        bounds: members
+            .iter()
+            .map(|&index| items[index].bounds)
+            .reduce(PixelBox::union)
+            .expect("a line has at least one block"),
+        words: words_in_line,
+    }
+}
+
+fn list_item(text: &str) -> Option<(String, &str)> {
+    let first = text.chars().next()?;
+    if LIST_BULLETS.contains(&first) {
+        let rest = &text[first.len_utf8()..];
+        let spaced = rest.starts_with(' ');
+        return (spaced || !first.is_ascii()).then(|| ("-".to_string(), rest.trim_start()));
+    }
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = text[digits..].strip_prefix(['.', ')'])?.strip_prefix(' ')?;
+    (1..=3)
+        .contains(&digits)
+        .then(|| (format!("{}.", &text[..digits]), rest.trim_start()))
+}
+
+fn build_region(items: &[Item], mut members: Vec<usize>, line_height: f64) -> Region {
+    members.sort_by_key(|&index| items[index].bounds.y + items[index].bounds.height / 2);
+    let mut groups: Vec<(PixelBox, Vec<usize>)> = Vec::new();
+    for index in members {
+        let bounds = items[index].bounds;
+        match groups.last_mut() {
+            Some((line, group)) if same_line(*line, bounds) => {
+                *line = line.union(bounds);
+                group.push(index);
+            }
+            _ => groups.push((bounds, vec![index])),
+        }
+    }
+
+    let mut paragraphs: Vec<Paragraph> = Vec::new();
+    for (_, group) in groups {
+        let line = build_line(items, group);
+        let continues = paragraphs.last().is_some_and(|paragraph| {
+            let previous = paragraph.lines.last().expect("paragraphs are never empty");
+            let gap = f64::from(line.bounds.y) - f64::from(previous.bounds.bottom());
+            let heights = (
+                f64::from(previous.bounds.height),
+                f64::from(line.bounds.height),
+            );
+            gap <= PARAGRAPH_GAP * line_height
+                && heights.0.max(heights.1) <= LINE_HEIGHT_CHANGE * heights.0.min(heights.1)
+                && list_item(&line.text).is_none()
+        });
+        match paragraphs.last_mut() {
+            Some(paragraph) if continues => {
+                paragraph.bounds = paragraph.bounds.union(line.bounds);
+                paragraph.lines.push(line);
+            }
+            _ => paragraphs.push(Paragraph {
+                bounds: line.bounds,
+                lines: vec![line],
+            }),
+        }
+    }
+    Region {
+        bounds: paragraphs
+            .iter()
+            .map(|paragraph| paragraph.bounds)
+            .reduce(PixelBox::union)
+            .expect("a region has at least one block"),
+        paragraphs,
+    }
+}
+
+fn layout(result: &OcrScanResult) -> Layout {
+    let image_width = result.image_width.max(1);
+    let image_height = result.image_height.max(1);
+    let items = result
+        .blocks
+        .iter()
+        .filter(|block| !block.text.trim().is_empty())
+        .map(|block| Item {
+            text: block.text.split_whitespace().collect::<Vec<_>>().join(" "),
+            confidence: block.confidence,
+            bounds: PixelBox::from_bounds(&block.bounds, image_width, image_height),
+        })
+        .collect::<Vec<_>>();
+    let line_height = median_height(&items);
+    let mut leaves = Vec::new();
+    if !items.is_empty() {
+        xy_cut(&items, (0..items.len()).collect(), line_height, &mut leaves);
+    }
+    Layout {
+        image_width,
+        image_height,
+        regions: leaves
+            .into_iter()
+            .map(|leaf| build_region(&items, leaf, line_height))
+            .collect(),
+        qr_codes: result.qr_codes.clone(),
+        line_height,
+    }
+}
+
+fn paragraph_text(paragraph: &Paragraph) -> String {
+    let mut text = String::new();
+    for line in &paragraph.lines {
+        join_text(&mut text, &line.text, true);
+    }
+    text
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for (index, character) in text.chars().enumerate() {
+        let leading = index == 0 && matches!(character, '#' | '+' | '-' | '=');
+        if leading
+            || matches!(
+                character,
+                '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|'
+            )
+        {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+    escaped
+}
+
+fn heading_level(paragraph: &Paragraph, line_height: f64) -> Option<usize> {
+    if paragraph.lines.len() > 2 {
+        return None;
+    }
+    let height = paragraph
+        .lines
+        .iter()
+        .map(|line| f64::from(line.bounds.height))
+        .sum::<f64>()
+        / paragraph.lines.len() as f64;
+    HEADING_RATIOS
+        .iter()
+        .position(|&ratio| height >= ratio * line_height)
+        .map(|level| level + 1)
+}
+
+fn markdown(layout: &Layout) -> String {
+    let mut output = String::new();
+    let mut previous_list = false;
+    for paragraph in layout.paragraphs() {
+        let text = paragraph_text(paragraph);
+        let list = list_item(&text);
+        let block = match (&list, heading_level(paragraph, layout.line_height)) {
+            (Some((marker, rest)), _) => format!("{} {}", marker, escape_markdown(rest)),
+            (None, Some(level)) => format!("{} {}", "#".repeat(level), escape_markdown(&text)),
+            (None, None) => escape_markdown(&text),
+        };
+        if !output.is_empty() {
+            output.push_str(if list.is_some() && previous_list {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        output.push_str(&block);
+        previous_list = list.is_some();
+    }
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// A standalone page with one absolutely positioned span per line, stretched to the recognized
+/// width once fonts are laid out.
+fn html(layout: &Layout, title: &str) -> String {
+    let mut output = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n",
+        escape_xml(title)
+    );
+    output.push_str("body { margin: 0; background: #f4f5f7; }\n");
+    output.push_str(&format!(
+        ".page {{ position: relative; width: {}px; height: {}px; margin: 24px auto; \
+         background: #ffffff; color: #17212b; \
+         font-family: -apple-system, \"PingFang SC\", \"Segoe UI\", sans-serif; }}\n",
+        layout.image_width, layout.image_height
+    ));
+    output.push_str(".page p { margin: 0; }\n");
+    output.push_str(
+        ".page span { position: absolute; line-height: 1; white-space: pre; \
+         transform-origin: 0 0; }\n",
+    );
+    output.push_str("</style>\n</head>\n<body>\n<div class=\"page\">\n");
+    for paragraph in layout.paragraphs() {
+        output.push_str("<p>\n");
+        for line in &paragraph.lines {
+            let bounds = line.bounds;
+            output.push_str(&format!(
+                "<span style=\"left: {}px; top: {}px; font-size: {}px\" data-width=\"{}\" \
+                 data-confidence=\"{:.2}\">{}</span>\n",
+                bounds.x,
+                bounds.y,
+                (f64::from(bounds.height) * 0.8).round().max(1.0),
+                bounds.width,
+                line.confidence,
+                escape_xml(&line.text)
+            ));
+        }
+        output.push_str("</p>\n");
+    }
+    output.push_str("</div>\n<script>\n");
+    output.push_str("for (const span of document.querySelectorAll(\".page span\")) {\n");
+    output.push_str("  const width = Number(span.dataset.width);\n");
+    output.push_str(
+        "  if (span.offsetWidth > 0) span.style.transform = `scaleX(${width / span.offsetWidth})`;\n",
+    );
+    output.push_str("}\n</script>\n</body>\n</html>\n");
+    output
+}
+
+fn hocr(layout: &Layout, title: &str) -> String {
+    let mut output = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" \
+         \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\">\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\">\n<head>\n",
+    );
+    output.push_str(&format!("<title>{}</title>\n", escape_xml(title)));
+    output.push_str("<meta http-equiv=\"Content-Type\" content=\"text/html; charset=utf-8\"/>\n");
+    output.push_str("<meta name=\"ocr-system\" content=\"xshot\"/>\n");
+    output.push_str(
+        "<meta name=\"ocr-capabilities\" content=\"ocr_page ocr_carea ocr_par ocr_line ocrx_word\"/>\n",
+    );
+    output.push_str("</head>\n<body>\n");
+    output.push_str(&format!(
+        "<div class=\"ocr_page\" id=\"page_1\" title=\"bbox 0 0 {} {}; ppageno 0\">\n",
+        layout.image_width, layout.image_height
+    ));
+    let (mut paragraph_id, mut line_id, mut word_id) = (0, 0, 0);
+    for (region_id, region) in layout.regions.iter().enumerate() {
+        output.push_str(&format!(
+            "<div class=\"ocr_carea\" id=\"block_1_{}\" title=\"{}\">\n",
+            region_id + 1,
+            region.bounds.bbox()
+        ));
+        for paragraph in &region.paragraphs {
+            paragraph_id += 1;
+            output.push_str(&format!(
+                "<p class=\"ocr_par\" id=\"par_1_{}\" title=\"{}\">\n",
+                paragraph_id,
+                paragraph.bounds.bbox()
+            ));
+            for line in &paragraph.lines {
+                line_id += 1;
+                output.push_str(&format!(
+                    "<span class=\"ocr_line\" id=\"line_1_{}\" title=\"{}\">",
+                    line_id,
+                    line.bounds.bbox()
+                ));
+                for (index, word) in line.words.iter().enumerate() {
+                    word_id += 1;
+                    if index > 0 {
+                        output.push(' ');
+                    }
+                    output.push_str(&format!(
+                        "<span class=\"ocrx_word\" id=\"word_1_{}\" title=\"{}; x_wconf {}\">{}</span>",
+                        word_id,
+                        word.bounds.bbox(),
+                        (line.confidence * 100.0).round(),
+                        escape_xml(&word.text)
+                    ));
+                }
+                output.push_str("</span>\n");
+            }
+            output.push_str("</p>\n");
+        }
+        output.push_str("</div>\n");
+    }
+    output.push_str("</div>\n</body>\n</html>\n");
+    output
+}
+
+fn alto_position(bounds: PixelBox) -> String {
+    format!(
+        "HPOS=\"{}\" VPOS=\"{}\" WIDTH=\"{}\" HEIGHT=\"{}\"",
+        bounds.x, bounds.y, bounds.width, bounds.height
+    )
+}
+
+/// ALTO v4; each paragraph becomes a `TextBlock`.
+fn alto(layout: &Layout, title: &str) -> String {
+    let page = PixelBox {
+        x: 0,
+        y: 0,
+        width: layout.image_width,
+        height: layout.image_height,
+    };
+    let mut output = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <alto xmlns=\"http://www.loc.gov/standards/alto/ns-v4#\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://www.loc.gov/standards/alto/ns-v4# \
+         http://www.loc.gov/alto/v4/alto-4-2.xsd\">\n",
+    );
+    output.push_str("  <Description>\n    <MeasurementUnit>pixel</MeasurementUnit>\n");
+    output.push_str(&format!(
+        "    <sourceImageInformation>\n      <fileName>{}</fileName>\n    </sourceImageInformation>\n",
+        escape_xml(title)
+    ));
+    output.push_str(
+        "    <OCRProcessing ID=\"OCR_0\">\n      <ocrProcessingStep>\n        <processingSoftware>\n          \
+         <softwareName>xshot</softwareName>\n        </processingSoftware>\n      \
+         </ocrProcessingStep>\n    </OCRProcessing>\n  </Description>\n",
+    );
+    output.push_str(&format!(
+        "  <Layout>\n    <Page ID=\"PAGE_1\" PHYSICAL_IMG_NR=\"1\" WIDTH=\"{}\" HEIGHT=\"{}\">\n      \
+         <PrintSpace {}>\n",
+        page.width,
+        page.height,
+        alto_position(page)
+    ));
+    let (mut line_id, mut string_id) = (0, 0);
+    for (block_id, paragraph) in layout.paragraphs().enumerate() {
+        output.push_str(&format!(
+            "        <TextBlock ID=\"BLOCK_{}\" {}>\n",
+            block_id + 1,
+            alto_position(paragraph.bounds)
+        ));
+        for line in &paragraph.lines {
+            line_id += 1;
+            output.push_str(&format!(
+                "          <TextLine ID=\"LINE_{}\" {}>\n",
+                line_id,
+                alto_position(line.bounds)
+            ));
+            for (index, word) in line.words.iter().enumerate() {
+                string_id += 1;
+                if index > 0 {
+                    output.push_str("            <SP/>\n");
+                }
+                output.push_str(&format!(
+                    "            <String ID=\"STRING_{}\" CONTENT=\"{}\" {} WC=\"{:.2}\"/>\n",
+                    string_id,
+                    escape_xml(&word.text),
+                    alto_position(word.bounds),
+                    line.confidence.clamp(0.0, 1.0)
+                ));
+            }
+            output.push_str("          </TextLine>\n");
+        }
+        output.push_str("        </TextBlock>\n");
+    }
+    output.push_str("      </PrintSpace>\n    </Page>\n  </Layout>\n</alto>\n");
+    output
+}
+
+/// Renders `result` as a document in `format`. `title` names the document where the format has a
+/// title or source field.
+pub fn render(
+    result: &OcrScanResult,
+    format: OcrExportFormat,
+    title: &str,
+) -> Result<String, String> {
+    let layout = layout(result);
+    Ok(match format {
+        OcrExportFormat::Markdown => markdown(&layout),
+        OcrExportFormat::Html => html(&layout, title),
+        OcrExportFormat::Hocr => hocr(&layout, title),
+        OcrExportFormat::Alto => alto(&layout, title),
+        OcrExportFormat::Json => serde_json::to_string_pretty(&layout)
+            .map_err(|e| format!("Failed to serialize OCR layout: {}", e))?,
+    })
+}
+
+pub fn document_title(path: &Path) -> &str {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("xshot OCR")
+}
+
+#[tauri::command]
+pub async fn export_ocr_result(
+    result: OcrScanResult,
+    path: String,
+    format: Option<OcrExportFormat>,
+) -> Result<String, String> {
+    let path = Path::new(&path);
+    let format = format
+        .or_else(|| OcrExportFormat::from_path(path))
+        .ok_or_else(|| format!("Unknown OCR export format for {}", path.display()))?;
+    let document = render(&result, format, document_title(path))?;
+    std::fs::write(path, document)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    println!(
+        "[xshot][ocr-export][rust] path={} format={:?} blocks={}",
+        path.display(),
+        format,
+        result.blocks.len()
+    );
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocr::OcrTextBlock;
+
+    /// A block at pixel coordinates of a 1000x1000 capture.
+    fn block(text: &str, x: u32, y: u32, width: u32, height: u32) -> OcrTextBlock {
+        OcrTextBlock {
+            text: text.to_string(),
+            confidence: 0.9,
+            bounds: OcrBounds {
+                x: f64::from(x) / 1000.0,
+                y: f64::from(y) / 1000.0,
+                width: f64::from(width) / 1000.0,
+                height: f64::from(height) / 1000.0,
+            },
+        }
+    }
+
+    fn result(blocks: Vec<OcrTextBlock>) -> OcrScanResult {
+        OcrScanResult {
+            text: blocks
+                .iter()
+                .map(|block| block.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            blocks,
+            qr_codes: Vec::new(),
+            image_width: 1000,
+            image_height: 1000,
+        }
+    }
+
+    fn markdown_of(blocks: Vec<OcrTextBlock>) -> String {
+        render(&result(blocks), OcrExportFormat::Markdown, "page").unwrap()
+    }
+
+    #[test]
+    fn reads_the_left_column_first() {
+        // Recognizers report blocks row by row, interleaving the columns.
+        let markdown = markdown_of(vec![
+            block("Left one", 50, 100, 300, 20),
+            block("Right one", 550, 100, 300, 20),
+            block("Left two", 50, 130, 300, 20),
+            block("Right two", 550, 130, 300, 20),
+            block("Left three", 50, 160, 300, 20),
+            block("Right three", 550, 160, 300, 20),
+        ]);
+        assert_eq!(
+            markdown,
+            "Left one Left two Left three\n\nRight one Right two Right three\n"
+        );
+    }
+
+    #[test]
+    fn tall_lines_become_headings() {
+        let markdown = markdown_of(vec![
+            block("Release notes", 50, 50, 400, 40),
+            block("What changed", 50, 120, 300, 28),
+            block("Body one", 50, 180, 600, 20),
+            block("body two", 50, 210, 600, 20),
+            block("body three", 50, 240, 600, 20),
+        ]);
+        assert_eq!(
+            markdown,
+            "# Release notes\n\n## What changed\n\nBody one body two body three\n"
+        );
+    }
+
+    #[test]
+    fn numbered_and_bulleted_lists() {
+        let markdown = markdown_of(vec![
+            block("Steps:", 50, 100, 200, 20),
+            block("1. Open the file", 50, 130, 300, 20),
+            block("2) Save it", 50, 160, 300, 20),
+            block("• First point", 50, 190, 300, 20),
+            block("- second *point*", 50, 220, 300, 20),
+            block("-5 degrees", 50, 300, 300, 20),
+        ]);
+        assert_eq!(
+            markdown,
+            concat!(
+                "Steps:\n\n",
+                "1. Open the file\n2. Save it\n- First point\n- second \\*point\\*\n\n",
+                "\\-5 degrees\n"
+            )
+        );
+    }
+
+    #[test]
+    fn joins_cjk_without_spaces_and_mends_hyphenation() {
+        let markdown = markdown_of(vec![
+            block("这是第一行", 50, 100, 300, 20),
+            block("第二行 and English", 50, 130, 300, 20),
+            block("A well-known exam-", 50, 300, 300, 20),
+            block("ple of Multi-", 50, 330, 300, 20),
+            block("Line text", 50, 360, 300, 20),
+        ]);
+        assert_eq!(
+            markdown,
+            "这是第一行第二行 and English\n\nA well-known example of Multi- Line text\n"
+        );
+    }
+
+    #[test]
+    fn escapes_xml_in_hocr_and_alto() {
+        let result = result(vec![block("if a<b && c>\"d\" 'e'", 50, 100, 500, 20)]);
+        let hocr = render(&result, OcrExportFormat::Hocr, "R&D <notes>").unwrap();
+        assert!(hocr.contains("<title>R&amp;D &lt;notes&gt;</title>"));
+        assert!(hocr.contains(">a&lt;b</span>"));
+        assert!(hocr.contains(">&amp;&amp;</span>"));
+        assert!(hocr.contains(">c&gt;&quot;d&quot;</span>"));
+        assert!(hocr.contains(">&apos;e&apos;</span>"));
+
+        let alto = render(&result, OcrExportFormat::Alto, "R&D <notes>").unwrap();
+        assert!(alto.contains("<fileName>R&amp;D &lt;notes&gt;</fileName>"));
+        assert!(alto.contains("CONTENT=\"a&lt;b\""));
+        assert!(alto.contains("CONTENT=\"c&gt;&quot;d&quot;\""));
+        for document in [&hocr, &alto] {
+            assert!(!document.contains("a<b"));
+            assert!(!document.contains("\"d\""));
+        }
+    }
+}
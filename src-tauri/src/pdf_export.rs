@@ -0,0 +1,651 @@
+// PDF export for captures and long screenshots. Tall images are cut into pages, preferring blank
+// rows as break points so lines of text are not split, and each page can carry an invisible OCR
+// text layer so the document is searchable and selectable.
+use crate::ocr;
+use image::RgbImage;
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, TextRenderingMode, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+/// 96 DPI: small captures are never enlarged beyond their on-screen size.
+const MAX_POINTS_PER_PIXEL: f32 = 0.75;
+/// How far above a full page to look for a blank row to break on.
+const PAGE_BREAK_SEARCH: f32 = 0.15;
+/// Mean luma change between neighbouring pixels below which a row counts as blank.
+const BLANK_ROW_THRESHOLD: f32 = 1.5;
+/// Largest page side viewers accept (200 inches); fitted pages are split beyond it.
+const MAX_PAGE_POINTS: f32 = 14_400.0;
+const TEXT_FONT: Name = Name(b"F0");
+/// Advance width of every glyph in the text layer font, in 1/1000 em.
+const TEXT_GLYPH_WIDTH: f32 = 500.0;
+const TEXT_SYSTEM_INFO: SystemInfo = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PageSize {
+    #[default]
+    A4,
+    Letter,
+    /// One page per image, sized to the image.
+    Fit,
+}
+
+impl PageSize {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "a4" => Some(PageSize::A4),
+            "letter" => Some(PageSize::Letter),
+            "fit" => Some(PageSize::Fit),
+            _ => None,
+        }
+    }
+
+    fn points(self, landscape: bool) -> Option<(f32, f32)> {
+        let (width, height) = match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::Letter => (612.0, 792.0),
+            PageSize::Fit => return None,
+        };
+        Some(if landscape {
+            (height, width)
+        } else {
+            (width, height)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PdfOptions {
+    pub page_size: PageSize,
+    pub landscape: bool,
+    /// Page margin in millimetres.
+    pub margin: f32,
+    /// Adds an invisible OCR text layer. OCR is macOS-only; elsewhere pages are exported without
+    /// text.
+    pub text_layer: bool,
+    pub title: Option<String>,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::A4,
+            landscape: false,
+            margin: 10.0,
+            text_layer: false,
+            title: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfSummary {
+    pub path: String,
+    pub pages: usize,
+    pub text_blocks: usize,
+}
+
+struct TextLine {
+    text: String,
+    /// Box in page points, origin bottom left.
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+struct PdfPage {
+    width: f32,
+    height: f32,
+    image: RgbImage,
+    /// Where the image is drawn, in page points.
+    image_rect: [f32; 4],
+    text: Vec<TextLine>,
+}
+
+fn flatten(image: &image::DynamicImage) -> RgbImage {
+    let rgba = image.to_rgba8();
+    let mut output = RgbImage::new(rgba.width(), rgba.height());
+    for (source, target) in rgba.pixels().zip(output.pixels_mut()) {
+        let alpha = u16::from(source[3]);
+        for channel in 0..3 {
+            let value = u16::from(source[channel]) * alpha + 255 * (255 - alpha);
+            target[channel] = (value / 255) as u8;
+        }
+    }
+    output
+}
+
+fn row_busyness(image: &RgbImage, y: u32) -> f32 {
+    let luma = |x: u32| {
+        let [r, g, b] = image.get_pixel(x, y).0;
+        0.299 * f32::from(r) + 0.587 * f32::from(g) + 0.114 * f32::from(b)
+    };
+    let total = (1..image.width())
+        .map(|x| (luma(x) - luma(x - 1)).abs())
+        .sum::<f32>();
+    total / image.width().max(2) as f32
+}
+
+/// End row (exclusive) for a page starting at `top` that may hold `rows` rows: the lowest blank
+/// row near the limit, or the limit itself when there is none.
+fn page_break(image: &RgbImage, top: u32, rows: u32) -> u32 {
+    let limit = top + rows;
+    if limit >= image.height() {
+        return image.height();
+    }
+    let search = ((rows as f32 * PAGE_BREAK_SEARCH) as u32).max(1);
+    (limit - search..limit)
+        .rev()
+        .find(|&y| row_busyness(image, y) < BLANK_ROW_THRESHOLD)
+        .map(|y| y + 1)
+        .unwrap_or(limit)
+}
+
+fn text_layer(slice: &RgbImage, image_rect: [f32; 4]) -> Result<Vec<TextLine>, String> {
+    let mut png = Vec::new();
+    image::DynamicImage::ImageRgb8(slice.clone())
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode OCR page: {}", e))?;
+    let result = ocr::recognize(png)?;
+    let [left, bottom, width, height] = image_rect;
+    Ok(result
+        .blocks
+        .into_iter()
+        .filter(|block| !block.text.trim().is_empty())
+        .map(|block| TextLine {
+            text: block.text.trim().to_string(),
+            x: left + block.bounds.x as f32 * width,
+            y: bottom + (1.0 - (block.bounds.y + block.bounds.height) as f32) * height,
+            width: block.bounds.width as f32 * width,
+            height: block.bounds.height as f32 * height,
+        })
+        .collect())
+}
+
+/// Lays `image` out over as many pages as it needs.
+fn paginate(image: &RgbImage, options: &PdfOptions) -> Vec<PdfPage> {
+    let margin = options.margin.clamp(0.0, 50.0) * POINTS_PER_MM;
+    let page = options.page_size.points(options.landscape);
+    // Fitted pages grow with the image, but no wider than viewers accept.
+    let content_width = page.map_or(MAX_PAGE_POINTS, |(width, _)| width) - 2.0 * margin;
+    let content_height = page.map_or(MAX_PAGE_POINTS, |(_, height)| height) - 2.0 * margin;
+    let scale = (content_width / image.width() as f32).min(MAX_POINTS_PER_PIXEL);
+    let rows = (content_height / scale).floor().max(1.0) as u32;
+
+    let mut pages = Vec::new();
+    let mut top = 0;
+    while top < image.height() {
+        let bottom = page_break(image, top, rows);
+        let slice =
+            image::imageops::crop_imm(image, 0, top, image.width(), bottom - top).to_image();
+        let draw_width = slice.width() as f32 * scale;
+        let draw_height = slice.height() as f32 * scale;
+        let (page_width, page_height) =
+            page.unwrap_or((draw_width + 2.0 * margin, draw_height + 2.0 * margin));
+        let image_rect = [
+            (page_width - draw_width) / 2.0,
+            page_height - margin - draw_height,
+            draw_width,
+            draw_height,
+        ];
+        let text = if options.text_layer {
+            text_layer(&slice, image_rect).unwrap_or_else(|error| {
+                println!("[xshot][pdf][rust] text layer skipped: {}", error);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        pages.push(PdfPage {
+            width: page_width,
+            height: page_height,
+            image: slice,
+            image_rect,
+            text,
+        });
+        top = bottom;
+    }
+    pages
+}
+
+fn put_u16(output: &mut Vec<u8>, value: u16) {
+    output.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_u32(output: &mut Vec<u8>, value: u32) {
+    output.extend_from_slice(&value.to_be_bytes());
+}
+
+fn table_checksum(table: &[u8]) -> u32 {
+    table.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// A TrueType font with a single empty glyph. Text drawn in invisible mode never shows glyphs, so
+/// this is all the text layer needs to embed, and it works for every script because characters
+/// are mapped through `/ToUnicode` rather than the font.
+fn glyphless_font() -> Vec<u8> {
+    let mut head = Vec::new();
+    put_u32(&mut head, 0x0001_0000); // version
+    put_u32(&mut head, 0x0001_0000); // fontRevision
+    put_u32(&mut head, 0); // checkSumAdjustment, patched below
+    put_u32(&mut head, 0x5F0F_3CF5); // magicNumber
+    put_u16(&mut head, 0); // flags
+    put_u16(&mut head, 1000); // unitsPerEm
+    head.extend_from_slice(&[0; 16]); // created, modified
+    head.extend_from_slice(&[0; 8]); // xMin, yMin, xMax, yMax
+    put_u16(&mut head, 0); // macStyle
+    put_u16(&mut head, 3); // lowestRecPPEM
+    put_u16(&mut head, 2); // fontDirectionHint
+    put_u16(&mut head, 0); // indexToLocFormat: short offsets
+    put_u16(&mut head, 0); // glyphDataFormat
+
+    let mut hhea = Vec::new();
+    put_u32(&mut hhea, 0x0001_0000);
+    put_u16(&mut hhea, 800); // ascender
+    put_u16(&mut hhea, (-200i16) as u16); // descender
+    put_u16(&mut hhea, 0); // lineGap
+    put_u16(&mut hhea, TEXT_GLYPH_WIDTH as u16); // advanceWidthMax
+    hhea.extend_from_slice(&[0; 6]); // minLeftSideBearing, minRightSideBearing, xMaxExtent
+    put_u16(&mut hhea, 1); // caretSlopeRise
+    hhea.extend_from_slice(&[0; 14]); // caretSlopeRun, caretOffset, reserved, metricDataFormat
+    put_u16(&mut hhea, 1); // numberOfHMetrics
+
+    let mut maxp = Vec::new();
+    put_u32(&mut maxp, 0x0001_0000);
+    put_u16(&mut maxp, 1); // numGlyphs
+    maxp.extend_from_slice(&[0; 8]); // maxPoints .. maxCompositeContours
+    put_u16(&mut maxp, 2); // maxZones
+    maxp.extend_from_slice(&[0; 16]);
+
+    let mut hmtx = Vec::new();
+    put_u16(&mut hmtx, TEXT_GLYPH_WIDTH as u16);
+    put_u16(&mut hmtx, 0);
+
+    let loca = vec![0u8; 4];
+
+    let mut post = Vec::new();
+    put_u32(&mut post, 0x0003_0000);
+    post.extend_from_slice(&[0; 28]);
+
+    // Tables in tag order.
+    let tables: [(&[u8; 4], Vec<u8>); 7] = [
+        (b"glyf", Vec::new()),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"loca", loca),
+        (b"maxp", maxp),
+        (b"post", post),
+    ];
+    let mut font = Vec::new();
+    put_u32(&mut font, 0x0001_0000);
+    put_u16(&mut font, tables.len() as u16);
+    put_u16(&mut font, 64); // searchRange
+    put_u16(&mut font, 2); // entrySelector
+    put_u16(&mut font, tables.len() as u16 * 16 - 64); // rangeShift
+
+    let mut offset = 12 + 16 * tables.len();
+    let mut body = Vec::new();
+    let mut head_offset = 0;
+    for (tag, table) in &tables {
+        if *tag == b"head" {
+            head_offset = offset;
+        }
+        font.extend_from_slice(*tag);
+        put_u32(&mut font, table_checksum(table));
+        put_u32(&mut font, offset as u32);
+        put_u32(&mut font, table.len() as u32);
+        body.extend_from_slice(table);
+        body.resize(body.len().next_multiple_of(4), 0);
+        offset = 12 + 16 * tables.len() + body.len();
+    }
+    font.extend_from_slice(&body);
+    let adjustment = 0xB1B0_AFBAu32.wrapping_sub(table_checksum(&font));
+    font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    font
+}
+
+/// UTF-16 code units used as CIDs: characters outside the Basic Multilingual Plane are dropped.
+fn text_cids(text: &str) -> Vec<(u16, char)> {
+    text.chars()
+        .filter_map(|character| {
+            let code = u32::from(character);
+            (code <= 0xFFFF).then_some((code as u16, character))
+        })
+        .collect()
+}
+
+fn write_text(content: &mut Content, line: &TextLine, used: &mut BTreeSet<(u16, char)>) {
+    let cids = text_cids(&line.text);
+    if cids.is_empty() || line.width <= 0.0 || line.height <= 0.0 {
+        return;
+    }
+    let font_size = line.height;
+    let natural_width = cids.len() as f32 * TEXT_GLYPH_WIDTH / 1000.0 * font_size;
+    let mut bytes = Vec::with_capacity(cids.len() * 2);
+    for &(cid, character) in &cids {
+        bytes.extend_from_slice(&cid.to_be_bytes());
+        used.insert((cid, character));
+    }
+    content
+        .set_font(TEXT_FONT, font_size)
+        .set_horizontal_scaling(line.width / natural_width * 100.0)
+        .set_text_matrix([1.0, 0.0, 0.0, 1.0, line.x, line.y + 0.2 * font_size])
+        .show(Str(&bytes));
+}
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    miniz_oxide::deflate::compress_to_vec_zlib(data, 6)
+}
+
+fn build_pdf(pages: &[PdfPage], title: Option<&str>) -> Vec<u8> {
+    let mut next_id = 1;
+    let mut alloc = || {
+        let id = Ref::new(next_id);
+        next_id += 1;
+        id
+    };
+    let catalog_id = alloc();
+    let page_tree_id = alloc();
+    let info_id = alloc();
+    let has_text = pages.iter().any(|page| !page.text.is_empty());
+    let font_ids = has_text.then(|| (alloc(), alloc(), alloc(), alloc(), alloc()));
+    let page_ids = pages
+        .iter()
+        .map(|_| (alloc(), alloc(), alloc()))
+        .collect::<Vec<_>>();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().map(|(page_id, _, _)| *page_id))
+        .count(pages.len() as i32);
+    let mut info = pdf.document_info(info_id);
+    info.producer(TextStr("xshot"));
+    if let Some(title) = title {
+        info.title(TextStr(title));
+    }
+    info.finish();
+
+    let mut used = BTreeSet::new();
+    for (page, &(page_id, image_id, content_id)) in pages.iter().zip(&page_ids) {
+        let image_name = Name(b"Im0");
+        let mut content = Content::new();
+        let [x, y, width, height] = page.image_rect;
+        content
+            .save_state()
+            .transform([width, 0.0, 0.0, height, x, y])
+            .x_object(image_name)
+            .restore_state();
+        if !page.text.is_empty() {
+            content
+                .begin_text()
+                .set_text_rendering_mode(TextRenderingMode::Invisible);
+            for line in &page.text {
+                write_text(&mut content, line, &mut used);
+            }
+            content.end_text();
+        }
+        let content = compress(&content.finish());
+        pdf.stream(content_id, &content).filter(Filter::FlateDecode);
+
+        let samples = compress(page.image.as_raw());
+        let mut image = pdf.image_xobject(image_id, &samples);
+        image.filter(Filter::FlateDecode);
+        image
+            .width(page.image.width() as i32)
+            .height(page.image.height() as i32)
+            .bits_per_component(8);
+        image.color_space().device_rgb();
+        image.finish();
+
+        let mut pdf_page = pdf.page(page_id);
+        pdf_page
+            .parent(page_tree_id)
+            .media_box(Rect::new(0.0, 0.0, page.width, page.height))
+            .contents(content_id);
+        let mut resources = pdf_page.resources();
+        resources.x_objects().pair(image_name, image_id);
+        if let Some((type0_id, ..)) = font_ids {
+            resources.fonts().pair(TEXT_FONT, type0_id);
+        }
+    }
+
+    if let Some((type0_id, cid_font_id, descriptor_id, font_file_id, cmap_id)) = font_ids {
+        let base_font = Name(b"GlyphLessFont");
+        pdf.type0_font(type0_id)
+            .base_font(base_font)
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_font_id)
+            .to_unicode(cmap_id);
+        pdf.cid_font(cid_font_id)
+            .subtype(CidFontType::Type2)
+            .base_font(base_font)
+            .system_info(TEXT_SYSTEM_INFO)
+            .font_descriptor(descriptor_id)
+            .default_width(TEXT_GLYPH_WIDTH)
+            .cid_to_gid_map_predefined(Name(b"Identity"));
+        pdf.font_descriptor(descriptor_id)
+            .name(base_font)
+            .flags(FontFlags::SYMBOLIC)
+            .bbox(Rect::new(0.0, -200.0, TEXT_GLYPH_WIDTH, 800.0))
+            .italic_angle(0.0)
+            .ascent(800.0)
+            .descent(-200.0)
+            .cap_height(800.0)
+            .stem_v(80.0)
+            .font_file2(font_file_id);
+        let font = glyphless_font();
+        let font_data = compress(&font);
+        pdf.stream(font_file_id, &font_data)
+            .filter(Filter::FlateDecode)
+            .pair(Name(b"Length1"), font.len() as i32);
+
+        let mut cmap = UnicodeCmap::new(Name(b"xshot-UCS"), TEXT_SYSTEM_INFO);
+        for (cid, character) in used {
+            cmap.pair(cid, character);
+        }
+        pdf.cmap(cmap_id, &cmap.finish());
+    }
+
+    pdf.finish()
+}
+
+/// Writes `images` as one PDF at `output`, each image paginated in order.
+pub fn export(
+    images: &[image::DynamicImage],
+    output: &Path,
+    options: &PdfOptions,
+) -> Result<PdfSummary, String> {
+    if images.is_empty() {
+        return Err("No images to export".to_string());
+    }
+    let mut pages = Vec::new();
+    for image in images {
+        pages.extend(paginate(&flatten(image), options));
+    }
+    let title = options
+        .title
+        .as_deref()
+        .or_else(|| output.file_stem().and_then(|stem| stem.to_str()));
+    let bytes = build_pdf(&pages, title);
+    std::fs::write(output, bytes)
+        .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+    Ok(PdfSummary {
+        path: output.to_string_lossy().to_string(),
+        pages: pages.len(),
+        text_blocks: pages.iter().map(|page| page.text.len()).sum(),
+    })
+}
+
+fn log_summary(summary: &PdfSummary, images: usize, options: &PdfOptions) {
+    println!(
+        "[xshot][pdf][rust] path={} images={} pages={} text_blocks={} page_size={:?} margin_mm={}",
+        summary.path, images, summary.pages, summary.text_blocks, options.page_size, options.margin
+    );
+}
+
+#[tauri::command]
+pub async fn export_pdf(
+    blob_data: Vec<u8>,
+    path: String,
+    options: PdfOptions,
+) -> Result<PdfSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let image = image::load_from_memory(&blob_data)
+            .map_err(|e| format!("Failed to decode image: {}", e))?;
+        let summary = export(&[image], Path::new(&path), &options)?;
+        log_summary(&summary, 1, &options);
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("PDF export task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn export_pdf_from_files(
+    paths: Vec<String>,
+    output: String,
+    options: PdfOptions,
+) -> Result<PdfSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let images = paths
+            .iter()
+            .map(|path| {
+                image::open(path).map_err(|e| format!("Failed to open image {}: {}", path, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let summary = export(&images, Path::new(&output), &options)?;
+        log_summary(&summary, images.len(), &options);
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("PDF export task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ab_glyph::{Font, FontRef, GlyphId};
+
+    /// Bands of noisy "text" rows separated by blank gaps.
+    fn long_screenshot(width: u32, height: u32) -> RgbImage {
+        RgbImage::from_fn(width, height, |x, y| {
+            if y % 30 < 20 && (x * 7 + y * 3) % 11 < 4 {
+                image::Rgb([20, 20, 20])
+            } else {
+                image::Rgb([250, 250, 250])
+            }
+        })
+    }
+
+    fn options(page_size: PageSize) -> PdfOptions {
+        PdfOptions {
+            page_size,
+            ..PdfOptions::default()
+        }
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[test]
+    fn glyphless_font_parses() {
+        let bytes = glyphless_font();
+        let font = FontRef::try_from_slice(&bytes).unwrap();
+        assert_eq!(font.glyph_count(), 1);
+        assert_eq!(font.units_per_em(), Some(1000.0));
+        assert_eq!(font.h_advance_unscaled(GlyphId(0)), TEXT_GLYPH_WIDTH);
+        assert!(font.outline(GlyphId(0)).is_none());
+        assert_eq!(table_checksum(&bytes), 0xB1B0_AFBA);
+    }
+
+    #[test]
+    fn page_breaks_prefer_blank_rows() {
+        let image = long_screenshot(200, 300);
+        // Rows 80..90 are blank, so a page that would end at 95 ends after them instead.
+        assert_eq!(page_break(&image, 0, 95), 90);
+        assert_eq!(page_break(&image, 0, 400), 300);
+
+        let busy = RgbImage::from_fn(200, 300, |x, _| image::Rgb([(x % 2 * 255) as u8; 3]));
+        assert_eq!(page_break(&busy, 0, 95), 95);
+    }
+
+    #[test]
+    fn tall_images_split_on_blank_rows() {
+        let image = long_screenshot(800, 6000);
+        let pages = paginate(&image, &options(PageSize::A4));
+        assert_eq!(pages.len(), 6);
+
+        let mut top = 0;
+        for (index, page) in pages.iter().enumerate() {
+            assert_eq!((page.width, page.height), (595.28, 841.89));
+            assert_eq!(page.image.width(), 800);
+            let bottom = top + page.image.height();
+            if index + 1 < pages.len() {
+                assert!(row_busyness(&image, bottom - 1) < BLANK_ROW_THRESHOLD);
+            }
+            let [_, y, _, height] = page.image_rect;
+            assert!(y >= 10.0 * POINTS_PER_MM - 0.01 && y + height <= page.height);
+            top = bottom;
+        }
+        assert_eq!(top, image.height());
+    }
+
+    #[test]
+    fn fitted_pages_stay_within_viewer_limits() {
+        let small = paginate(&long_screenshot(400, 300), &options(PageSize::Fit));
+        assert_eq!(small.len(), 1);
+        assert_eq!(small[0].image_rect[2], 400.0 * MAX_POINTS_PER_PIXEL);
+
+        let wide = paginate(&long_screenshot(30_000, 200), &options(PageSize::Fit));
+        assert_eq!(wide.len(), 1);
+        assert!(wide[0].width <= MAX_PAGE_POINTS + 0.01);
+
+        let tall = paginate(&long_screenshot(400, 60_000), &options(PageSize::Fit));
+        assert!(tall.len() > 1);
+        assert!(tall
+            .iter()
+            .all(|page| page.height <= MAX_PAGE_POINTS + 0.01));
+    }
+
+    #[test]
+    fn built_pdf_lists_every_page() {
+        let mut pages = paginate(&long_screenshot(800, 3000), &options(PageSize::Letter));
+        assert_eq!(pages.len(), 3);
+        let pdf = build_pdf(&pages, Some("Long capture"));
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(contains(&pdf, b"/Count 3"));
+        assert!(contains(&pdf, b"(Long capture)"));
+        assert!(!contains(&pdf, b"/FontFile2"));
+
+        pages[0].text.push(TextLine {
+            text: "Hello 世界".into(),
+            x: 40.0,
+            y: 700.0,
+            width: 120.0,
+            height: 12.0,
+        });
+        let pdf = build_pdf(&pages, None);
+        assert!(contains(&pdf, b"/Count 3"));
+        assert!(contains(&pdf, b"/FontFile2"));
+        assert!(contains(&pdf, b"<4E16>"));
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const PIN_STORE_FILE: &str = "pins.json";
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+fn default_always_on_top() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinRecord {
+    pub label: String,
+    pub image_file: String,
+    pub image_width: f64,
+    pub image_height: f64,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+    #[serde(default)]
+    pub click_through: bool,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default = "default_always_on_top")]
+    pub always_on_top: bool,
+    #[serde(default)]
+    pub monitor: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PinStoreFile {
+    enabled: bool,
+    pins: Vec<PinRecord>,
+}
+
+pub struct PersistentPinStore {
+    dir: PathBuf,
+    state: Mutex<PinStoreFile>,
+    exiting: AtomicBool,
+    save_generation: AtomicU64,
+}
+
+impl PersistentPinStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let dir = app
+            .path()
+            .app_data_dir()
+            .unwrap_or_else(|_| std::env::temp_dir().join("xshot"))
+            .join("pins");
+        let state = std::fs::read(dir.join(PIN_STORE_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<PinStoreFile>(&bytes).ok())
+            .unwrap_or_default();
+
+        Self {
+            dir,
+            state: Mutex::new(state),
+            exiting: AtomicBool::new(false),
+            save_generation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state
+            .lock()
+            .map(|state| state.enabled)
+            .unwrap_or(false)
+    }
+
+    pub fn records(&self) -> Vec<PinRecord> {
+        self.state
+            .lock()
+            .map(|state| state.pins.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn image_path(&self, record: &PinRecord) -> PathBuf {
+        self.dir.join(&record.image_file)
+    }
+
+    pub fn mark_exiting(&self) {
+        self.exiting.store(true, Ordering::SeqCst);
+    }
+
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        {
+            let mut state = self
+                .state
+                .lock()
+                .map_err(|_| "Failed to lock pin store".to_string())?;
+            state.enabled = enabled;
+            if !enabled {
+                for record in state.pins.drain(..) {
+                    let _ = std::fs::remove_file(self.dir.join(&record.image_file));
+                }
+            }
+        }
+        self.save()
+    }
+
+    pub fn insert(&self, record: PinRecord, png_bytes: &[u8]) -> Result<(), String> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to prepare pin store directory: {}", e))?;
+        std::fs::write(self.dir.join(&record.image_file), png_bytes)
+            .map_err(|e| format!("Failed to persist pinned image: {}", e))?;
+        {
+            let mut state = self
+                .state
+                .lock()
+                .map_err(|_| "Failed to lock pin store".to_string())?;
+            state.pins.retain(|pin| pin.label != record.label);
+            state.pins.push(record);
+        }
+        self.save()
+    }
+
+    pub fn remove(&self, label: &str) -> Result<(), String> {
+        if self.exiting.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let removed = {
+            let mut state = self
+                .state
+                .lock()
+                .map_err(|_| "Failed to lock pin store".to_string())?;
+            let index = state.pins.iter().position(|pin| pin.label == label);
+            index.map(|index| state.pins.remove(index))
+        };
+        let Some(record) = removed else {
+            return Ok(());
+        };
+
+        let _ = std::fs::remove_file(self.dir.join(&record.image_file));
+        self.save()
+    }
+
+    pub fn update<F: FnOnce(&mut PinRecord)>(&self, label: &str, update: F) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        let Some(record) = state.pins.iter_mut().find(|pin| pin.label == label) else {
+            return false;
+        };
+        update(record);
+        true
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let bytes = {
+            let state = self
+                .state
+                .lock()
+                .map_err(|_| "Failed to lock pin store".to_string())?;
+            serde_json::to_vec_pretty(&*state).map_err(|e| e.to_string())?
+        };
+
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to prepare pin store directory: {}", e))?;
+        let path = self.dir.join(PIN_STORE_FILE);
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, bytes).map_err(|e| format!("Failed to save pins: {}", e))?;
+        std::fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save pins: {}", e))
+    }
+}
+
+/// Coalesces the burst of move/resize events produced while dragging a pin into one write.
+pub fn schedule_save(app: &AppHandle) {
+    let generation = app
+        .state::<PersistentPinStore>()
+        .save_generation
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        std::thread::sleep(Duration::from_millis(400));
+        let store = app.state::<PersistentPinStore>();
+        if store.save_generation.load(Ordering::SeqCst) == generation {
+            if let Err(error) = store.save() {
+                eprintln!("Failed to save pinned windows: {}", error);
+            }
+        }
+    });
+}
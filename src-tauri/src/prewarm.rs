@@ -0,0 +1,186 @@
+use crate::{
+    capture_monitor_image, capture_monitors, frame::FrameFormat, CaptureMonitor,
+    PreparedCaptureStore,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const DEFAULT_INTERVAL_MS: u64 = 2000;
+const MIN_INTERVAL_MS: u64 = 500;
+const MAX_INTERVAL_MS: u64 = 10_000;
+/// Oldest prewarmed frame `start_capture` uses unless the caller asks otherwise.
+pub const DEFAULT_MAX_FRAME_AGE_MS: f64 = 3000.0;
+/// Prewarmed frames live in `PreparedCaptureStore` under this capture id until a capture
+/// takes them.
+pub const PREWARM_CAPTURE_ID: &str = "prewarm";
+
+/// When a monitor's frame was grabbed, and whether it came from the rolling background capture
+/// rather than a capture taken after the shortcut.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FrameStamp {
+    #[serde(rename = "frameCapturedAtMs")]
+    pub captured_at_ms: f64,
+    #[serde(rename = "framePrewarmed")]
+    pub prewarmed: bool,
+}
+
+/// What a prewarmed frame in `PreparedCaptureStore` was grabbed from.
+struct PrewarmedFrame {
+    monitor: CaptureMonitor,
+    format: FrameFormat,
+    captured_at_ms: f64,
+}
+
+/// The refresh loop's generation and the metadata of the frames it stored.
+#[derive(Default)]
+pub struct PrewarmState {
+    generation: AtomicU64,
+    frames: Mutex<HashMap<String, PrewarmedFrame>>,
+}
+
+fn prewarm_key(label: &str) -> (String, String) {
+    (PREWARM_CAPTURE_ID.to_string(), label.to_string())
+}
+
+fn clear(app: &AppHandle) {
+    let state = app.state::<PrewarmState>();
+    let store = app.state::<PreparedCaptureStore>();
+    if let (Ok(mut frames), Ok(mut captures)) = (state.frames.lock(), store.0.lock()) {
+        frames.clear();
+        captures.retain(|(capture_id, _), _| capture_id != PREWARM_CAPTURE_ID);
+    }
+}
+
+/// Takes the frames that still match the monitor layout, were grabbed in `format` and are at
+/// most `max_age_ms` old. Taken frames are refilled by the next refresh.
+pub fn take_fresh(
+    app: &AppHandle,
+    monitors: &[CaptureMonitor],
+    format: FrameFormat,
+    max_age_ms: f64,
+) -> HashMap<String, (Vec<u8>, FrameStamp)> {
+    let state = app.state::<PrewarmState>();
+    let store = app.state::<PreparedCaptureStore>();
+    let (Ok(mut frames), Ok(mut captures)) = (state.frames.lock(), store.0.lock()) else {
+        return HashMap::new();
+    };
+    let now = crate::unix_epoch_ms();
+
+    monitors
+        .iter()
+        .filter_map(|monitor| {
+            let usable = frames.get(&monitor.label).is_some_and(|frame| {
+                frame.format == format
+                    && now - frame.captured_at_ms <= max_age_ms
+                    && crate::monitor_watcher::same_geometry(&frame.monitor, monitor)
+            });
+            if !usable {
+                return None;
+            }
+            let frame = frames.remove(&monitor.label)?;
+            let bytes = captures.remove(&prewarm_key(&monitor.label))?;
+            let stamp = FrameStamp {
+                captured_at_ms: frame.captured_at_ms,
+                prewarmed: true,
+            };
+            Some((monitor.label.clone(), (bytes.into_bytes(), stamp)))
+        })
+        .collect()
+}
+
+// Refreshing while the overlay is up would capture the overlay itself.
+fn overlay_visible(app: &AppHandle) -> bool {
+    crate::screenshot_windows(app)
+        .iter()
+        .any(|(_, window)| window.is_visible().unwrap_or(false))
+}
+
+fn refresh(app: &AppHandle, format: FrameFormat, generation: u64) {
+    let state = app.state::<PrewarmState>();
+    if overlay_visible(app) {
+        return;
+    }
+    let Ok(monitors) = capture_monitors() else {
+        return;
+    };
+
+    let mut refreshed = Vec::new();
+    for monitor in monitors {
+        match capture_monitor_image(
+            &monitor,
+            format,
+            PREWARM_CAPTURE_ID,
+            PREWARM_CAPTURE_ID,
+            false,
+        ) {
+            Ok(bytes) => {
+                let frame = PrewarmedFrame {
+                    monitor,
+                    format,
+                    captured_at_ms: crate::unix_epoch_ms(),
+                };
+                refreshed.push((frame, bytes));
+            }
+            Err(error) => eprintln!(
+                "[xshot][prewarm] monitor={} error={:?}",
+                monitor.label, error
+            ),
+        }
+    }
+
+    // A capture may have started, or prewarming been reconfigured, while grabbing.
+    if overlay_visible(app) || state.generation.load(Ordering::SeqCst) != generation {
+        return;
+    }
+    let store = app.state::<PreparedCaptureStore>();
+    if let (Ok(mut frames), Ok(mut captures)) = (state.frames.lock(), store.0.lock()) {
+        frames.clear();
+        captures.retain(|(capture_id, _), _| capture_id != PREWARM_CAPTURE_ID);
+        for (frame, bytes) in refreshed {
+            captures.insert(
+                prewarm_key(&frame.monitor.label),
+                crate::protocol::ServedImage::new(bytes),
+            );
+            frames.insert(frame.monitor.label.clone(), frame);
+        }
+    }
+}
+
+/// Keeps a recent frame per monitor so `start_capture` can show the overlay without waiting
+/// for a capture. Each call restarts the loop with the new settings.
+#[tauri::command]
+pub fn set_capture_prewarm(
+    app: AppHandle,
+    enabled: bool,
+    interval_ms: Option<u64>,
+    frame_format: Option<FrameFormat>,
+) {
+    let state = app.state::<PrewarmState>();
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    clear(&app);
+    if !enabled {
+        return;
+    }
+
+    let interval = Duration::from_millis(
+        interval_ms
+            .unwrap_or(DEFAULT_INTERVAL_MS)
+            .clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS),
+    );
+    let format = frame_format.unwrap_or_default();
+    std::thread::spawn(move || {
+        while app
+            .state::<PrewarmState>()
+            .generation
+            .load(Ordering::SeqCst)
+            == generation
+        {
+            refresh(&app, format, generation);
+            std::thread::sleep(interval);
+        }
+    });
+}
@@ -0,0 +1,749 @@
+// .xshot project bundles: a zip holding the untouched capture next to the annotation scene, crop,
+// watermark settings and OCR result, so a capture can be re-opened for editing or re-exported
+// exactly instead of only existing as baked-in pixels.
+use crate::annotation_scene::{self, AnnotationScene};
+use crate::visible_watermark::{self, VisibleWatermarkOptions};
+use crate::watermark;
+use crate::watermark_signing::{self, SignatureScheme, SigningKeys};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const PROJECT_VERSION: u32 = 1;
+pub const PROJECT_EXTENSION: &str = "xshot";
+const MANIFEST_ENTRY: &str = "manifest.json";
+const ORIGINAL_ENTRY: &str = "original.png";
+const SCENE_ENTRY: &str = "scene.json";
+const OCR_ENTRY: &str = "ocr.json";
+/// The visible watermark logo, bundled because its path only exists on the saving machine. The
+/// manifest names this entry as the logo path.
+const LOGO_ENTRY: &str = "logo.png";
+
+/// A pixel rectangle, in original-capture pixels unless noted otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CropRect {
+    fn clamp_to(self, width: u32, height: u32) -> Option<CropRect> {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        let crop = CropRect {
+            x,
+            y,
+            width: self.width.min(width - x),
+            height: self.height.min(height - y),
+        };
+        (crop.width > 0 && crop.height > 0).then_some(crop)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectWatermark {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visible: Option<VisibleWatermarkOptions>,
+    /// Hidden watermark text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden: Option<String>,
+    /// How the hidden watermark is signed on export, if at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureScheme>,
+}
+
+/// Everything in a project except the original capture, which travels as image bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDocument {
+    #[serde(default)]
+    pub capture_id: Option<String>,
+    #[serde(default)]
+    pub crop: Option<CropRect>,
+    pub scene: AnnotationScene,
+    #[serde(default)]
+    pub watermark: ProjectWatermark,
+    /// The editor's OCR result, kept as-is.
+    #[serde(default)]
+    pub ocr: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectManifest {
+    version: u32,
+    app_version: String,
+    saved_at_ms: f64,
+    original_width: u32,
+    original_height: u32,
+    #[serde(default)]
+    capture_id: Option<String>,
+    #[serde(default)]
+    crop: Option<CropRect>,
+    #[serde(default)]
+    watermark: ProjectWatermark,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreparedProject {
+    #[serde(flatten)]
+    pub document: ProjectDocument,
+    /// The crop mapped into the editor frame the original was imported into.
+    pub frame_crop: CropRect,
+}
+
+/// Projects opened in the editor, keyed by capture ID until the screenshot window takes them.
+#[derive(Default)]
+pub struct PreparedProjectStore(Mutex<HashMap<String, PreparedProject>>);
+
+impl PreparedProjectStore {
+    pub fn insert(&self, capture_id: String, project: PreparedProject) -> Result<(), String> {
+        self.0
+            .lock()
+            .map_err(|_| "Failed to lock prepared project store".to_string())?
+            .insert(capture_id, project);
+        Ok(())
+    }
+}
+
+struct ImportedOriginal {
+    capture_id: String,
+    image: RgbaImage,
+    placed: CropRect,
+}
+
+/// The image behind the latest imported capture (a file, the clipboard or an opened project), so
+/// saving it as a project stores those pixels instead of the padded editor frame. Only one capture
+/// is edited at a time, so one slot is enough.
+#[derive(Default)]
+pub struct ImportedOriginalStore(Mutex<Option<ImportedOriginal>>);
+
+impl ImportedOriginalStore {
+    pub fn replace(
+        &self,
+        capture_id: String,
+        image: RgbaImage,
+        placed: CropRect,
+    ) -> Result<(), String> {
+        *self
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock imported original store".to_string())? =
+            Some(ImportedOriginal {
+                capture_id,
+                image,
+                placed,
+            });
+        Ok(())
+    }
+
+    fn get(&self, capture_id: &str) -> Result<Option<(RgbaImage, CropRect)>, String> {
+        Ok(self
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock imported original store".to_string())?
+            .as_ref()
+            .filter(|imported| imported.capture_id == capture_id)
+            .map(|imported| (imported.image.clone(), imported.placed)))
+    }
+}
+
+/// Maps the document's crop from `original` pixels into the editor frame, where the original was
+/// drawn into `placed`.
+pub fn frame_crop(document: &ProjectDocument, original: &RgbaImage, placed: CropRect) -> CropRect {
+    let crop = document.crop.unwrap_or(CropRect {
+        x: 0,
+        y: 0,
+        width: original.width(),
+        height: original.height(),
+    });
+    let scale_x = placed.width as f64 / original.width().max(1) as f64;
+    let scale_y = placed.height as f64 / original.height().max(1) as f64;
+    CropRect {
+        x: placed.x + (crop.x as f64 * scale_x).round() as u32,
+        y: placed.y + (crop.y as f64 * scale_y).round() as u32,
+        width: ((crop.width as f64 * scale_x).round() as u32).max(1),
+        height: ((crop.height as f64 * scale_y).round() as u32).max(1),
+    }
+}
+
+pub struct Project {
+    pub original: RgbaImage,
+    pub document: ProjectDocument,
+}
+
+fn zip_error(path: &Path, error: impl std::fmt::Display) -> String {
+    format!("Failed to write project {}: {}", path.display(), error)
+}
+
+/// The visible watermark's logo file, if it has one.
+fn logo_path(watermark: &ProjectWatermark) -> Option<&str> {
+    watermark
+        .visible
+        .as_ref()?
+        .logo
+        .as_ref()
+        .map(|logo| logo.path.as_str())
+        .filter(|path| !path.is_empty())
+}
+
+fn set_logo_path(watermark: &mut ProjectWatermark, path: String) {
+    if let Some(logo) = watermark
+        .visible
+        .as_mut()
+        .and_then(|visible| visible.logo.as_mut())
+    {
+        logo.path = path;
+    }
+}
+
+/// Writes the project next to `path` first and renames it into place, so a failed save never
+/// leaves a truncated project behind.
+pub fn save(path: &Path, original: &RgbaImage, document: &ProjectDocument) -> Result<(), String> {
+    document.scene.validate()?;
+    let mut watermark = document.watermark.clone();
+    let logo = match logo_path(&watermark) {
+        Some(logo_path) => {
+            let logo = image::open(logo_path)
+                .map_err(|e| format!("Failed to open logo {}: {}", logo_path, e))?
+                .to_rgba8();
+            set_logo_path(&mut watermark, LOGO_ENTRY.to_string());
+            Some(crate::encode_rgba_png_fast(&logo)?)
+        }
+        None => None,
+    };
+    let manifest = ProjectManifest {
+        version: PROJECT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        saved_at_ms: crate::unix_epoch_ms(),
+        original_width: original.width(),
+        original_height: original.height(),
+        capture_id: document.capture_id.clone(),
+        crop: document.crop,
+        watermark,
+    };
+    let mut entries = vec![
+        (
+            MANIFEST_ENTRY,
+            serde_json::to_vec_pretty(&manifest).map_err(|e| zip_error(path, e))?,
+        ),
+        (ORIGINAL_ENTRY, crate::encode_rgba_png_fast(original)?),
+        (
+            SCENE_ENTRY,
+            serde_json::to_vec_pretty(&document.scene).map_err(|e| zip_error(path, e))?,
+        ),
+    ];
+    if let Some(ocr) = &document.ocr {
+        entries.push((
+            OCR_ENTRY,
+            serde_json::to_vec_pretty(ocr).map_err(|e| zip_error(path, e))?,
+        ));
+    }
+    if let Some(logo) = logo {
+        entries.push((LOGO_ENTRY, logo));
+    }
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| zip_error(path, "not a file path"))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    let result = write_archive(&temp_path, entries)
+        .and_then(|()| std::fs::rename(&temp_path, path).map_err(|e| zip_error(path, e)));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn write_archive(path: &Path, entries: Vec<(&str, Vec<u8>)>) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| zip_error(path, e))?;
+    let mut writer = ZipWriter::new(file);
+    for (name, bytes) in entries {
+        // PNG is already compressed; deflating it again only costs time.
+        let method = if name.ends_with(".png") {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+        writer
+            .start_file(
+                name,
+                SimpleFileOptions::default().compression_method(method),
+            )
+            .map_err(|e| zip_error(path, e))?;
+        writer.write_all(&bytes).map_err(|e| zip_error(path, e))?;
+    }
+    let file = writer.finish().map_err(|e| zip_error(path, e))?;
+    file.sync_all().map_err(|e| zip_error(path, e))
+}
+
+fn read_entry(
+    archive: &mut ZipArchive<File>,
+    path: &Path,
+    name: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(error) => {
+            return Err(format!(
+                "Failed to read {} from {}: {}",
+                name,
+                path.display(),
+                error
+            ))
+        }
+    };
+    let mut bytes = Vec::new();
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read {} from {}: {}", name, path.display(), e))?;
+    Ok(Some(bytes))
+}
+
+fn required_entry(
+    archive: &mut ZipArchive<File>,
+    path: &Path,
+    name: &str,
+) -> Result<Vec<u8>, String> {
+    read_entry(archive, path, name)?.ok_or_else(|| {
+        format!(
+            "{} is not an xshot project: missing {}",
+            path.display(),
+            name
+        )
+    })
+}
+
+pub fn load(path: &Path) -> Result<Project, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("{} is not an xshot project: {}", path.display(), e))?;
+
+    let manifest = serde_json::from_slice::<ProjectManifest>(&required_entry(
+        &mut archive,
+        path,
+        MANIFEST_ENTRY,
+    )?)
+    .map_err(|e| format!("Invalid project manifest in {}: {}", path.display(), e))?;
+    if manifest.version == 0 || manifest.version > PROJECT_VERSION {
+        return Err(format!(
+            "Unsupported project version {} in {}",
+            manifest.version,
+            path.display()
+        ));
+    }
+    let scene = serde_json::from_slice::<AnnotationScene>(&required_entry(
+        &mut archive,
+        path,
+        SCENE_ENTRY,
+    )?)
+    .map_err(|e| format!("Invalid annotation scene in {}: {}", path.display(), e))?;
+    scene.validate()?;
+    let original = image::load_from_memory(&required_entry(&mut archive, path, ORIGINAL_ENTRY)?)
+        .map_err(|e| format!("Invalid original image in {}: {}", path.display(), e))?
+        .to_rgba8();
+    let ocr = read_entry(&mut archive, path, OCR_ENTRY)?
+        .map(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes))
+        .transpose()
+        .map_err(|e| format!("Invalid OCR result in {}: {}", path.display(), e))?;
+    let mut watermark = manifest.watermark;
+    if logo_path(&watermark) == Some(LOGO_ENTRY) {
+        let logo = required_entry(&mut archive, path, LOGO_ENTRY)?;
+        set_logo_path(
+            &mut watermark,
+            extract_logo(&logo)?.to_string_lossy().into_owned(),
+        );
+    }
+
+    Ok(Project {
+        document: ProjectDocument {
+            capture_id: manifest.capture_id,
+            crop: manifest
+                .crop
+                .and_then(|crop| crop.clamp_to(original.width(), original.height())),
+            scene,
+            watermark,
+            ocr,
+        },
+        original,
+    })
+}
+
+/// Writes a bundled logo where the visible watermark can open it, named after its contents so
+/// reopening the same project reuses the file.
+fn extract_logo(bytes: &[u8]) -> Result<PathBuf, String> {
+    let dir = std::env::temp_dir().join("xshot-project");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to prepare logo directory: {}", e))?;
+    let path = dir.join(format!("logo-{:08x}.png", watermark::fnv1a(bytes)));
+    std::fs::write(&path, bytes).map_err(|e| format!("Failed to extract project logo: {}", e))?;
+    Ok(path)
+}
+
+impl Project {
+    /// The scheme the hidden watermark is signed with, when the project has one to sign.
+    pub fn signature(&self) -> Option<SignatureScheme> {
+        let watermarks = &self.document.watermark;
+        watermarks.hidden.as_ref().and(watermarks.signature)
+    }
+
+    /// Renders the project the way the editor exports it: cropped, annotated, then watermarked.
+    /// `keys` sign the hidden watermark when `signature()` asks for it.
+    pub fn export(&self, keys: Option<&SigningKeys>) -> Result<RgbaImage, String> {
+        let crop = self.document.crop.unwrap_or(CropRect {
+            x: 0,
+            y: 0,
+            width: self.original.width(),
+            height: self.original.height(),
+        });
+        let cropped =
+            image::imageops::crop_imm(&self.original, crop.x, crop.y, crop.width, crop.height)
+                .to_image();
+        let mut image = annotation_scene::render(&cropped, &self.document.scene)?;
+
+        let now = crate::unix_epoch_ms();
+        let watermarks = &self.document.watermark;
+        if let Some(options) = &watermarks.visible {
+            visible_watermark::apply(
+                &mut image,
+                options,
+                self.document.capture_id.as_deref(),
+                now,
+            )?;
+        }
+        if let Some(text) = &watermarks.hidden {
+            let signer = match self.signature() {
+                Some(scheme) => Some((
+                    keys.ok_or("The project's hidden watermark needs signing keys")?,
+                    scheme,
+                )),
+                None => None,
+            };
+            watermark::embed(&mut image, text, now, signer)?;
+        }
+        Ok(image)
+    }
+}
+
+/// Maps a crop in the editor frame back into `original` pixels, the inverse of `frame_crop`. Parts
+/// of the crop outside `placed` are dropped.
+fn original_crop(frame_crop: CropRect, original: &RgbaImage, placed: CropRect) -> Option<CropRect> {
+    let left = frame_crop.x.max(placed.x) - placed.x;
+    let top = frame_crop.y.max(placed.y) - placed.y;
+    let right = (frame_crop.x + frame_crop.width)
+        .min(placed.x + placed.width)
+        .saturating_sub(placed.x);
+    let bottom = (frame_crop.y + frame_crop.height)
+        .min(placed.y + placed.height)
+        .saturating_sub(placed.y);
+    if right <= left || bottom <= top {
+        return None;
+    }
+    let scale_x = original.width() as f64 / placed.width.max(1) as f64;
+    let scale_y = original.height() as f64 / placed.height.max(1) as f64;
+    CropRect {
+        x: (left as f64 * scale_x).round() as u32,
+        y: (top as f64 * scale_y).round() as u32,
+        width: (((right - left) as f64 * scale_x).round() as u32).max(1),
+        height: (((bottom - top) as f64 * scale_y).round() as u32).max(1),
+    }
+    .clamp_to(original.width(), original.height())
+}
+
+/// Saves a project. The original is `blob_data`, or for an imported capture the image it was
+/// imported from, in which case `project.crop` is given in editor frame pixels.
+#[tauri::command]
+pub async fn save_project(
+    app: AppHandle,
+    path: String,
+    blob_data: Option<Vec<u8>>,
+    imported_capture_id: Option<String>,
+    mut project: ProjectDocument,
+) -> Result<String, String> {
+    let imported = match imported_capture_id {
+        Some(capture_id) => Some(
+            app.state::<ImportedOriginalStore>()
+                .get(&capture_id)?
+                .ok_or_else(|| format!("Imported capture {} is no longer open", capture_id))?,
+        ),
+        None => None,
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let original = match (imported, blob_data) {
+            (Some((original, placed)), _) => {
+                project.crop = project
+                    .crop
+                    .map(|crop| {
+                        original_crop(crop, &original, placed)
+                            .ok_or_else(|| "The selection is outside the image".to_string())
+                    })
+                    .transpose()?;
+                original
+            }
+            (None, Some(blob_data)) => image::load_from_memory(&blob_data)
+                .map_err(|e| format!("Failed to decode image: {}", e))?
+                .to_rgba8(),
+            (None, None) => return Err("Missing project image".to_string()),
+        };
+        let mut path = PathBuf::from(path);
+        if path.extension().is_none() {
+            path.set_extension(PROJECT_EXTENSION);
+        }
+        save(&path, &original, &project)?;
+        println!(
+            "[xshot][project][rust] saved path={} original={}x{} annotations={}",
+            path.display(),
+            original.width(),
+            original.height(),
+            project.scene.annotations.len()
+        );
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Project save task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn load_project(path: String) -> Result<ProjectDocument, String> {
+    tauri::async_runtime::spawn_blocking(move || load(Path::new(&path)).map(|p| p.document))
+        .await
+        .map_err(|e| format!("Project load task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn export_project(app: AppHandle, path: String) -> Result<tauri::ipc::Response, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let project = load(Path::new(&path))?;
+        let keys = project
+            .signature()
+            .map(|_| watermark_signing::signing_keys(&app))
+            .transpose()?;
+        let image = project.export(keys.as_ref())?;
+        crate::encode_rgba_png_fast(&image).map(tauri::ipc::Response::new)
+    })
+    .await
+    .map_err(|e| format!("Project export task failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn take_prepared_project(
+    app: AppHandle,
+    capture_id: String,
+) -> Result<Option<PreparedProject>, String> {
+    Ok(app
+        .state::<PreparedProjectStore>()
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock prepared project store".to_string())?
+        .remove(&capture_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotation_scene::Annotation;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("xshot-project-{}-{}", std::process::id(), name))
+    }
+
+    fn original() -> RgbaImage {
+        RgbaImage::from_fn(40, 30, |x, y| {
+            image::Rgba([x as u8 * 6, y as u8 * 8, 90, 255])
+        })
+    }
+
+    fn document() -> ProjectDocument {
+        ProjectDocument {
+            capture_id: Some("capture-1".into()),
+            crop: Some(CropRect {
+                x: 30,
+                y: 20,
+                width: 50,
+                height: 50,
+            }),
+            scene: AnnotationScene {
+                version: annotation_scene::SCENE_VERSION,
+                width: 10.0,
+                height: 10.0,
+                annotations: vec![Annotation::Mosaic {
+                    x: 1.0,
+                    y: 2.0,
+                    width: 3.0,
+                    height: 4.0,
+                    block_size: None,
+                }],
+            },
+            watermark: ProjectWatermark {
+                visible: None,
+                hidden: Some("hidden text".into()),
+                signature: Some(SignatureScheme::Ed25519),
+            },
+            ocr: Some(serde_json::json!({ "lines": [{ "text": "Hello" }] })),
+        }
+    }
+
+    #[test]
+    fn saved_projects_load_back() {
+        let path = temp_path("roundtrip.xshot");
+        let document = document();
+        save(&path, &original(), &document).unwrap();
+        let project = load(&path);
+        let _ = std::fs::remove_file(&path);
+        let project = project.unwrap();
+
+        assert_eq!(project.original, original());
+        assert_eq!(project.document.capture_id, document.capture_id);
+        // The stored crop runs past the 40x30 original and is clamped on load.
+        assert_eq!(
+            project.document.crop,
+            Some(CropRect {
+                x: 30,
+                y: 20,
+                width: 10,
+                height: 10,
+            })
+        );
+        assert_eq!(project.document.scene, document.scene);
+        assert_eq!(project.document.ocr, document.ocr);
+        assert_eq!(project.document.watermark.hidden, document.watermark.hidden);
+        assert_eq!(project.signature(), Some(SignatureScheme::Ed25519));
+    }
+
+    #[test]
+    fn save_replaces_the_file_in_one_step() {
+        let path = temp_path("replace.xshot");
+        std::fs::write(&path, b"previous").unwrap();
+        let mut invalid = document();
+        invalid.scene.version = 0;
+        assert!(save(&path, &original(), &invalid).is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"previous");
+
+        save(&path, &original(), &document()).unwrap();
+        let temp = path.with_file_name(format!(
+            ".{}.tmp",
+            path.file_name().unwrap().to_string_lossy()
+        ));
+        assert!(!temp.exists());
+        let project = load(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(project.is_ok());
+    }
+
+    #[test]
+    fn bundles_the_visible_watermark_logo() {
+        let logo_file = temp_path("logo.png");
+        let logo = RgbaImage::from_pixel(6, 4, image::Rgba([200, 10, 10, 255]));
+        logo.save(&logo_file).unwrap();
+        let mut document = document();
+        document.watermark.visible = Some(VisibleWatermarkOptions {
+            logo: Some(visible_watermark::LogoOptions {
+                path: logo_file.to_string_lossy().into_owned(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let path = temp_path("logo.xshot");
+        save(&path, &original(), &document).unwrap();
+        std::fs::remove_file(&logo_file).unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let manifest = serde_json::from_slice::<ProjectManifest>(
+            &required_entry(&mut archive, &path, MANIFEST_ENTRY).unwrap(),
+        )
+        .unwrap();
+        let project = load(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(logo_path(&manifest.watermark), Some(LOGO_ENTRY));
+        let project = project.unwrap();
+        let extracted = logo_path(&project.document.watermark).unwrap();
+        assert_eq!(image::open(extracted).unwrap().to_rgba8(), logo);
+    }
+
+    #[test]
+    fn original_crop_inverts_frame_crop() {
+        let original = RgbaImage::new(400, 300);
+        let crop = CropRect {
+            x: 100,
+            y: 60,
+            width: 200,
+            height: 100,
+        };
+        let document = ProjectDocument {
+            crop: Some(crop),
+            ..document()
+        };
+        for placed in [
+            CropRect {
+                x: 50,
+                y: 20,
+                width: 200,
+                height: 150,
+            },
+            CropRect {
+                x: 0,
+                y: 0,
+                width: 400,
+                height: 300,
+            },
+            CropRect {
+                x: 16,
+                y: 9,
+                width: 800,
+                height: 600,
+            },
+        ] {
+            let frame = frame_crop(&document, &original, placed);
+            assert_eq!(
+                original_crop(frame, &original, placed),
+                Some(crop),
+                "{:?}",
+                placed
+            );
+        }
+
+        let placed = CropRect {
+            x: 50,
+            y: 20,
+            width: 200,
+            height: 150,
+        };
+        // Parts in the padding around the placed image are dropped; a crop only in it is rejected.
+        let padded = CropRect {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 300,
+        };
+        assert_eq!(
+            original_crop(padded, &original, placed),
+            Some(CropRect {
+                x: 0,
+                y: 0,
+                width: 100,
+                height: 300,
+            })
+        );
+        let outside = CropRect {
+            x: 0,
+            y: 0,
+            width: 40,
+            height: 300,
+        };
+        assert_eq!(original_crop(outside, &original, placed), None);
+    }
+}
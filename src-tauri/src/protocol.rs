@@ -0,0 +1,341 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, UriSchemeContext, UriSchemeResponder, Wry};
+
+pub const SCHEME: &str = "xshot";
+
+const MAX_THUMBNAIL_EDGE: u32 = 4096;
+
+/// URL for a resource served by this protocol. Windows and Android webviews only route custom
+/// schemes through `http://<scheme>.localhost`.
+pub fn url(path: &str) -> String {
+    if cfg!(any(target_os = "windows", target_os = "android")) {
+        format!("http://{}.localhost/{}", SCHEME, path)
+    } else {
+        format!("{}://localhost/{}", SCHEME, path)
+    }
+}
+
+pub fn pin_url(label: &str) -> String {
+    url(&format!("pin/{}", label))
+}
+
+/// Image bytes as stored by the capture and pin stores. The ETag is fixed when the image is
+/// stored, and framed (RGBA/QOI) captures are encoded to PNG at most once.
+#[derive(Debug)]
+pub struct ServedImage {
+    bytes: Vec<u8>,
+    etag: String,
+    png: OnceLock<Result<Vec<u8>, String>>,
+}
+
+impl ServedImage {
+    pub fn new(bytes: Vec<u8>) -> Arc<Self> {
+        static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+        let etag = format!(
+            "\"{:x}-{:x}-{:x}\"",
+            crate::unix_epoch_ms() as u64,
+            SEQUENCE.fetch_add(1, Ordering::Relaxed),
+            bytes.len()
+        );
+        Arc::new(Self {
+            bytes,
+            etag,
+            png: OnceLock::new(),
+        })
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self: Arc<Self>) -> Vec<u8> {
+        Arc::try_unwrap(self)
+            .map(|image| image.bytes)
+            .unwrap_or_else(|image| image.bytes.clone())
+    }
+
+    fn png(&self) -> Result<&[u8], ProtocolError> {
+        if crate::frame::detect_format(&self.bytes) == crate::frame::FrameFormat::Png {
+            return Ok(&self.bytes);
+        }
+        // Framed RGBA/QOI captures are not something an `<img>` can decode.
+        self.png
+            .get_or_init(|| {
+                crate::frame::decode_frame(&self.bytes)
+                    .and_then(|image| crate::encode_rgba_png_fast(&image))
+            })
+            .as_deref()
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+    }
+}
+
+/// Serves `pin/{label}`, a pinned image from `PinWindowStore`. `?thumb=N` downscales it so the
+/// longest edge is at most `N` pixels.
+pub fn handle(
+    ctx: UriSchemeContext<'_, Wry>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    // Decoding and thumbnailing can take a while, so keep them off the webview's thread.
+    tauri::async_runtime::spawn_blocking(move || {
+        let response = respond(&app, &request).unwrap_or_else(|(status, message)| {
+            eprintln!(
+                "[xshot][protocol][rust] uri={} status={} error={}",
+                request.uri(),
+                status.as_u16(),
+                message
+            );
+            build(status, "text/plain", message.into_bytes())
+        });
+        responder.respond(response);
+    });
+}
+
+type ProtocolError = (StatusCode, String);
+
+fn respond(
+    app: &AppHandle,
+    request: &Request<Vec<u8>>,
+) -> Result<Response<Vec<u8>>, ProtocolError> {
+    let segments: Vec<String> = request
+        .uri()
+        .path()
+        .trim_matches('/')
+        .split('/')
+        .map(percent_decode)
+        .collect();
+    let thumb = query_param(request.uri().query(), "thumb")
+        .map(|value| {
+            value
+                .parse::<u32>()
+                .ok()
+                .filter(|edge| *edge > 0)
+                .map(|edge| edge.min(MAX_THUMBNAIL_EDGE))
+                .ok_or((
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid thumb size {}", value),
+                ))
+        })
+        .transpose()?;
+
+    let source = match segments.as_slice() {
+        [kind, label] if kind == "pin" => pin_image(app, label)?,
+        _ => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Unknown resource {}", request.uri().path()),
+            ))
+        }
+    };
+
+    let etag = match thumb {
+        Some(edge) => format!("{}-{}\"", source.etag.trim_end_matches('"'), edge),
+        None => source.etag.clone(),
+    };
+    let matches_etag = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .split(',')
+                .any(|tag| tag.trim() == etag || tag.trim() == "*")
+        });
+    if matches_etag {
+        let mut response = build(StatusCode::NOT_MODIFIED, "image/png", Vec::new());
+        insert_header(&mut response, header::ETAG, &etag);
+        return Ok(response);
+    }
+
+    let thumbnail = thumb
+        .map(|edge| thumbnail(source.bytes(), edge))
+        .transpose()?;
+    let body = match thumbnail.as_deref() {
+        Some(thumbnail) => thumbnail,
+        None => source.png()?,
+    };
+
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok());
+    let mut response = match range {
+        Some(range) => match parse_range(range, body.len()) {
+            Some((start, end)) => {
+                let mut response = build(
+                    StatusCode::PARTIAL_CONTENT,
+                    "image/png",
+                    body[start..=end].to_vec(),
+                );
+                insert_header(
+                    &mut response,
+                    header::CONTENT_RANGE,
+                    &format!("bytes {}-{}/{}", start, end, body.len()),
+                );
+                response
+            }
+            None => {
+                let mut response =
+                    build(StatusCode::RANGE_NOT_SATISFIABLE, "text/plain", Vec::new());
+                insert_header(
+                    &mut response,
+                    header::CONTENT_RANGE,
+                    &format!("bytes */{}", body.len()),
+                );
+                return Ok(response);
+            }
+        },
+        None => build(StatusCode::OK, "image/png", body.to_vec()),
+    };
+    insert_header(&mut response, header::ETAG, &etag);
+    insert_header(&mut response, header::CACHE_CONTROL, "no-cache");
+    Ok(response)
+}
+
+fn pin_image(app: &AppHandle, label: &str) -> Result<Arc<ServedImage>, ProtocolError> {
+    app.state::<crate::PinWindowStore>()
+        .0
+        .lock()
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to lock pin window store".to_string(),
+            )
+        })?
+        .get(label)
+        .map(|payload| payload.image.clone())
+        .ok_or((StatusCode::NOT_FOUND, format!("No pinned image {}", label)))
+}
+
+fn thumbnail(bytes: &[u8], edge: u32) -> Result<Vec<u8>, ProtocolError> {
+    let image =
+        crate::frame::decode_frame(bytes).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    let image = if image.width().max(image.height()) > edge {
+        image::DynamicImage::ImageRgba8(image)
+            .thumbnail(edge, edge)
+            .to_rgba8()
+    } else {
+        image
+    };
+    crate::encode_rgba_png_fast(&image).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+/// Parses a single `bytes=` range into inclusive offsets. Multi-range requests are not
+/// supported and yield `None`, as do ranges outside the body.
+fn parse_range(value: &str, len: usize) -> Option<(usize, usize)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') || len == 0 {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix = suffix.parse::<usize>().ok()?.min(len);
+            (len.checked_sub(suffix)?, len - 1)
+        }
+        (start, "") => (start.parse().ok()?, len - 1),
+        (start, end) => (start.parse().ok()?, end.parse::<usize>().ok()?.min(len - 1)),
+    };
+    (start <= end && start < len).then_some((start, end))
+}
+
+fn query_param<'a>(query: Option<&'a str>, name: &str) -> Option<&'a str> {
+    query?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn build(status: StatusCode, content_type: &str, body: Vec<u8>) -> Response<Vec<u8>> {
+    let length = body.len();
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    insert_header(&mut response, header::CONTENT_TYPE, content_type);
+    insert_header(&mut response, header::CONTENT_LENGTH, &length.to_string());
+    insert_header(&mut response, header::ACCEPT_RANGES, "bytes");
+    // Pins and the editor load from the app origin, and canvases must stay untainted.
+    insert_header(&mut response, header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+    response
+}
+
+fn insert_header(response: &mut Response<Vec<u8>>, name: header::HeaderName, value: &str) {
+    if let Ok(value) = header::HeaderValue::from_str(value) {
+        response.headers_mut().insert(name, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_byte_ranges() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range(" bytes=10-10 ", 1000), Some((10, 10)));
+        // Ends past the body are clamped, open ends run to the last byte.
+        assert_eq!(parse_range("bytes=900-2000", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=990-", 1000), Some((990, 999)));
+        // Suffix ranges count from the end and cover the whole body at most.
+        assert_eq!(parse_range("bytes=-10", 1000), Some((990, 999)));
+        assert_eq!(parse_range("bytes=-5000", 1000), Some((0, 999)));
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_and_malformed_ranges() {
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=1000-1200", 1000), None);
+        assert_eq!(parse_range("bytes=50-10", 1000), None);
+        assert_eq!(parse_range("bytes=0-9", 0), None);
+        assert_eq!(parse_range("bytes=-0", 1000), None);
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("bytes=a-b", 1000), None);
+        assert_eq!(parse_range("bytes=10", 1000), None);
+        assert_eq!(parse_range("items=0-9", 1000), None);
+    }
+
+    #[test]
+    fn finds_query_params_by_exact_name() {
+        assert_eq!(query_param(Some("thumb=256"), "thumb"), Some("256"));
+        assert_eq!(query_param(Some("a=1&thumb=64&b"), "thumb"), Some("64"));
+        assert_eq!(query_param(Some("thumbnail=64"), "thumb"), None);
+        assert_eq!(query_param(Some("thumb"), "thumb"), None);
+        assert_eq!(query_param(None, "thumb"), None);
+    }
+
+    #[test]
+    fn percent_decodes_path_segments() {
+        assert_eq!(percent_decode("screenshot_window_1"), "screenshot_window_1");
+        assert_eq!(percent_decode("pin%201%2Fa"), "pin 1/a");
+        assert_eq!(percent_decode("caf%C3%A9"), "caf\u{e9}");
+        // Incomplete or invalid escapes are kept as written.
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+    }
+}
@@ -0,0 +1,1189 @@
+use crate::{capture_monitor_image, capture_monitors, frame, CaptureMonitor};
+use image::ImageEncoder;
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::menu::MenuItem;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+pub const TRAY_ID: &str = "xshot";
+const DEFAULT_FPS: u32 = 10;
+const MAX_FPS: u32 = 30;
+const MAX_CONSECUTIVE_FAILURES: u32 = 10;
+// Frames buffered between the capture and encoder threads before capture waits.
+const FRAME_QUEUE_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingFormat {
+    Gif,
+    Apng,
+    Webp,
+    Mp4,
+    Webm,
+}
+
+impl RecordingFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            RecordingFormat::Gif => "gif",
+            RecordingFormat::Apng => "png",
+            RecordingFormat::Webp => "webp",
+            RecordingFormat::Mp4 => "mp4",
+            RecordingFormat::Webm => "webm",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStatus {
+    recording: bool,
+    paused: bool,
+    format: Option<RecordingFormat>,
+    frames: u64,
+    duration_ms: u64,
+    path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingResult {
+    path: String,
+    format: RecordingFormat,
+    width: u32,
+    height: u32,
+    frames: u64,
+    duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordingFinishedPayload {
+    result: Option<RecordingResult>,
+    error: Option<String>,
+}
+
+#[derive(Default)]
+struct RecordingControl {
+    paused: AtomicBool,
+    stopped: AtomicBool,
+    frames: AtomicU64,
+    duration_ms: AtomicU64,
+}
+
+struct ActiveRecording {
+    control: Arc<RecordingControl>,
+    format: RecordingFormat,
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    capture: JoinHandle<Result<(), String>>,
+    encoder: JoinHandle<Result<(), String>>,
+}
+
+enum RecordingSlot {
+    /// Claimed by a `start_recording` call that is still preparing the recording.
+    Starting,
+    Active(ActiveRecording),
+}
+
+#[derive(Default)]
+pub struct RecordingState(Mutex<Option<RecordingSlot>>);
+
+/// Holds the `Starting` slot and releases it again unless the recording goes live.
+struct SlotReservation {
+    app: AppHandle,
+    armed: bool,
+}
+
+impl SlotReservation {
+    fn claim(app: &AppHandle) -> Result<Self, String> {
+        let state = app.state::<RecordingState>();
+        let mut slot = state
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock recording state".to_string())?;
+        if slot.is_some() {
+            return Err("A recording is already in progress".into());
+        }
+        *slot = Some(RecordingSlot::Starting);
+        Ok(Self {
+            app: app.clone(),
+            armed: true,
+        })
+    }
+
+    fn activate(mut self, active: ActiveRecording) -> Result<(), String> {
+        let state = self.app.state::<RecordingState>();
+        let mut slot = state
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock recording state".to_string())?;
+        *slot = Some(RecordingSlot::Active(active));
+        self.armed = false;
+        Ok(())
+    }
+}
+
+impl Drop for SlotReservation {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        if let Ok(mut slot) = self.app.state::<RecordingState>().0.lock() {
+            if matches!(*slot, Some(RecordingSlot::Starting)) {
+                *slot = None;
+            }
+        }
+    }
+}
+
+/// Tray menu entries that only make sense while a recording is running.
+pub struct RecordingTrayItems {
+    pub pause: MenuItem<Wry>,
+    pub stop: MenuItem<Wry>,
+}
+
+/// The part of a monitor capture being recorded, in capture pixels.
+#[derive(Debug, Clone, Copy)]
+struct CropRect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl CropRect {
+    /// Converts a monitor-local logical selection into pixels of a `capture_width` wide capture.
+    fn from_selection(
+        monitor: &CaptureMonitor,
+        capture_width: u32,
+        capture_height: u32,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Result<Self, String> {
+        let pixels_per_logical = capture_width as f64 / monitor.width.max(1.0);
+        let left = (x * pixels_per_logical).round().max(0.0) as u32;
+        let top = (y * pixels_per_logical).round().max(0.0) as u32;
+        let right = ((x + width) * pixels_per_logical)
+            .round()
+            .clamp(0.0, capture_width as f64) as u32;
+        let bottom = ((y + height) * pixels_per_logical)
+            .round()
+            .clamp(0.0, capture_height as f64) as u32;
+        if right <= left || bottom <= top {
+            return Err("Recording area is outside the monitor".into());
+        }
+
+        Ok(Self {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+}
+
+fn capture_frame(monitor: &CaptureMonitor, crop: CropRect) -> Result<image::RgbaImage, String> {
+    let bytes = capture_monitor_image(
+        monitor,
+        frame::FrameFormat::Rgba,
+        "recording",
+        "recording",
+        false,
+    )?;
+    let capture = frame::decode_frame(&bytes)?;
+    if crop.x + crop.width > capture.width() || crop.y + crop.height > capture.height() {
+        return Err("Monitor resolution changed while recording".into());
+    }
+    Ok(image::imageops::crop_imm(&capture, crop.x, crop.y, crop.width, crop.height).to_image())
+}
+
+trait FrameSink: Send {
+    fn push(&mut self, frame: &image::RgbaImage, delay_ms: u32) -> Result<(), String>;
+    fn finish(self: Box<Self>) -> Result<(), String>;
+}
+
+struct GifSink {
+    encoder: image::codecs::gif::GifEncoder<BufWriter<std::fs::File>>,
+}
+
+impl GifSink {
+    fn create(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Failed to create recording file: {}", e))?;
+        // Speed 10 trades a little palette quality for keeping up with the capture rate.
+        let mut encoder = image::codecs::gif::GifEncoder::new_with_speed(BufWriter::new(file), 10);
+        encoder
+            .set_repeat(image::codecs::gif::Repeat::Infinite)
+            .map_err(|e| format!("Failed to start GIF: {}", e))?;
+        Ok(Self { encoder })
+    }
+}
+
+impl FrameSink for GifSink {
+    fn push(&mut self, frame: &image::RgbaImage, delay_ms: u32) -> Result<(), String> {
+        let delay = image::Delay::from_numer_denom_ms(delay_ms, 1);
+        self.encoder
+            .encode_frame(image::Frame::from_parts(frame.clone(), 0, 0, delay))
+            .map_err(|e| format!("Failed to encode GIF frame: {}", e))
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), String> {
+        // The GIF trailer is written and the file flushed when the encoder drops.
+        drop(self.encoder);
+        Ok(())
+    }
+}
+
+/// APNG and animated WebP both need the frame count or total size up front, so encoded frames
+/// spill to a temporary file while recording and only their lengths stay in memory.
+struct FrameSpool {
+    path: PathBuf,
+    writer: BufWriter<std::fs::File>,
+}
+
+impl FrameSpool {
+    fn create(output: &Path) -> Result<Self, String> {
+        let dir = std::env::temp_dir().join("xshot-recording");
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to prepare recording spool: {}", e))?;
+        let name = output
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "recording".to_string());
+        let path = dir.join(format!("{}.part", name));
+        let file = std::fs::File::create(&path)
+            .map_err(|e| format!("Failed to create recording spool: {}", e))?;
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    fn append(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.writer
+            .write_all(bytes)
+            .map_err(|e| format!("Failed to spool recording frame: {}", e))
+    }
+
+    /// Replays the spooled bytes into `write`, then deletes the spool either way.
+    fn drain<F>(self, write: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut BufReader<std::fs::File>) -> Result<(), String>,
+    {
+        let result = self
+            .writer
+            .into_inner()
+            .map_err(|e| format!("Failed to flush recording spool: {}", e.error()))
+            .and_then(|_| {
+                std::fs::File::open(&self.path)
+                    .map_err(|e| format!("Failed to read recording spool: {}", e))
+            })
+            .and_then(|file| write(&mut BufReader::new(file)));
+        let _ = std::fs::remove_file(&self.path);
+        result
+    }
+}
+
+fn read_spooled(reader: &mut impl Read, len: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0; len];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|e| format!("Recording spool is truncated: {}", e))?;
+    Ok(bytes)
+}
+
+fn create_output(path: &Path) -> Result<BufWriter<std::fs::File>, String> {
+    std::fs::File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| format!("Failed to create recording file: {}", e))
+}
+
+/// Frames are encoded as PNG and their image data re-chunked into one animation at the end.
+struct ApngSink {
+    path: PathBuf,
+    spool: FrameSpool,
+    ihdr: Option<Vec<u8>>,
+    /// IDAT lengths and delay of each frame.
+    frames: Vec<(Vec<usize>, u32)>,
+}
+
+impl FrameSink for ApngSink {
+    fn push(&mut self, frame: &image::RgbaImage, delay_ms: u32) -> Result<(), String> {
+        let png = crate::encode_rgba_png_fast(frame)?;
+        let mut lengths = Vec::new();
+        for (kind, data) in png_chunks(&png) {
+            match kind {
+                b"IHDR" if self.ihdr.is_none() => self.ihdr = Some(data.to_vec()),
+                b"IDAT" => {
+                    self.spool.append(data)?;
+                    lengths.push(data.len());
+                }
+                _ => {}
+            }
+        }
+        self.frames.push((lengths, delay_ms));
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), String> {
+        let ApngSink {
+            path,
+            spool,
+            ihdr,
+            frames,
+        } = *self;
+        spool.drain(|spooled| {
+            let ihdr = ihdr.ok_or("No frames were recorded")?;
+            let mut out = create_output(&path)?;
+            write_apng(&mut out, &ihdr, &frames, spooled)
+                .and_then(|_| out.flush())
+                .map_err(|e| format!("Failed to write APNG: {}", e))
+        })
+    }
+}
+
+/// Each frame is encoded losslessly and its VP8L chunk wrapped into an ANMF frame at the end.
+struct WebpSink {
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    spool: FrameSpool,
+    /// VP8L chunk length and delay of each frame.
+    frames: Vec<(usize, u32)>,
+}
+
+impl FrameSink for WebpSink {
+    fn push(&mut self, frame: &image::RgbaImage, delay_ms: u32) -> Result<(), String> {
+        let mut bytes = Vec::new();
+        image::codecs::webp::WebPEncoder::new_lossless(&mut bytes)
+            .write_image(
+                frame.as_raw(),
+                frame.width(),
+                frame.height(),
+                image::ExtendedColorType::Rgba8,
+            )
+            .map_err(|e| format!("Failed to encode WebP frame: {}", e))?;
+        let chunk = riff_chunk(&bytes[12..], b"VP8L")
+            .ok_or("WebP encoder did not produce a lossless bitstream")?;
+        self.spool.append(chunk)?;
+        self.frames.push((chunk.len(), delay_ms));
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), String> {
+        let WebpSink {
+            path,
+            width,
+            height,
+            spool,
+            frames,
+        } = *self;
+        spool.drain(|spooled| {
+            if frames.is_empty() {
+                return Err("No frames were recorded".into());
+            }
+            let mut out = create_output(&path)?;
+            write_webp(&mut out, width, height, &frames, spooled)
+                .and_then(|_| out.flush().map_err(|e| e.to_string()))
+                .map_err(|e| format!("Failed to write WebP: {}", e))
+        })
+    }
+}
+
+/// Streams raw RGBA into a system ffmpeg at a constant rate, repeating frames to cover the time
+/// a slow capture took.
+struct FfmpegSink {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    fps: u32,
+    elapsed_ms: u64,
+    written: u64,
+}
+
+impl FfmpegSink {
+    fn spawn(
+        ffmpeg: &Path,
+        format: RecordingFormat,
+        path: &Path,
+        width: u32,
+        height: u32,
+        fps: u32,
+    ) -> Result<Self, String> {
+        let mut command = Command::new(ffmpeg);
+        command
+            .args(["-y", "-loglevel", "error", "-nostats"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", width, height)])
+            .args(["-r", &fps.to_string(), "-i", "-"])
+            // yuv420p needs even dimensions.
+            .args([
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                "-pix_fmt",
+                "yuv420p",
+            ]);
+        match format {
+            RecordingFormat::Mp4 => command.args([
+                "-c:v",
+                "libx264",
+                "-preset",
+                "veryfast",
+                "-crf",
+                "23",
+                "-movflags",
+                "+faststart",
+            ]),
+            _ => command.args([
+                "-c:v",
+                "libvpx-vp9",
+                "-b:v",
+                "0",
+                "-crf",
+                "34",
+                "-deadline",
+                "realtime",
+                "-cpu-used",
+                "8",
+            ]),
+        };
+        command
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+            command.creation_flags(CREATE_NO_WINDOW);
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
+        let stdin = child.stdin.take();
+        Ok(Self {
+            child,
+            stdin,
+            fps,
+            elapsed_ms: 0,
+            written: 0,
+        })
+    }
+}
+
+impl FrameSink for FfmpegSink {
+    fn push(&mut self, frame: &image::RgbaImage, delay_ms: u32) -> Result<(), String> {
+        self.elapsed_ms += delay_ms as u64;
+        let target = ((self.elapsed_ms * self.fps as u64) as f64 / 1000.0).round() as u64;
+        let stdin = self.stdin.as_mut().ok_or("ffmpeg input is closed")?;
+        for _ in self.written..target.max(self.written + 1) {
+            stdin
+                .write_all(frame.as_raw())
+                .map_err(|e| format!("ffmpeg stopped accepting frames: {}", e))?;
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), String> {
+        drop(self.stdin.take());
+        let output = self
+            .child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "ffmpeg exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+}
+
+fn find_ffmpeg() -> Option<PathBuf> {
+    // Apps launched from Finder do not inherit the shell PATH, so check the usual installs too.
+    [
+        "ffmpeg",
+        "/opt/homebrew/bin/ffmpeg",
+        "/usr/local/bin/ffmpeg",
+        "/usr/bin/ffmpeg",
+    ]
+    .into_iter()
+    .map(PathBuf::from)
+    .find(|path| {
+        Command::new(path)
+            .arg("-version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+fn create_sink(
+    format: RecordingFormat,
+    path: &Path,
+    width: u32,
+    height: u32,
+    fps: u32,
+) -> Result<Box<dyn FrameSink>, String> {
+    Ok(match format {
+        RecordingFormat::Gif => Box::new(GifSink::create(path)?),
+        RecordingFormat::Apng => Box::new(ApngSink {
+            path: path.to_path_buf(),
+            spool: FrameSpool::create(path)?,
+            ihdr: None,
+            frames: Vec::new(),
+        }),
+        RecordingFormat::Webp => Box::new(WebpSink {
+            path: path.to_path_buf(),
+            width,
+            height,
+            spool: FrameSpool::create(path)?,
+            frames: Vec::new(),
+        }),
+        RecordingFormat::Mp4 | RecordingFormat::Webm => {
+            let ffmpeg = find_ffmpeg()
+                .ok_or("ffmpeg was not found; install it or record to GIF, APNG or WebP instead")?;
+            Box::new(FfmpegSink::spawn(
+                &ffmpeg, format, path, width, height, fps,
+            )?)
+        }
+    })
+}
+
+fn crc32_update(mut crc: u32, bytes: &[u8]) -> u32 {
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// Writes a PNG chunk whose data is the concatenation of `parts`.
+fn write_png_chunk(out: &mut impl Write, kind: &[u8; 4], parts: &[&[u8]]) -> std::io::Result<()> {
+    let length = parts.iter().map(|part| part.len()).sum::<usize>();
+    out.write_all(&(length as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    let mut crc = crc32_update(0xffff_ffff, kind);
+    for part in parts {
+        out.write_all(part)?;
+        crc = crc32_update(crc, part);
+    }
+    out.write_all(&(!crc).to_be_bytes())
+}
+
+fn png_chunks(png: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut offset = 8;
+    std::iter::from_fn(move || {
+        let length = u32::from_be_bytes(png.get(offset..offset + 4)?.try_into().ok()?) as usize;
+        let kind = png.get(offset + 4..offset + 8)?;
+        let data = png.get(offset + 8..offset + 8 + length)?;
+        offset += 12 + length;
+        Some((kind, data))
+    })
+}
+
+fn write_apng(
+    out: &mut impl Write,
+    ihdr: &[u8],
+    frames: &[(Vec<usize>, u32)],
+    spooled: &mut impl Read,
+) -> std::io::Result<()> {
+    let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+
+    out.write_all(b"\x89PNG\r\n\x1a\n")?;
+    write_png_chunk(out, b"IHDR", &[ihdr])?;
+    let frame_count = (frames.len() as u32).to_be_bytes();
+    write_png_chunk(out, b"acTL", &[&frame_count, &0u32.to_be_bytes()])?;
+
+    let mut sequence = 0u32;
+    for (index, (lengths, delay_ms)) in frames.iter().enumerate() {
+        let delay = ((*delay_ms).min(u16::MAX as u32) as u16).to_be_bytes();
+        // Width and height, then a zero x/y offset; APNG_DISPOSE_OP_NONE, APNG_BLEND_OP_SOURCE.
+        write_png_chunk(
+            out,
+            b"fcTL",
+            &[
+                &sequence.to_be_bytes(),
+                &ihdr[..8],
+                &[0; 8],
+                &delay,
+                &1000u16.to_be_bytes(),
+                &[0, 0],
+            ],
+        )?;
+        sequence += 1;
+
+        for length in lengths {
+            let data = read_spooled(spooled, *length).map_err(invalid)?;
+            if index == 0 {
+                write_png_chunk(out, b"IDAT", &[&data])?;
+            } else {
+                write_png_chunk(out, b"fdAT", &[&sequence.to_be_bytes(), &data])?;
+                sequence += 1;
+            }
+        }
+    }
+    write_png_chunk(out, b"IEND", &[])
+}
+
+/// Finds a chunk in a RIFF body and returns it whole (header, payload and padding).
+fn riff_chunk<'a>(mut body: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    while body.len() >= 8 {
+        let size = u32::from_le_bytes(body[4..8].try_into().ok()?) as usize;
+        let padded = (8 + size + (size & 1)).min(body.len());
+        if &body[..4] == kind {
+            return Some(&body[..padded]);
+        }
+        body = &body[padded..];
+    }
+    None
+}
+
+fn push_u24(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes()[..3]);
+}
+
+fn write_webp(
+    out: &mut impl Write,
+    width: u32,
+    height: u32,
+    frames: &[(usize, u32)],
+    spooled: &mut impl Read,
+) -> Result<(), String> {
+    // "WEBP", the VP8X and ANIM chunks, then one ANMF header per frame.
+    let body_len = 4 + 18 + 14 + frames.iter().map(|(len, _)| 24 + len).sum::<usize>();
+    let body_len = u32::try_from(body_len).map_err(|_| "Recording is too large for WebP")?;
+
+    let mut header = b"RIFF".to_vec();
+    header.extend_from_slice(&body_len.to_le_bytes());
+    header.extend_from_slice(b"WEBP");
+
+    header.extend_from_slice(b"VP8X");
+    header.extend_from_slice(&10u32.to_le_bytes());
+    // Alpha and animation flags.
+    header.extend_from_slice(&[0x12, 0, 0, 0]);
+    push_u24(&mut header, width - 1);
+    push_u24(&mut header, height - 1);
+
+    header.extend_from_slice(b"ANIM");
+    header.extend_from_slice(&6u32.to_le_bytes());
+    // Transparent background, loop forever.
+    header.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+    out.write_all(&header).map_err(|e| e.to_string())?;
+
+    for (len, delay_ms) in frames {
+        let chunk = read_spooled(spooled, *len)?;
+        let mut anmf = b"ANMF".to_vec();
+        anmf.extend_from_slice(&(16 + *len as u32).to_le_bytes());
+        push_u24(&mut anmf, 0);
+        push_u24(&mut anmf, 0);
+        push_u24(&mut anmf, width - 1);
+        push_u24(&mut anmf, height - 1);
+        push_u24(&mut anmf, (*delay_ms).min(0xff_ffff));
+        // Do not blend with the previous frame; keep it as is when moving on.
+        anmf.push(0x02);
+        out.write_all(&anmf).map_err(|e| e.to_string())?;
+        out.write_all(&chunk).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn run_encoder(
+    frames: Receiver<(image::RgbaImage, u64)>,
+    mut sink: Box<dyn FrameSink>,
+    frame_interval_ms: u32,
+) -> Result<(), String> {
+    // A frame's delay is only known once the next one arrives.
+    let mut pending: Option<(image::RgbaImage, u64)> = None;
+    for (frame, timestamp_ms) in frames {
+        if let Some((previous, previous_ms)) = pending.take() {
+            let delay = timestamp_ms.saturating_sub(previous_ms).max(1) as u32;
+            sink.push(&previous, delay)?;
+        }
+        pending = Some((frame, timestamp_ms));
+    }
+    if let Some((last, _)) = pending {
+        sink.push(&last, frame_interval_ms)?;
+    }
+    sink.finish()
+}
+
+fn run_capture(
+    app: AppHandle,
+    monitor: CaptureMonitor,
+    crop: CropRect,
+    fps: u32,
+    control: Arc<RecordingControl>,
+    frames: std::sync::mpsc::SyncSender<(image::RgbaImage, u64)>,
+) -> Result<(), String> {
+    let interval = Duration::from_secs_f64(1.0 / fps as f64);
+    let mut recorded = Duration::ZERO;
+    let mut last_tick: Option<Instant> = None;
+    let mut failures = 0;
+
+    while !control.stopped.load(Ordering::SeqCst) {
+        if control.paused.load(Ordering::SeqCst) {
+            last_tick = None;
+            std::thread::sleep(Duration::from_millis(50));
+            continue;
+        }
+
+        let tick = Instant::now();
+        if let Some(last_tick) = last_tick {
+            recorded += tick - last_tick;
+        }
+        last_tick = Some(tick);
+
+        match capture_frame(&monitor, crop) {
+            Ok(image) => {
+                failures = 0;
+                let timestamp_ms = recorded.as_millis() as u64;
+                if frames.send((image, timestamp_ms)).is_err() {
+                    break;
+                }
+                control.frames.fetch_add(1, Ordering::SeqCst);
+                control.duration_ms.store(timestamp_ms, Ordering::SeqCst);
+            }
+            Err(error) => {
+                failures += 1;
+                eprintln!(
+                    "[xshot][recording] frame_failed count={} error={}",
+                    failures, error
+                );
+                if failures >= MAX_CONSECUTIVE_FAILURES {
+                    // Finishing joins this thread, so hand it to another one.
+                    std::thread::spawn(move || {
+                        let _ = finish(&app);
+                    });
+                    return Err(error);
+                }
+            }
+        }
+
+        if let Some(remaining) = interval.checked_sub(tick.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+    Ok(())
+}
+
+fn update_tray(app: &AppHandle, recording: bool, paused: bool) {
+    if let Some(items) = app.try_state::<RecordingTrayItems>() {
+        let _ = items.stop.set_enabled(recording);
+        let _ = items.pause.set_enabled(recording);
+        let _ = items.pause.set_text(if paused {
+            "Resume Recording"
+        } else {
+            "Pause Recording"
+        });
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let (title, tooltip) = match (recording, paused) {
+            (false, _) => (None, "xshot"),
+            (true, false) => (Some("● REC"), "xshot - Recording"),
+            (true, true) => (Some("❚❚ REC"), "xshot - Recording paused"),
+        };
+        let _ = tray.set_title(title);
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+fn current_status(app: &AppHandle) -> RecordingStatus {
+    let state = app.state::<RecordingState>();
+    let slot = state.0.lock().ok();
+    match slot.as_ref().and_then(|slot| slot.as_ref()) {
+        Some(RecordingSlot::Active(active)) => RecordingStatus {
+            recording: true,
+            paused: active.control.paused.load(Ordering::SeqCst),
+            format: Some(active.format),
+            frames: active.control.frames.load(Ordering::SeqCst),
+            duration_ms: active.control.duration_ms.load(Ordering::SeqCst),
+            path: Some(active.path.to_string_lossy().to_string()),
+        },
+        _ => RecordingStatus {
+            recording: false,
+            paused: false,
+            format: None,
+            frames: 0,
+            duration_ms: 0,
+            path: None,
+        },
+    }
+}
+
+fn emit_status(app: &AppHandle) -> RecordingStatus {
+    let status = current_status(app);
+    update_tray(app, status.recording, status.paused);
+    let _ = app.emit("recording-status", status.clone());
+    status
+}
+
+fn finish(app: &AppHandle) -> Result<RecordingResult, String> {
+    let active = {
+        let state = app.state::<RecordingState>();
+        let mut slot = state
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock recording state".to_string())?;
+        match slot.take() {
+            Some(RecordingSlot::Active(active)) => active,
+            other => {
+                *slot = other;
+                return Err("No recording in progress".into());
+            }
+        }
+    };
+    active.control.stopped.store(true, Ordering::SeqCst);
+
+    let capture_result = active
+        .capture
+        .join()
+        .unwrap_or_else(|_| Err("Recording capture worker panicked".into()));
+    let encode_result = active
+        .encoder
+        .join()
+        .unwrap_or_else(|_| Err("Recording encoder worker panicked".into()));
+    let result = capture_result.and(encode_result).map(|_| RecordingResult {
+        path: active.path.to_string_lossy().to_string(),
+        format: active.format,
+        width: active.width,
+        height: active.height,
+        frames: active.control.frames.load(Ordering::SeqCst),
+        duration_ms: active.control.duration_ms.load(Ordering::SeqCst),
+    });
+
+    match &result {
+        Ok(result) => println!(
+            "[xshot][recording] stage=finished path={} frames={} duration_ms={}",
+            result.path, result.frames, result.duration_ms
+        ),
+        Err(error) => eprintln!("[xshot][recording] stage=failed error={}", error),
+    }
+    emit_status(app);
+    let _ = app.emit(
+        "recording-finished",
+        RecordingFinishedPayload {
+            result: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().cloned(),
+        },
+    );
+    result
+}
+
+/// Records a monitor-local logical rectangle of the monitor behind `window_label`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn start_recording(
+    app: AppHandle,
+    window_label: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    format: RecordingFormat,
+    fps: Option<u32>,
+    directory: Option<String>,
+) -> Result<RecordingStatus, String> {
+    if window_label == crate::virtual_desktop::virtual_desktop_label() {
+        return Err("Recording across displays is not supported".into());
+    }
+    let reservation = SlotReservation::claim(&app)?;
+
+    let monitor = capture_monitors()?
+        .into_iter()
+        .find(|monitor| monitor.label == window_label)
+        .ok_or_else(|| format!("No monitor for {}", window_label))?;
+    let fps = fps.unwrap_or(DEFAULT_FPS).clamp(1, MAX_FPS);
+
+    let probe = tauri::async_runtime::spawn_blocking({
+        let monitor = monitor.clone();
+        move || {
+            capture_monitor_image(
+                &monitor,
+                frame::FrameFormat::Rgba,
+                "recording",
+                "recording",
+                true,
+            )
+            .and_then(|bytes| frame::decode_frame(&bytes))
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let crop =
+        CropRect::from_selection(&monitor, probe.width(), probe.height(), x, y, width, height)?;
+
+    let timestamp = crate::unix_epoch_ms().round() as u128;
+    let path = crate::downloads_directory(directory).join(format!(
+        "xshot-{}.{}",
+        timestamp,
+        format.extension()
+    ));
+    let sink = create_sink(format, &path, crop.width, crop.height, fps)?;
+
+    let control = Arc::new(RecordingControl::default());
+    let (sender, receiver) = sync_channel(FRAME_QUEUE_DEPTH);
+    let frame_interval_ms = 1000 / fps;
+    let encoder = std::thread::spawn(move || run_encoder(receiver, sink, frame_interval_ms));
+    let capture = std::thread::spawn({
+        let app = app.clone();
+        let control = control.clone();
+        move || run_capture(app, monitor, crop, fps, control, sender)
+    });
+
+    println!(
+        "[xshot][recording] stage=started monitor={} rect={},{},{},{} format={:?} fps={} path={}",
+        window_label,
+        crop.x,
+        crop.y,
+        crop.width,
+        crop.height,
+        format,
+        fps,
+        path.display()
+    );
+    reservation.activate(ActiveRecording {
+        control,
+        format,
+        path,
+        width: crop.width,
+        height: crop.height,
+        capture,
+        encoder,
+    })?;
+    Ok(emit_status(&app))
+}
+
+fn set_paused(app: &AppHandle, paused: bool) -> Result<RecordingStatus, String> {
+    {
+        let state = app.state::<RecordingState>();
+        let slot = state
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock recording state".to_string())?;
+        match slot.as_ref() {
+            Some(RecordingSlot::Active(active)) => {
+                active.control.paused.store(paused, Ordering::SeqCst)
+            }
+            _ => return Err("No recording in progress".into()),
+        }
+    }
+    Ok(emit_status(app))
+}
+
+#[tauri::command]
+pub fn pause_recording(app: AppHandle) -> Result<RecordingStatus, String> {
+    set_paused(&app, true)
+}
+
+#[tauri::command]
+pub fn resume_recording(app: AppHandle) -> Result<RecordingStatus, String> {
+    set_paused(&app, false)
+}
+
+pub fn toggle_pause(app: &AppHandle) -> Result<RecordingStatus, String> {
+    let paused = current_status(app).paused;
+    set_paused(app, !paused)
+}
+
+#[tauri::command]
+pub async fn stop_recording(app: AppHandle) -> Result<RecordingResult, String> {
+    tauri::async_runtime::spawn_blocking(move || finish(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn get_recording_status(app: AppHandle) -> RecordingStatus {
+    current_status(&app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::AnimationDecoder;
+
+    fn monitor(width: f64, height: f64) -> CaptureMonitor {
+        CaptureMonitor {
+            id: 1,
+            label: "test".into(),
+            x: 0.0,
+            y: 0.0,
+            width,
+            height,
+            scale_factor: 2.0,
+            is_primary: true,
+            name: "test".into(),
+            physical_x: 0.0,
+            physical_y: 0.0,
+            physical_width: width * 2.0,
+            physical_height: height * 2.0,
+        }
+    }
+
+    fn frames() -> Vec<(image::RgbaImage, u32)> {
+        [100, 250, 40]
+            .into_iter()
+            .enumerate()
+            .map(|(index, delay)| {
+                let frame = image::RgbaImage::from_fn(37, 21, |x, y| {
+                    image::Rgba([
+                        (x * 7) as u8,
+                        (y * 11) as u8,
+                        index as u8 * 90,
+                        255 - x as u8,
+                    ])
+                });
+                (frame, delay)
+            })
+            .collect()
+    }
+
+    fn record(format: RecordingFormat) -> (PathBuf, Vec<(image::RgbaImage, u32)>) {
+        let path = std::env::temp_dir().join(format!(
+            "xshot-recording-test-{}.{}",
+            std::process::id(),
+            format.extension()
+        ));
+        let frames = frames();
+        let mut sink = create_sink(format, &path, 37, 21, 10).unwrap();
+        for (frame, delay) in &frames {
+            sink.push(frame, *delay).unwrap();
+        }
+        sink.finish().unwrap();
+        (path, frames)
+    }
+
+    fn assert_frames(decoded: Vec<image::Frame>, expected: &[(image::RgbaImage, u32)]) {
+        assert_eq!(decoded.len(), expected.len());
+        for (frame, (image, delay)) in decoded.iter().zip(expected) {
+            assert_eq!(frame.buffer(), image);
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            assert_eq!(numer / denom, *delay);
+        }
+    }
+
+    #[test]
+    fn selection_scales_to_capture_pixels() {
+        let crop = CropRect::from_selection(
+            &monitor(1000.0, 500.0),
+            2000,
+            1000,
+            10.25,
+            20.0,
+            100.0,
+            50.5,
+        )
+        .unwrap();
+        assert_eq!(
+            (crop.x, crop.y, crop.width, crop.height),
+            (21, 40, 200, 101)
+        );
+    }
+
+    #[test]
+    fn selection_is_clamped_to_the_monitor() {
+        let crop = CropRect::from_selection(
+            &monitor(1000.0, 500.0),
+            2000,
+            1000,
+            -10.0,
+            450.0,
+            200.0,
+            100.0,
+        )
+        .unwrap();
+        assert_eq!(
+            (crop.x, crop.y, crop.width, crop.height),
+            (0, 900, 380, 100)
+        );
+        assert!(CropRect::from_selection(
+            &monitor(1000.0, 500.0),
+            2000,
+            1000,
+            1200.0,
+            0.0,
+            50.0,
+            50.0
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn apng_decodes_back_to_the_recorded_frames() {
+        let (path, frames) = record(RecordingFormat::Apng);
+        let file = BufReader::new(std::fs::File::open(&path).unwrap());
+        let decoded = image::codecs::png::PngDecoder::new(file)
+            .unwrap()
+            .apng()
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_frames(decoded, &frames);
+    }
+
+    #[test]
+    fn webp_decodes_back_to_the_recorded_frames() {
+        let (path, frames) = record(RecordingFormat::Webp);
+        let file = BufReader::new(std::fs::File::open(&path).unwrap());
+        let decoded = image::codecs::webp::WebPDecoder::new(file)
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_frames(decoded, &frames);
+    }
+
+    #[test]
+    fn png_chunks_walks_every_chunk() {
+        let png = crate::encode_rgba_png_fast(&frames()[0].0).unwrap();
+        let chunks = png_chunks(&png).collect::<Vec<_>>();
+        assert_eq!(chunks.first().unwrap().0, b"IHDR");
+        assert_eq!(&chunks[0].1[..8], &[0, 0, 0, 37, 0, 0, 0, 21]);
+        assert!(chunks.iter().any(|(kind, _)| kind == b"IDAT"));
+        assert_eq!(chunks.last().unwrap(), &(&b"IEND"[..], &[][..]));
+        assert_eq!(png_chunks(&png[..png.len() - 5]).count(), chunks.len() - 1);
+    }
+
+    #[test]
+    fn riff_chunk_skips_padded_chunks() {
+        let body = [
+            &b"ICCP"[..],
+            &3u32.to_le_bytes(),
+            &[1, 2, 3, 0],
+            b"VP8L",
+            &2u32.to_le_bytes(),
+            &[4, 5],
+        ]
+        .concat();
+        assert_eq!(riff_chunk(&body, b"VP8L"), Some(&body[12..]));
+        assert_eq!(riff_chunk(&body, b"ICCP"), Some(&body[..12]));
+        assert_eq!(riff_chunk(&body, b"ALPH"), None);
+    }
+}
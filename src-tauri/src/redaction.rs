@@ -0,0 +1,421 @@
+// Irreversible redaction applied to exported pixels. Unlike the canvas mosaic, every mode here
+// throws the original detail away: pixelation keeps only jittered block averages, blur runs on top
+// of that pixelation, and solid fill keeps nothing.
+use crate::watermark;
+use image::RgbaImage;
+use serde::Deserialize;
+
+const DEFAULT_PIXELATE_BLOCK: u32 = 12;
+const MIN_PIXELATE_BLOCK: u32 = 8;
+const DEFAULT_BLUR_RADIUS: u32 = 16;
+const MIN_BLUR_RADIUS: u32 = 8;
+/// Per-channel jitter added to each block average, so averages can't be matched exactly against
+/// re-rendered candidate text.
+const BLOCK_JITTER: f64 = 6.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RedactionMode {
+    #[default]
+    Pixelate,
+    Blur,
+    Solid,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionRegion {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+}
+
+struct Area {
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+}
+
+impl RedactionRegion {
+    /// The smallest whole-pixel region containing fractional bounds, so partly covered pixels
+    /// are redacted too.
+    pub fn covering(left: f64, top: f64, width: f64, height: f64) -> Self {
+        let (x, y) = (left.floor() as i64, top.floor() as i64);
+        Self {
+            x,
+            y,
+            width: (left + width).ceil() as i64 - x,
+            height: (top + height).ceil() as i64 - y,
+        }
+    }
+
+    fn clamp_to(&self, image: &RgbaImage) -> Option<Area> {
+        let left = self.x.clamp(0, image.width() as i64);
+        let top = self.y.clamp(0, image.height() as i64);
+        let right = (self.x + self.width).clamp(0, image.width() as i64);
+        let bottom = (self.y + self.height).clamp(0, image.height() as i64);
+        (right > left && bottom > top).then(|| Area {
+            left: left as u32,
+            top: top as u32,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        })
+    }
+}
+
+fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn pixelate(image: &mut RgbaImage, area: &Area, block: u32, random: &mut impl FnMut() -> f64) {
+    for block_top in (area.top..area.top + area.height).step_by(block as usize) {
+        for block_left in (area.left..area.left + area.width).step_by(block as usize) {
+            let right = (block_left + block).min(area.left + area.width);
+            let bottom = (block_top + block).min(area.top + area.height);
+            let mut sums = [0.0f64; 4];
+            for y in block_top..bottom {
+                for x in block_left..right {
+                    for (sum, value) in sums.iter_mut().zip(image.get_pixel(x, y).0) {
+                        *sum += f64::from(value);
+                    }
+                }
+            }
+            let count = f64::from((right - block_left) * (bottom - block_top));
+            let mut color = [0u8; 4];
+            for (channel, value) in color.iter_mut().enumerate() {
+                let jitter = if channel < 3 {
+                    (random() * 2.0 - 1.0) * BLOCK_JITTER
+                } else {
+                    0.0
+                };
+                *value = (sums[channel] / count + jitter).round().clamp(0.0, 255.0) as u8;
+            }
+            for y in block_top..bottom {
+                for x in block_left..right {
+                    image.put_pixel(x, y, image::Rgba(color));
+                }
+            }
+        }
+    }
+}
+
+fn gaussian_kernel(radius: u32) -> Vec<f64> {
+    let sigma = radius as f64 / 3.0;
+    let weights = (-(radius as i64)..=radius as i64)
+        .map(|offset| (-((offset * offset) as f64) / (2.0 * sigma * sigma)).exp())
+        .collect::<Vec<_>>();
+    let total = weights.iter().sum::<f64>();
+    weights.into_iter().map(|weight| weight / total).collect()
+}
+
+/// Separable Gaussian blur confined to `area`, clamping at its edges so nothing outside the
+/// region bleeds in.
+fn blur(image: &mut RgbaImage, area: &Area, radius: u32) {
+    let kernel = gaussian_kernel(radius);
+    let (width, height) = (area.width as usize, area.height as usize);
+    let mut plane = vec![[0.0f64; 4]; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = image.get_pixel(area.left + x as u32, area.top + y as u32);
+            plane[y * width + x] = pixel.0.map(f64::from);
+        }
+    }
+
+    let pass = |source: &[[f64; 4]], horizontal: bool| {
+        let mut output = vec![[0.0f64; 4]; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0f64; 4];
+                for (index, weight) in kernel.iter().enumerate() {
+                    let offset = index as i64 - radius as i64;
+                    let (sx, sy) = if horizontal {
+                        ((x as i64 + offset).clamp(0, width as i64 - 1) as usize, y)
+                    } else {
+                        (x, (y as i64 + offset).clamp(0, height as i64 - 1) as usize)
+                    };
+                    for (total, value) in sum.iter_mut().zip(source[sy * width + sx]) {
+                        *total += value * weight;
+                    }
+                }
+                output[y * width + x] = sum;
+            }
+        }
+        output
+    };
+    let plane = pass(&pass(&plane, true), false);
+
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = plane[y * width + x].map(|value| value.round().clamp(0.0, 255.0) as u8);
+            image.put_pixel(
+                area.left + x as u32,
+                area.top + y as u32,
+                image::Rgba(pixel),
+            );
+        }
+    }
+}
+
+/// Redacts every region in place and returns how many overlapped the image. `strength` is the
+/// block size for pixelation and the blur radius for blur, both raised to a safe minimum; `color`
+/// is a `#rrggbb` fill for solid mode and defaults to black.
+pub fn redact(
+    image: &mut RgbaImage,
+    regions: &[RedactionRegion],
+    mode: RedactionMode,
+    strength: Option<u32>,
+    color: Option<&str>,
+) -> Result<usize, String> {
+    let fill = match color {
+        Some(value) => {
+            parse_hex_color(value).ok_or_else(|| format!("Invalid redaction color {}", value))?
+        }
+        None => [0, 0, 0],
+    };
+    let mut seed = [0u8; 4];
+    getrandom::fill(&mut seed).map_err(|e| format!("Failed to generate random bytes: {}", e))?;
+    let mut random = watermark::prng(u32::from_le_bytes(seed));
+
+    let areas = regions
+        .iter()
+        .filter_map(|region| region.clamp_to(image))
+        .collect::<Vec<_>>();
+    for area in &areas {
+        match mode {
+            RedactionMode::Pixelate => {
+                let block = strength
+                    .unwrap_or(DEFAULT_PIXELATE_BLOCK)
+                    .max(MIN_PIXELATE_BLOCK);
+                pixelate(image, area, block, &mut random);
+            }
+            RedactionMode::Blur => {
+                let radius = strength.unwrap_or(DEFAULT_BLUR_RADIUS).max(MIN_BLUR_RADIUS);
+                // A plain blur can be deconvolved, so only blur what pixelation already reduced
+                // to block averages.
+                pixelate(image, area, radius.div_ceil(2), &mut random);
+                blur(image, area, radius);
+            }
+            RedactionMode::Solid => {
+                for y in area.top..area.top + area.height {
+                    for x in area.left..area.left + area.width {
+                        image.put_pixel(x, y, image::Rgba([fill[0], fill[1], fill[2], 255]));
+                    }
+                }
+            }
+        }
+    }
+    Ok(areas.len())
+}
+
+#[tauri::command]
+pub async fn redact_regions(
+    blob_data: Vec<u8>,
+    regions: Vec<RedactionRegion>,
+    mode: RedactionMode,
+    strength: Option<u32>,
+    color: Option<String>,
+) -> Result<tauri::ipc::Response, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut image = image::load_from_memory(&blob_data)
+            .map_err(|e| format!("Failed to decode image: {}", e))?
+            .to_rgba8();
+        let redacted = redact(&mut image, &regions, mode, strength, color.as_deref())?;
+        println!(
+            "[xshot][redaction][rust] mode={:?} regions={} redacted={}",
+            mode,
+            regions.len(),
+            redacted
+        );
+        crate::encode_rgba_png_fast(&image).map(tauri::ipc::Response::new)
+    })
+    .await
+    .map_err(|e| format!("Redaction task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const REGION: RedactionRegion = RedactionRegion {
+        x: 8,
+        y: 8,
+        width: 48,
+        height: 40,
+    };
+
+    /// Dark glyph-like strokes on white, with detail down to single pixels.
+    fn text_image() -> RgbaImage {
+        RgbaImage::from_fn(72, 64, |x, y| {
+            if (x * 7 + y * 13) % 5 < 2 || (x % 9 == 3 && y % 11 < 7) {
+                Rgba([24, 28, 36, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        })
+    }
+
+    fn in_region(x: u32, y: u32) -> bool {
+        (8..56).contains(&x) && (8..48).contains(&y)
+    }
+
+    fn redacted(mode: RedactionMode, strength: Option<u32>) -> RgbaImage {
+        let mut image = text_image();
+        assert_eq!(redact(&mut image, &[REGION], mode, strength, None), Ok(1));
+        image
+    }
+
+    fn assert_outside_untouched(image: &RgbaImage) {
+        let source = text_image();
+        for (x, y, pixel) in image.enumerate_pixels() {
+            if !in_region(x, y) {
+                assert_eq!(pixel, source.get_pixel(x, y), "pixel {},{}", x, y);
+            }
+        }
+    }
+
+    /// Every `block`-sized cell of the region, counted from its top-left corner.
+    fn blocks(block: u32) -> impl Iterator<Item = (u32, u32)> {
+        (8..48)
+            .step_by(block as usize)
+            .flat_map(move |top| (8..56).step_by(block as usize).map(move |left| (left, top)))
+    }
+
+    #[test]
+    fn pixelate_keeps_one_color_per_block() {
+        for (strength, block) in [
+            (None, DEFAULT_PIXELATE_BLOCK),
+            (Some(1), MIN_PIXELATE_BLOCK),
+            (Some(MIN_PIXELATE_BLOCK), MIN_PIXELATE_BLOCK),
+        ] {
+            let image = redacted(RedactionMode::Pixelate, strength);
+            for (left, top) in blocks(block) {
+                let color = image.get_pixel(left, top);
+                for y in top..(top + block).min(48) {
+                    for x in left..(left + block).min(56) {
+                        assert_eq!(image.get_pixel(x, y), color, "strength {:?}", strength);
+                    }
+                }
+            }
+            assert_outside_untouched(&image);
+        }
+    }
+
+    #[test]
+    fn pixelate_does_not_match_rerendered_source() {
+        // Re-pixelating the right candidate text reproduces the exact block averages, which the
+        // jitter must break.
+        let source = text_image();
+        let block = MIN_PIXELATE_BLOCK;
+        let image = redacted(RedactionMode::Pixelate, Some(block));
+        let matches = blocks(block)
+            .filter(|&(left, top)| {
+                let mut sums = [0u32; 3];
+                for y in top..top + block {
+                    for x in left..left + block {
+                        for (sum, value) in sums.iter_mut().zip(source.get_pixel(x, y).0) {
+                            *sum += u32::from(value);
+                        }
+                    }
+                }
+                let average = sums.map(|sum| (f64::from(sum) / f64::from(block * block)).round());
+                let redacted = image.get_pixel(left, top).0;
+                (0..3).all(|channel| f64::from(redacted[channel]) == average[channel])
+            })
+            .count();
+        assert!(
+            matches * 10 < blocks(block).count(),
+            "{} blocks match",
+            matches
+        );
+        assert_ne!(image, redacted(RedactionMode::Pixelate, Some(block)));
+    }
+
+    #[test]
+    fn blur_leaves_no_per_pixel_detail() {
+        for strength in [None, Some(1), Some(MIN_BLUR_RADIUS)] {
+            let image = redacted(RedactionMode::Blur, strength);
+            for y in 8..48 {
+                for x in 8..55 {
+                    let step = (0..3)
+                        .map(|channel| {
+                            image.get_pixel(x, y)[channel]
+                                .abs_diff(image.get_pixel(x + 1, y)[channel])
+                        })
+                        .max()
+                        .unwrap();
+                    assert!(
+                        step <= 12,
+                        "strength {:?} step {} at {},{}",
+                        strength,
+                        step,
+                        x,
+                        y
+                    );
+                }
+            }
+            assert_outside_untouched(&image);
+        }
+        assert_ne!(
+            redacted(RedactionMode::Blur, None),
+            redacted(RedactionMode::Blur, None)
+        );
+    }
+
+    #[test]
+    fn solid_fills_only_the_clamped_region() {
+        let mut image = text_image();
+        let regions = [
+            REGION,
+            RedactionRegion {
+                x: -20,
+                y: 60,
+                width: 10,
+                height: 10,
+            },
+        ];
+        let count = redact(
+            &mut image,
+            &regions,
+            RedactionMode::Solid,
+            None,
+            Some("#336699"),
+        );
+        assert_eq!(count, Ok(1));
+        for (x, y, pixel) in image.enumerate_pixels() {
+            if in_region(x, y) {
+                assert_eq!(*pixel, Rgba([0x33, 0x66, 0x99, 255]));
+            }
+        }
+        assert_outside_untouched(&image);
+    }
+
+    #[test]
+    fn fractional_bounds_cover_partly_covered_pixels() {
+        let (left, top, width, height) = (10.6, 12.3, 20.8, 15.5);
+        let mut image = text_image();
+        let region = RedactionRegion::covering(left, top, width, height);
+        let count = redact(
+            &mut image,
+            &[region],
+            RedactionMode::Solid,
+            None,
+            Some("#000000"),
+        );
+        assert_eq!(count, Ok(1));
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let touched = (x as f64 + 1.0) > left
+                && (x as f64) < left + width
+                && (y as f64 + 1.0) > top
+                && (y as f64) < top + height;
+            assert_eq!(*pixel == Rgba([0, 0, 0, 255]), touched, "pixel {x},{y}");
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use crate::coordinates::{CoordinateSpace, Rect};
+use crate::frame::{decode_frame, encode_frame, FrameFormat};
+use crate::{CaptureMonitor, CaptureWindowRegion};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::State;
+
+const VIRTUAL_DESKTOP_MONITOR_ID: u32 = 0;
+
+#[derive(Debug, Clone)]
+pub struct VirtualDesktopLayout {
+    origin_x: f64,
+    origin_y: f64,
+    width: f64,
+    height: f64,
+    pixel_scale: f64,
+    monitors: Vec<CaptureMonitor>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualDesktopSegment {
+    label: String,
+    monitor_id: u32,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    desktop_x: f64,
+    desktop_y: f64,
+    scale_factor: f64,
+    coordinate_space: CoordinateSpace,
+}
+
+#[derive(Default)]
+pub struct VirtualDesktopStore(Mutex<HashMap<String, VirtualDesktopLayout>>);
+
+impl VirtualDesktopStore {
+    pub fn insert(&self, capture_id: String, layout: VirtualDesktopLayout) {
+        if let Ok(mut layouts) = self.0.lock() {
+            layouts.insert(capture_id, layout);
+        }
+    }
+
+    /// Whether a virtual-desktop capture is on screen and not finished yet.
+    pub fn is_active(&self) -> bool {
+        self.0.lock().is_ok_and(|layouts| !layouts.is_empty())
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut layouts) = self.0.lock() {
+            layouts.clear();
+        }
+    }
+}
+
+pub fn virtual_desktop_label() -> String {
+    format!("{}_virtual", crate::SCREENSHOT_WINDOW_PREFIX)
+}
+
+// Monitor bounds in the space xcap reports them in: global points on macOS, where they tile
+// correctly, and physical pixels elsewhere, where per-monitor logical origins can overlap.
+fn desktop_bounds(monitor: &CaptureMonitor) -> Rect {
+    monitor.space().bounds(CoordinateSpace::native())
+}
+
+impl VirtualDesktopLayout {
+    /// `pixel_scale` is the number of output pixels per desktop coordinate unit; it is the
+    /// densest monitor's scale so no display is downsampled in the stitched image.
+    pub fn new(monitors: &[CaptureMonitor], pixel_scale: f64) -> Option<Self> {
+        let bounds = monitors
+            .iter()
+            .map(desktop_bounds)
+            .reduce(|union, bounds| union.union(&bounds))?;
+
+        Some(Self {
+            origin_x: bounds.x,
+            origin_y: bounds.y,
+            width: bounds.width.max(1.0),
+            height: bounds.height.max(1.0),
+            pixel_scale: pixel_scale.max(1.0),
+            monitors: monitors.to_vec(),
+        })
+    }
+
+    pub fn pixel_size(&self) -> (u32, u32) {
+        (
+            (self.width * self.pixel_scale).round().max(1.0) as u32,
+            (self.height * self.pixel_scale).round().max(1.0) as u32,
+        )
+    }
+
+    pub fn monitor(&self) -> CaptureMonitor {
+        CaptureMonitor::new(
+            VIRTUAL_DESKTOP_MONITOR_ID,
+            virtual_desktop_label(),
+            Rect::new(self.origin_x, self.origin_y, self.width, self.height),
+            CoordinateSpace::native(),
+            self.pixel_scale,
+            self.monitors.iter().any(|monitor| monitor.is_primary),
+            "Virtual Desktop".to_string(),
+        )
+    }
+
+    pub fn stitch(&self, images: &HashMap<String, image::RgbaImage>) -> image::RgbaImage {
+        let (canvas_width, canvas_height) = self.pixel_size();
+        let mut canvas = image::RgbaImage::new(canvas_width, canvas_height);
+
+        for monitor in &self.monitors {
+            let Some(image) = images.get(&monitor.label) else {
+                continue;
+            };
+            let bounds = desktop_bounds(monitor);
+            let left = ((bounds.x - self.origin_x) * self.pixel_scale).round() as i64;
+            let top = ((bounds.y - self.origin_y) * self.pixel_scale).round() as i64;
+            let width = (bounds.width * self.pixel_scale).round().max(1.0) as u32;
+            let height = (bounds.height * self.pixel_scale).round().max(1.0) as u32;
+
+            if image.width() == width && image.height() == height {
+                image::imageops::replace(&mut canvas, image, left, top);
+            } else {
+                let resized = image::imageops::resize(
+                    image,
+                    width,
+                    height,
+                    image::imageops::FilterType::Triangle,
+                );
+                image::imageops::replace(&mut canvas, &resized, left, top);
+            }
+        }
+
+        canvas
+    }
+
+    /// Re-expresses per-monitor regions in desktop coordinates, merging the clipped halves of
+    /// windows that straddle a monitor edge back into one region.
+    pub fn merge_window_regions(
+        &self,
+        regions_by_monitor: HashMap<String, Vec<CaptureWindowRegion>>,
+    ) -> Vec<CaptureWindowRegion> {
+        let mut merged: Vec<CaptureWindowRegion> = Vec::new();
+
+        for monitor in &self.monitors {
+            let Some(regions) = regions_by_monitor.get(&monitor.label) else {
+                continue;
+            };
+            let bounds = desktop_bounds(monitor);
+            let offset_x = bounds.x - self.origin_x;
+            let offset_y = bounds.y - self.origin_y;
+
+            for region in regions {
+                let mut region = region.clone();
+                region.x += offset_x;
+                region.y += offset_y;
+                region.monitor_width = self.width;
+                region.monitor_height = self.height;
+                region.is_fullscreen_like = false;
+                region.is_overlay_candidate = false;
+                region.coordinate_space = CoordinateSpace::native();
+                region.scale_factor = self.pixel_scale;
+
+                if let Some(existing) = merged.iter_mut().find(|existing| existing.id == region.id)
+                {
+                    let right = (existing.x + existing.width).max(region.x + region.width);
+                    let bottom = (existing.y + existing.height).max(region.y + region.height);
+                    existing.x = existing.x.min(region.x);
+                    existing.y = existing.y.min(region.y);
+                    existing.width = right - existing.x;
+                    existing.height = bottom - existing.y;
+                } else {
+                    merged.push(region);
+                }
+            }
+        }
+
+        merged
+    }
+
+    /// Splits a rectangle given in desktop coordinates (relative to the layout origin) into
+    /// the pieces that fall on each monitor, in that monitor's local coordinates.
+    pub fn map_rect(&self, x: f64, y: f64, width: f64, height: f64) -> Vec<VirtualDesktopSegment> {
+        let rect = Rect::new(x, y, width.max(0.0), height.max(0.0));
+
+        self.monitors
+            .iter()
+            .filter_map(|monitor| {
+                let bounds = desktop_bounds(monitor).translate(-self.origin_x, -self.origin_y);
+                let clipped = rect.intersect(&bounds)?;
+
+                Some(VirtualDesktopSegment {
+                    label: monitor.label.clone(),
+                    monitor_id: monitor.id,
+                    x: clipped.x - bounds.x,
+                    y: clipped.y - bounds.y,
+                    width: clipped.width,
+                    height: clipped.height,
+                    desktop_x: clipped.x,
+                    desktop_y: clipped.y,
+                    scale_factor: monitor.scale_factor,
+                    coordinate_space: CoordinateSpace::native(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Replaces the per-monitor captures of one capture id with a single stitched capture.
+pub fn stitch_prepared_captures(
+    monitors: &[CaptureMonitor],
+    prepared: HashMap<(String, String), Vec<u8>>,
+    capture_id: &str,
+    format: FrameFormat,
+) -> Result<(VirtualDesktopLayout, Vec<u8>), String> {
+    let mut images = HashMap::with_capacity(prepared.len());
+    let mut pixel_scale = 1.0_f64;
+
+    for monitor in monitors {
+        let Some(bytes) = prepared.get(&(capture_id.to_string(), monitor.label.clone())) else {
+            continue;
+        };
+        let image = decode_frame(bytes)
+            .map_err(|e| format!("Failed to decode capture of {}: {}", monitor.label, e))?;
+        pixel_scale =
+            pixel_scale.max(image.width() as f64 / desktop_bounds(monitor).width.max(1.0));
+        images.insert(monitor.label.clone(), image);
+    }
+
+    let layout = VirtualDesktopLayout::new(monitors, pixel_scale).ok_or("No monitor found")?;
+    let stitched = layout.stitch(&images);
+    let bytes = encode_frame(&stitched, format)?;
+
+    Ok((layout, bytes))
+}
+
+#[tauri::command]
+pub fn map_virtual_desktop_selection(
+    store: State<'_, VirtualDesktopStore>,
+    capture_id: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) -> Result<Vec<VirtualDesktopSegment>, String> {
+    let layouts = store
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock virtual desktop store".to_string())?;
+    let layout = layouts
+        .get(&capture_id)
+        .ok_or("Virtual desktop capture not found")?;
+
+    Ok(layout.map_rect(x, y, width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLEAR: image::Rgba<u8> = image::Rgba([0, 0, 0, 0]);
+    const RED: image::Rgba<u8> = image::Rgba([255, 0, 0, 255]);
+    const BLUE: image::Rgba<u8> = image::Rgba([0, 0, 255, 255]);
+
+    // A 1x display up and to the left of the origin, and a 2x display at the origin whose top
+    // edge sits lower. Bounds are in the platform's native space, like xcap reports them.
+    fn layout() -> VirtualDesktopLayout {
+        let monitor = |id: u32, bounds: Rect, scale_factor: f64| {
+            CaptureMonitor::new(
+                id,
+                format!("screen_{}", id),
+                bounds,
+                CoordinateSpace::native(),
+                scale_factor,
+                id == 2,
+                String::new(),
+            )
+        };
+        VirtualDesktopLayout::new(
+            &[
+                monitor(1, Rect::new(-100.0, -20.0, 100.0, 60.0), 1.0),
+                monitor(2, Rect::new(0.0, 0.0, 80.0, 50.0), 2.0),
+            ],
+            2.0,
+        )
+        .unwrap()
+    }
+
+    fn region(id: u32, x: f64, y: f64, width: f64, height: f64) -> CaptureWindowRegion {
+        CaptureWindowRegion {
+            id,
+            pid: 1,
+            x,
+            y,
+            width,
+            height,
+            monitor_width: 100.0,
+            monitor_height: 60.0,
+            is_fullscreen_like: true,
+            is_overlay_candidate: true,
+            is_focused: false,
+            title: String::new(),
+            app_name: String::new(),
+            coordinate_space: CoordinateSpace::native(),
+            scale_factor: 1.0,
+        }
+    }
+
+    #[test]
+    fn layout_spans_every_monitor() {
+        let layout = layout();
+        assert_eq!(layout.pixel_size(), (360, 140));
+        let monitor = layout.monitor();
+        let bounds = monitor.space().bounds(CoordinateSpace::native());
+        assert_eq!(bounds, Rect::new(-100.0, -20.0, 180.0, 70.0));
+        assert!(monitor.is_primary);
+    }
+
+    #[test]
+    fn stitch_places_and_scales_each_monitor() {
+        let images = HashMap::from([
+            (
+                "screen_1".to_string(),
+                image::RgbaImage::from_pixel(100, 60, RED),
+            ),
+            (
+                "screen_2".to_string(),
+                image::RgbaImage::from_pixel(160, 100, BLUE),
+            ),
+        ]);
+        let canvas = layout().stitch(&images);
+        assert_eq!(canvas.dimensions(), (360, 140));
+        // The 1x display is upscaled to fill its 200x120 slot.
+        assert_eq!(canvas.get_pixel(0, 0), &RED);
+        assert_eq!(canvas.get_pixel(199, 119), &RED);
+        assert_eq!(canvas.get_pixel(200, 40), &BLUE);
+        assert_eq!(canvas.get_pixel(359, 139), &BLUE);
+        // Parts of the bounding box no display covers stay transparent.
+        assert_eq!(canvas.get_pixel(200, 39), &CLEAR);
+        assert_eq!(canvas.get_pixel(199, 120), &CLEAR);
+
+        let partial = layout().stitch(&HashMap::from([(
+            "screen_2".to_string(),
+            image::RgbaImage::from_pixel(160, 100, BLUE),
+        )]));
+        assert_eq!(partial.get_pixel(0, 0), &CLEAR);
+        assert_eq!(partial.get_pixel(200, 40), &BLUE);
+    }
+
+    #[test]
+    fn map_rect_splits_selections_across_monitors() {
+        let segments = layout().map_rect(50.0, 30.0, 100.0, 20.0);
+        assert_eq!(segments.len(), 2);
+
+        let left = &segments[0];
+        assert_eq!((left.label.as_str(), left.monitor_id), ("screen_1", 1));
+        assert_eq!(
+            (left.x, left.y, left.width, left.height),
+            (50.0, 30.0, 50.0, 20.0)
+        );
+        assert_eq!((left.desktop_x, left.desktop_y), (50.0, 30.0));
+        assert_eq!(left.scale_factor, 1.0);
+
+        let right = &segments[1];
+        assert_eq!((right.label.as_str(), right.monitor_id), ("screen_2", 2));
+        assert_eq!(
+            (right.x, right.y, right.width, right.height),
+            (0.0, 10.0, 50.0, 20.0)
+        );
+        assert_eq!((right.desktop_x, right.desktop_y), (100.0, 30.0));
+        assert_eq!(right.scale_factor, 2.0);
+
+        // Uncovered corners and empty selections map to nothing.
+        assert!(layout().map_rect(150.0, 0.0, 20.0, 10.0).is_empty());
+        assert!(layout().map_rect(10.0, 10.0, -5.0, 10.0).is_empty());
+    }
+
+    #[test]
+    fn straddling_windows_merge_into_one_region() {
+        let regions = HashMap::from([
+            (
+                "screen_1".to_string(),
+                vec![region(7, 60.0, 10.0, 40.0, 30.0)],
+            ),
+            (
+                "screen_2".to_string(),
+                vec![
+                    region(7, 0.0, 0.0, 30.0, 45.0),
+                    region(9, 10.0, 5.0, 20.0, 10.0),
+                ],
+            ),
+        ]);
+        let merged = layout().merge_window_regions(regions);
+        let rects = merged
+            .iter()
+            .map(|region| (region.id, region.x, region.y, region.width, region.height))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rects,
+            [(7, 60.0, 10.0, 70.0, 55.0), (9, 110.0, 25.0, 20.0, 10.0)]
+        );
+        for region in &merged {
+            assert_eq!((region.monitor_width, region.monitor_height), (180.0, 70.0));
+            assert_eq!(region.scale_factor, 2.0);
+            assert!(!region.is_fullscreen_like && !region.is_overlay_candidate);
+        }
+    }
+}
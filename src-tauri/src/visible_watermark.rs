@@ -0,0 +1,636 @@
+// Export-time visible watermark. The defaults reproduce `drawVisibleWatermark` in
+// `src/logic/watermark.ts`; on top of that the text can use `{user}`, `{date}` and `{hostname}`,
+// each line can be rotated, and a logo or a QR code of the capture ID can be stamped.
+use crate::watermark;
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use image::{GrayImage, Luma, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+const TEXT_COLOR: [u8; 3] = [17, 31, 45];
+const OPACITY_MIN: f32 = 0.08;
+const OPACITY_MAX: f32 = 0.35;
+const DIAGONAL_ROTATION: f32 = -30.0;
+const QR_QUIET_MODULES: u32 = 2;
+
+// DejaVu Sans Bold cut down to Latin, Greek, Cyrillic and common symbols, so text renders the
+// same on every machine. The system CJK collections only fill in characters it lacks.
+const EMBEDDED_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Bold-subset.ttf");
+const FALLBACK_FONTS: &[&str] = &[
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/PingFang.ttc",
+    "C:\\Windows\\Fonts\\msyhbd.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Bold.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Bold.ttc",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Placement {
+    #[default]
+    RepeatDiagonal,
+    RepeatHorizontal,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Placement {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "repeat-diagonal" => Some(Placement::RepeatDiagonal),
+            "repeat-horizontal" => Some(Placement::RepeatHorizontal),
+            "top-left" => Some(Placement::TopLeft),
+            "top-right" => Some(Placement::TopRight),
+            "bottom-left" => Some(Placement::BottomLeft),
+            "bottom-right" => Some(Placement::BottomRight),
+            _ => None,
+        }
+    }
+
+    fn is_repeat(self) -> bool {
+        matches!(
+            self,
+            Placement::RepeatDiagonal | Placement::RepeatHorizontal
+        )
+    }
+
+    /// Top-left corner for a `width`x`height` box inset by `margin`. Repeat placements have no
+    /// corner, so single marks fall back to the bottom right.
+    fn corner(self, image: &RgbaImage, width: u32, height: u32, margin: i64) -> (i64, i64) {
+        let right = image.width() as i64 - margin - width as i64;
+        let bottom = image.height() as i64 - margin - height as i64;
+        match self {
+            Placement::TopLeft => (margin, margin),
+            Placement::TopRight => (right, margin),
+            Placement::BottomLeft => (margin, bottom),
+            _ => (right, bottom),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogoOptions {
+    pub path: String,
+    /// Logo width as a fraction of the image's shorter side.
+    pub scale: f32,
+    pub opacity: f32,
+    pub placement: Placement,
+}
+
+impl Default for LogoOptions {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            scale: 0.15,
+            opacity: 0.35,
+            placement: Placement::BottomRight,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QrOptions {
+    /// QR width as a fraction of the image's shorter side.
+    pub scale: f32,
+    pub opacity: f32,
+    pub placement: Placement,
+}
+
+impl Default for QrOptions {
+    fn default() -> Self {
+        Self {
+            scale: 0.12,
+            opacity: 1.0,
+            placement: Placement::BottomRight,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VisibleWatermarkOptions {
+    pub text: String,
+    pub placement: Placement,
+    pub opacity: f32,
+    /// Clockwise degrees applied to each line. Defaults to -30 for `repeat-diagonal` and 0
+    /// otherwise, like the webview.
+    pub rotation: Option<f32>,
+    pub logo: Option<LogoOptions>,
+    pub qr: Option<QrOptions>,
+}
+
+impl Default for VisibleWatermarkOptions {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            placement: Placement::RepeatDiagonal,
+            opacity: 0.16,
+            rotation: None,
+            logo: None,
+            qr: None,
+        }
+    }
+}
+
+fn fonts() -> &'static [FontVec] {
+    static FONTS: OnceLock<Vec<FontVec>> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let embedded =
+            FontVec::try_from_vec(EMBEDDED_FONT.to_vec()).expect("embedded font is valid");
+        let fonts = std::iter::once(embedded)
+            .chain(
+                FALLBACK_FONTS
+                    .iter()
+                    .filter_map(|path| std::fs::read(path).ok())
+                    .filter_map(|data| FontVec::try_from_vec_and_index(data, 0).ok()),
+            )
+            .collect::<Vec<_>>();
+        println!(
+            "[xshot][watermark][rust] visible fonts loaded={}",
+            fonts.len()
+        );
+        fonts
+    })
+}
+
+fn user_name() -> String {
+    ["USER", "USERNAME", "LOGNAME"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_default()
+}
+
+fn host_name() -> String {
+    if let Some(name) = ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|value| !value.is_empty()))
+    {
+        return name;
+    }
+    std::process::Command::new("hostname")
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Expands `{user}`, `{date}` (UTC, `YYYY-MM-DD`) and `{hostname}`. Unknown placeholders are left
+/// as written.
+pub fn expand_template(template: &str, epoch_ms: f64) -> String {
+    let mut text = template.to_string();
+    if text.contains("{user}") {
+        text = text.replace("{user}", &user_name());
+    }
+    if text.contains("{date}") {
+        text = text.replace("{date}", &watermark::iso_timestamp(epoch_ms)[..10]);
+    }
+    if text.contains("{hostname}") {
+        text = text.replace("{hostname}", &host_name());
+    }
+    text
+}
+
+struct LineLayout {
+    glyphs: Vec<(usize, ab_glyph::Glyph)>,
+    width: f32,
+    height: f32,
+}
+
+fn layout_line(fonts: &[FontVec], text: &str, font_size: f32) -> LineLayout {
+    let px_scale = |font: &FontVec| {
+        let units_per_em = font.units_per_em().unwrap_or(1000.0);
+        PxScale::from(font_size * font.height_unscaled() / units_per_em)
+    };
+    let primary_scaled = fonts[0].as_scaled(px_scale(&fonts[0]));
+    let ascent = primary_scaled.ascent();
+
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0f32;
+    let mut previous: Option<(usize, ab_glyph::GlyphId)> = None;
+    for character in text.chars() {
+        let index = fonts
+            .iter()
+            .position(|font| font.glyph_id(character).0 != 0)
+            .unwrap_or(0);
+        let scaled = fonts[index].as_scaled(px_scale(&fonts[index]));
+        let id = scaled.glyph_id(character);
+        if let Some((previous_index, previous_id)) = previous {
+            if previous_index == index {
+                caret += scaled.kern(previous_id, id);
+            }
+        }
+        glyphs.push((
+            index,
+            id.with_scale_and_position(scaled.scale(), point(caret, ascent)),
+        ));
+        caret += scaled.h_advance(id);
+        previous = Some((index, id));
+    }
+    LineLayout {
+        glyphs,
+        width: caret,
+        height: ascent - primary_scaled.descent(),
+    }
+}
+
+/// Advance width of one line of text in pixels.
+pub fn measure_line(text: &str, font_size: f32) -> f32 {
+    layout_line(fonts(), text, font_size).width
+}
+
+/// Rasterizes one line of bold text into a coverage mask whose top is the line's ascent.
+pub fn render_line(text: &str, font_size: f32) -> GrayImage {
+    let fonts = fonts();
+    let layout = layout_line(fonts, text, font_size);
+    let mut mask = GrayImage::new(
+        layout.width.ceil().max(1.0) as u32,
+        layout.height.ceil().max(1.0) as u32,
+    );
+    for (index, glyph) in layout.glyphs {
+        let Some(outlined) = fonts[index].outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|x, y, coverage| {
+            let px = bounds.min.x as i64 + x as i64;
+            let py = bounds.min.y as i64 + y as i64;
+            if px >= 0 && py >= 0 && (px as u32) < mask.width() && (py as u32) < mask.height() {
+                let pixel = mask.get_pixel_mut(px as u32, py as u32);
+                pixel[0] = pixel[0].max((coverage.clamp(0.0, 1.0) * 255.0).round() as u8);
+            }
+        });
+    }
+    mask
+}
+
+/// Draws one opaque line of text with the top of its ascent at `(left, top)`.
+pub fn draw_line(
+    image: &mut RgbaImage,
+    text: &str,
+    font_size: f32,
+    left: i64,
+    top: i64,
+    color: [u8; 3],
+) {
+    blend_mask(image, &render_line(text, font_size), left, top, color, 1.0);
+}
+
+/// Rotates a mask clockwise about its centre into a canvas just large enough to hold it.
+fn rotate_mask(mask: &GrayImage, degrees: f32) -> GrayImage {
+    if degrees.abs() < 0.01 {
+        return mask.clone();
+    }
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (width, height) = (mask.width() as f32, mask.height() as f32);
+    let out_width = (width * cos.abs() + height * sin.abs()).ceil() as u32;
+    let out_height = (width * sin.abs() + height * cos.abs()).ceil() as u32;
+    let sample = |x: f32, y: f32| -> f32 {
+        if x < 0.0 || y < 0.0 || x >= width || y >= height {
+            return 0.0;
+        }
+        f32::from(mask.get_pixel(x as u32, y as u32)[0])
+    };
+    GrayImage::from_fn(out_width.max(1), out_height.max(1), |x, y| {
+        let dx = x as f32 + 0.5 - out_width as f32 / 2.0;
+        let dy = y as f32 + 0.5 - out_height as f32 / 2.0;
+        let sx = dx * cos + dy * sin + width / 2.0 - 0.5;
+        let sy = -dx * sin + dy * cos + height / 2.0 - 0.5;
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (fx, fy) = (sx - x0, sy - y0);
+        let value = sample(x0, y0) * (1.0 - fx) * (1.0 - fy)
+            + sample(x0 + 1.0, y0) * fx * (1.0 - fy)
+            + sample(x0, y0 + 1.0) * (1.0 - fx) * fy
+            + sample(x0 + 1.0, y0 + 1.0) * fx * fy;
+        Luma([value.round().clamp(0.0, 255.0) as u8])
+    })
+}
+
+fn blend_pixel(pixel: &mut image::Rgba<u8>, color: [u8; 3], alpha: f32) {
+    if alpha <= 0.0 {
+        return;
+    }
+    for channel in 0..3 {
+        pixel[channel] = (f32::from(pixel[channel]) * (1.0 - alpha)
+            + f32::from(color[channel]) * alpha)
+            .round() as u8;
+    }
+    pixel[3] = (f32::from(pixel[3]) + (255.0 - f32::from(pixel[3])) * alpha).round() as u8;
+}
+
+fn blend_mask(
+    image: &mut RgbaImage,
+    mask: &GrayImage,
+    left: i64,
+    top: i64,
+    color: [u8; 3],
+    opacity: f32,
+) {
+    for (x, y, coverage) in mask.enumerate_pixels() {
+        let px = left + x as i64;
+        let py = top + y as i64;
+        if coverage[0] == 0
+            || px < 0
+            || py < 0
+            || px as u32 >= image.width()
+            || py as u32 >= image.height()
+        {
+            continue;
+        }
+        let alpha = f32::from(coverage[0]) / 255.0 * opacity;
+        blend_pixel(image.get_pixel_mut(px as u32, py as u32), color, alpha);
+    }
+}
+
+fn blend_image(image: &mut RgbaImage, overlay: &RgbaImage, left: i64, top: i64, opacity: f32) {
+    for (x, y, source) in overlay.enumerate_pixels() {
+        let px = left + x as i64;
+        let py = top + y as i64;
+        if px < 0 || py < 0 || px as u32 >= image.width() || py as u32 >= image.height() {
+            continue;
+        }
+        let alpha = f32::from(source[3]) / 255.0 * opacity;
+        blend_pixel(
+            image.get_pixel_mut(px as u32, py as u32),
+            [source[0], source[1], source[2]],
+            alpha,
+        );
+    }
+}
+
+/// Centres of a grid tiled over the whole image in a frame rotated by `degrees`, matching the
+/// webview's translate/rotate loop. Centres further than `reach` outside the image are dropped.
+fn repeat_centres(
+    image: &RgbaImage,
+    spacing: (f32, f32),
+    degrees: f32,
+    reach: f32,
+) -> Vec<(f32, f32)> {
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    let range = width + height;
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let mut centres = Vec::new();
+    let mut y = -range;
+    while y <= range {
+        let mut x = -range;
+        while x <= range {
+            let cx = width / 2.0 + x * cos - y * sin;
+            let cy = height / 2.0 + x * sin + y * cos;
+            if cx >= -reach && cx <= width + reach && cy >= -reach && cy <= height + reach {
+                centres.push((cx, cy));
+            }
+            x += spacing.0;
+        }
+        y += spacing.1;
+    }
+    centres
+}
+
+fn draw_text(image: &mut RgbaImage, options: &VisibleWatermarkOptions, text: &str) {
+    let shortest_side = image.width().min(image.height()) as f32;
+    let font_size = (shortest_side / 18.0).round().clamp(18.0, 44.0);
+    let opacity = options.opacity.clamp(OPACITY_MIN, OPACITY_MAX);
+    let rotation = options.rotation.unwrap_or(match options.placement {
+        Placement::RepeatDiagonal => DIAGONAL_ROTATION,
+        _ => 0.0,
+    });
+    let lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let mask = render_line(line, font_size);
+            (mask.width(), rotate_mask(&mask, rotation))
+        })
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        return;
+    }
+    let line_height = lines
+        .iter()
+        .map(|(_, mask)| mask.height())
+        .max()
+        .unwrap_or(0) as f32;
+
+    if options.placement.is_repeat() {
+        let text_width = lines.iter().map(|(width, _)| *width).max().unwrap_or(0) as f32;
+        let block_height = font_size * 1.25 * lines.len() as f32;
+        let spacing = (
+            (text_width + 120.0).max(220.0),
+            (font_size * 4.0).max(block_height + font_size * 2.0),
+        );
+        let grid_rotation = match options.placement {
+            Placement::RepeatDiagonal => rotation,
+            _ => 0.0,
+        };
+        let (sin, cos) = grid_rotation.to_radians().sin_cos();
+        let reach = lines
+            .iter()
+            .map(|(_, mask)| mask.width().max(mask.height()))
+            .max()
+            .unwrap_or(0) as f32
+            + block_height;
+        for (cx, cy) in repeat_centres(image, spacing, grid_rotation, reach) {
+            for (index, (_, mask)) in lines.iter().enumerate() {
+                // Stack lines along the grid's own vertical axis.
+                let offset = (index as f32 - (lines.len() - 1) as f32 / 2.0) * font_size * 1.25;
+                let x = cx - offset * sin - mask.width() as f32 / 2.0;
+                let y = cy + offset * cos - mask.height() as f32 / 2.0;
+                blend_mask(
+                    image,
+                    mask,
+                    x.round() as i64,
+                    y.round() as i64,
+                    TEXT_COLOR,
+                    opacity,
+                );
+            }
+        }
+        return;
+    }
+
+    let margin = (font_size * 0.9).max(18.0) as i64;
+    let block_width = lines
+        .iter()
+        .map(|(_, mask)| mask.width())
+        .max()
+        .unwrap_or(0);
+    let block_height = (line_height * lines.len() as f32) as u32;
+    let (left, top) = options
+        .placement
+        .corner(image, block_width, block_height, margin);
+    let align_right = matches!(
+        options.placement,
+        Placement::TopRight | Placement::BottomRight
+    );
+    for (index, (_, mask)) in lines.iter().enumerate() {
+        let x = if align_right {
+            left + (block_width - mask.width()) as i64
+        } else {
+            left
+        };
+        let y = top + (index as f32 * line_height) as i64;
+        blend_mask(image, mask, x, y, TEXT_COLOR, opacity);
+    }
+}
+
+fn draw_logo(image: &mut RgbaImage, options: &LogoOptions) -> Result<(), String> {
+    let logo = image::open(&options.path)
+        .map_err(|e| format!("Failed to open logo {}: {}", options.path, e))?
+        .to_rgba8();
+    let shortest_side = image.width().min(image.height()) as f32;
+    let width = (shortest_side * options.scale.clamp(0.01, 1.0))
+        .round()
+        .max(1.0) as u32;
+    let height =
+        ((logo.height() as f32 * width as f32 / logo.width().max(1) as f32).round() as u32).max(1);
+    let logo = image::imageops::resize(&logo, width, height, image::imageops::FilterType::Triangle);
+    let opacity = options.opacity.clamp(0.0, 1.0);
+
+    if options.placement.is_repeat() {
+        let spacing = ((width * 2).max(220) as f32, (height * 2).max(120) as f32);
+        let rotation = match options.placement {
+            Placement::RepeatDiagonal => DIAGONAL_ROTATION,
+            _ => 0.0,
+        };
+        let reach = width.max(height) as f32;
+        for (cx, cy) in repeat_centres(image, spacing, rotation, reach) {
+            let left = (cx - width as f32 / 2.0).round() as i64;
+            let top = (cy - height as f32 / 2.0).round() as i64;
+            blend_image(image, &logo, left, top, opacity);
+        }
+    } else {
+        let margin = (shortest_side * 0.03).round().max(12.0) as i64;
+        let (left, top) = options.placement.corner(image, width, height, margin);
+        blend_image(image, &logo, left, top, opacity);
+    }
+    Ok(())
+}
+
+fn draw_qr(image: &mut RgbaImage, options: &QrOptions, capture_id: &str) -> Result<(), String> {
+    let code = qrcode::QrCode::new(capture_id.as_bytes())
+        .map_err(|e| format!("Failed to encode capture ID as QR: {}", e))?;
+    let modules = code.width() as u32;
+    let colors = code.to_colors();
+    let shortest_side = image.width().min(image.height()) as f32;
+    let total_modules = modules + QR_QUIET_MODULES * 2;
+    let module_size = ((shortest_side * options.scale.clamp(0.01, 1.0)) / total_modules as f32)
+        .floor()
+        .max(2.0) as u32;
+    let size = total_modules * module_size;
+    let qr = RgbaImage::from_fn(size, size, |x, y| {
+        let mx = (x / module_size) as i64 - QR_QUIET_MODULES as i64;
+        let my = (y / module_size) as i64 - QR_QUIET_MODULES as i64;
+        let dark = mx >= 0
+            && my >= 0
+            && (mx as u32) < modules
+            && (my as u32) < modules
+            && colors[my as usize * modules as usize + mx as usize] == qrcode::Color::Dark;
+        if dark {
+            image::Rgba([0, 0, 0, 255])
+        } else {
+            image::Rgba([255, 255, 255, 255])
+        }
+    });
+    let margin = (shortest_side * 0.03).round().max(12.0) as i64;
+    let (left, top) = options.placement.corner(image, size, size, margin);
+    blend_image(image, &qr, left, top, options.opacity.clamp(0.0, 1.0));
+    Ok(())
+}
+
+/// Draws the text, logo and QR parts that are configured. Returns whether anything was drawn.
+pub fn apply(
+    image: &mut RgbaImage,
+    options: &VisibleWatermarkOptions,
+    capture_id: Option<&str>,
+    epoch_ms: f64,
+) -> Result<bool, String> {
+    let mut drawn = false;
+    let text = watermark::normalize_text(&expand_template(&options.text, epoch_ms));
+    if !text.is_empty() {
+        draw_text(image, options, &text);
+        drawn = true;
+    }
+    if let Some(logo) = options.logo.as_ref().filter(|logo| !logo.path.is_empty()) {
+        draw_logo(image, logo)?;
+        drawn = true;
+    }
+    if let (Some(qr), Some(capture_id)) = (
+        options.qr.as_ref(),
+        capture_id.filter(|capture_id| !capture_id.is_empty()),
+    ) {
+        draw_qr(image, qr, capture_id)?;
+        drawn = true;
+    }
+    Ok(drawn)
+}
+
+#[tauri::command]
+pub async fn render_visible_watermark(
+    blob_data: Vec<u8>,
+    options: VisibleWatermarkOptions,
+    capture_id: Option<String>,
+) -> Result<tauri::ipc::Response, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut image = image::load_from_memory(&blob_data)
+            .map_err(|e| format!("Failed to decode image: {}", e))?
+            .to_rgba8();
+        apply(
+            &mut image,
+            &options,
+            capture_id.as_deref(),
+            crate::unix_epoch_ms(),
+        )?;
+        crate::encode_rgba_png_fast(&image).map(tauri::ipc::Response::new)
+    })
+    .await
+    .map_err(|e| format!("Watermark task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_known_placeholders_only() {
+        assert_eq!(
+            expand_template("{date} {team}", 1_700_000_000_000.0),
+            "2023-11-14 {team}"
+        );
+        assert_eq!(
+            expand_template("by {user}", 0.0),
+            format!("by {}", user_name())
+        );
+        assert_eq!(expand_template("plain text", 0.0), "plain text");
+    }
+
+    #[test]
+    fn corners_are_inset_by_the_margin() {
+        let image = RgbaImage::new(200, 100);
+        let corner = |placement: Placement| placement.corner(&image, 40, 20, 10);
+        assert_eq!(corner(Placement::TopLeft), (10, 10));
+        assert_eq!(corner(Placement::TopRight), (150, 10));
+        assert_eq!(corner(Placement::BottomLeft), (10, 70));
+        assert_eq!(corner(Placement::BottomRight), (150, 70));
+        assert_eq!(corner(Placement::RepeatDiagonal), (150, 70));
+        assert_eq!(Placement::TopLeft.corner(&image, 250, 120, 10), (10, 10));
+        assert_eq!(
+            Placement::BottomRight.corner(&image, 250, 120, 10),
+            (-60, -30)
+        );
+    }
+
+    #[test]
+    fn embedded_font_covers_latin_greek_and_cyrillic() {
+        let embedded = &fonts()[0];
+        for character in "Aé ßŒ ΩπЖя –€™№".chars() {
+            assert_ne!(embedded.glyph_id(character).0, 0, "{}", character);
+        }
+        let mask = render_line("Xshot", 24.0);
+        assert_eq!(mask.width(), measure_line("Xshot", 24.0).ceil() as u32);
+        assert!(mask.pixels().any(|pixel| pixel[0] == 255));
+    }
+}
@@ -0,0 +1,971 @@
+// Port of the hidden watermark in `src/logic/watermark.ts`. Packets, block order and pixel
+// arithmetic match the webview implementation bit for bit, so images marked by either side decode
+// on the other.
+use crate::watermark_signing::{self, SignatureCheck, SignatureScheme, SigningKeys, Verifier};
+use crate::watermark_sync;
+use serde::{Deserialize, Serialize};
+
+const ROBUST_WATERMARK_MAGIC: &[u8] = b"XSWM2";
+const ROBUST_WATERMARK_VERSION: u8 = 2;
+const ROBUST_MAX_BODY_BYTES: usize = 1024;
+pub const ROBUST_BLOCK_SIZE: usize = 8;
+const ROBUST_HEADER_BLOCK_RATIO: f64 = 0.18;
+const ROBUST_MAX_HEADER_REPEATS: usize = 15;
+const ROBUST_MAX_BODY_REPEATS: usize = 9;
+const ROBUST_COEFF_A: (usize, usize) = (3, 2);
+const ROBUST_COEFF_B: (usize, usize) = (2, 3);
+const ROBUST_MARGIN: f64 = 18.0;
+const ROBUST_MAX_COEFF_DELTA: f64 = 46.0;
+const ROBUST_HEADER_BYTES: usize = ROBUST_WATERMARK_MAGIC.len() + 2 + 4;
+const ROBUST_HEADER_BITS: usize = ROBUST_HEADER_BYTES * 8;
+/// How far a repaired read may stray from the magic, and how many of the least reliable length,
+/// checksum and body bits it tries flipping.
+const REPAIR_MAGIC_ERRORS: usize = 4;
+const REPAIR_LENGTH_FLIPS: usize = 4;
+const REPAIR_BITS: usize = 10;
+const LEGACY_WATERMARK_MAGIC: &[u8] = b"XSHOTWM1";
+const LEGACY_WATERMARK_VERSION: u32 = 1;
+const LEGACY_MAX_PAYLOAD_BYTES: usize = 2048;
+/// `WATERMARK_TEXT_MAX_LENGTH` in settings.ts, counted in UTF-16 code units like JS strings.
+const WATERMARK_TEXT_MAX_LENGTH: usize = 160;
+
+type Basis = [f64; ROBUST_BLOCK_SIZE * ROBUST_BLOCK_SIZE];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedHiddenWatermark {
+    pub text: String,
+    pub created_at: Option<String>,
+    pub version: u32,
+    /// Only DCT watermarks written by the Rust path can carry a signature.
+    pub signature: Option<SignatureCheck>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DetectionMethod {
+    Dct,
+    Lsb,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Detection {
+    #[serde(flatten)]
+    pub watermark: DecodedHiddenWatermark,
+    pub method: DetectionMethod,
+    /// Share of repeated blocks that agreed with the majority, over all header and body bits.
+    pub vote_ratio: f64,
+    /// 0 when the votes are no better than chance, 1 when every repeat agreed. The LSB
+    /// watermark has no repeats, so a checksum match counts as certain.
+    pub confidence: f64,
+}
+
+/// Majority-voted bytes and how many of the individual block reads agreed with the majority.
+struct Votes {
+    bytes: Vec<u8>,
+    /// Summed votes per bit, whose magnitude says how sure the bit is.
+    sums: Vec<f64>,
+    agreeing: usize,
+    total: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyEnvelope {
+    version: Option<serde_json::Value>,
+    text: Option<serde_json::Value>,
+    created_at: Option<serde_json::Value>,
+}
+
+fn truncate_text(text: &str) -> String {
+    let mut units = 0;
+    text.chars()
+        .take_while(|character| {
+            units += character.len_utf16();
+            units <= WATERMARK_TEXT_MAX_LENGTH
+        })
+        .collect()
+}
+
+pub fn normalize_text(text: &str) -> String {
+    truncate_text(text.trim())
+}
+
+/// Formats milliseconds since the Unix epoch like `Date.prototype.toISOString`.
+pub fn iso_timestamp(epoch_ms: f64) -> String {
+    let epoch_ms = epoch_ms.floor() as i64;
+    let days = epoch_ms.div_euclid(86_400_000);
+    let ms_of_day = epoch_ms.rem_euclid(86_400_000);
+
+    // Civil-from-days, proleptic Gregorian calendar.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    )
+}
+
+pub fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> usize {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]]) as usize
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+pub fn bytes_to_bits(bytes: &[u8]) -> Vec<u8> {
+    (0..bytes.len() * 8)
+        .map(|index| (bytes[index >> 3] >> (7 - index % 8)) & 1)
+        .collect()
+}
+
+pub fn bits_to_bytes(bits: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+    for (index, bit) in bits.iter().enumerate() {
+        bytes[index >> 3] |= bit << (7 - index % 8);
+    }
+    bytes
+}
+
+/// mulberry32, as in `createPrng`.
+pub fn prng(seed: u32) -> impl FnMut() -> f64 {
+    let mut state = seed;
+    move || {
+        state = state.wrapping_add(0x6d2b79f5);
+        let mut value = state;
+        value = (value ^ (value >> 15)).wrapping_mul(value | 1);
+        value ^= value.wrapping_add((value ^ (value >> 7)).wrapping_mul(value | 61));
+        (value ^ (value >> 14)) as f64 / 4_294_967_296.0
+    }
+}
+
+fn dct_basis((u, v): (usize, usize)) -> Basis {
+    let alpha_u = if u == 0 {
+        std::f64::consts::FRAC_1_SQRT_2
+    } else {
+        1.0
+    };
+    let alpha_v = if v == 0 {
+        std::f64::consts::FRAC_1_SQRT_2
+    } else {
+        1.0
+    };
+    let scale = 0.25 * alpha_u * alpha_v;
+    let mut basis = [0.0; ROBUST_BLOCK_SIZE * ROBUST_BLOCK_SIZE];
+    for y in 0..ROBUST_BLOCK_SIZE {
+        for x in 0..ROBUST_BLOCK_SIZE {
+            basis[y * ROBUST_BLOCK_SIZE + x] = scale
+                * (((2 * x + 1) * u) as f64 * std::f64::consts::PI / 16.0).cos()
+                * (((2 * y + 1) * v) as f64 * std::f64::consts::PI / 16.0).cos();
+        }
+    }
+    basis
+}
+
+/// Two DCT coefficients of an 8x8 block whose order carries one bit.
+pub struct CoefficientPair {
+    basis_a: Basis,
+    basis_b: Basis,
+    margin: f64,
+    max_delta: f64,
+}
+
+impl CoefficientPair {
+    pub fn new(
+        coeff_a: (usize, usize),
+        coeff_b: (usize, usize),
+        margin: f64,
+        max_delta: f64,
+    ) -> Self {
+        Self {
+            basis_a: dct_basis(coeff_a),
+            basis_b: dct_basis(coeff_b),
+            margin,
+            max_delta,
+        }
+    }
+
+    fn coefficient(image: &image::RgbaImage, left: usize, top: usize, basis: &Basis) -> f64 {
+        let mut coefficient = 0.0;
+        for y in 0..ROBUST_BLOCK_SIZE {
+            for x in 0..ROBUST_BLOCK_SIZE {
+                let pixel = image.get_pixel((left + x) as u32, (top + y) as u32);
+                let luma =
+                    pixel[0] as f64 * 0.299 + pixel[1] as f64 * 0.587 + pixel[2] as f64 * 0.114
+                        - 128.0;
+                coefficient += luma * basis[y * ROBUST_BLOCK_SIZE + x];
+            }
+        }
+        coefficient
+    }
+
+    pub fn write_bit(&self, image: &mut image::RgbaImage, left: usize, top: usize, bit: u8) {
+        let diff = Self::coefficient(image, left, top, &self.basis_a)
+            - Self::coefficient(image, left, top, &self.basis_b);
+        let target_diff = if bit == 1 { self.margin } else { -self.margin };
+        let delta = (target_diff - diff).clamp(-self.max_delta, self.max_delta);
+        if delta.abs() < 0.01 {
+            return;
+        }
+
+        let half_delta = delta / 2.0;
+        for y in 0..ROBUST_BLOCK_SIZE {
+            for x in 0..ROBUST_BLOCK_SIZE {
+                let basis_index = y * ROBUST_BLOCK_SIZE + x;
+                let adjustment =
+                    half_delta * self.basis_a[basis_index] - half_delta * self.basis_b[basis_index];
+                let pixel = image.get_pixel_mut((left + x) as u32, (top + y) as u32);
+                for channel in 0..3 {
+                    // Uint8ClampedArray stores round half to even.
+                    pixel[channel] = (pixel[channel] as f64 + adjustment)
+                        .clamp(0.0, 255.0)
+                        .round_ties_even() as u8;
+                }
+            }
+        }
+    }
+
+    /// Positive for a 1 bit.
+    pub fn difference(&self, image: &image::RgbaImage, left: usize, top: usize) -> f64 {
+        Self::coefficient(image, left, top, &self.basis_a)
+            - Self::coefficient(image, left, top, &self.basis_b)
+    }
+
+    pub fn read_bit(&self, image: &image::RgbaImage, left: usize, top: usize) -> u8 {
+        u8::from(self.difference(image, left, top) > 0.0)
+    }
+}
+
+/// The coefficient pair of the DCT watermark, shared with `writeRobustHiddenWatermark`.
+pub fn robust_pair() -> CoefficientPair {
+    CoefficientPair::new(
+        ROBUST_COEFF_A,
+        ROBUST_COEFF_B,
+        ROBUST_MARGIN,
+        ROBUST_MAX_COEFF_DELTA,
+    )
+}
+
+pub struct BlockLayout {
+    blocks_x: usize,
+    block_count: usize,
+    order: Vec<usize>,
+    pair: CoefficientPair,
+}
+
+impl BlockLayout {
+    pub fn new(width: u32, height: u32) -> Self {
+        let blocks_x = width as usize / ROBUST_BLOCK_SIZE;
+        let blocks_y = height as usize / ROBUST_BLOCK_SIZE;
+        let block_count = blocks_x * blocks_y;
+
+        let mut order = (0..block_count).collect::<Vec<_>>();
+        let seed = fnv1a(format!("xshot-watermark-v2:{}:{}", blocks_x, blocks_y).as_bytes());
+        let mut random = prng(seed);
+        for index in (1..block_count).rev() {
+            let swap_index = (random() * (index + 1) as f64).floor() as usize;
+            order.swap(index, swap_index);
+        }
+
+        Self {
+            blocks_x,
+            block_count,
+            order,
+            pair: robust_pair(),
+        }
+    }
+
+    fn top_left(&self, block_index: usize) -> (usize, usize) {
+        (
+            (block_index % self.blocks_x) * ROBUST_BLOCK_SIZE,
+            (block_index / self.blocks_x) * ROBUST_BLOCK_SIZE,
+        )
+    }
+
+    fn write_bit(&self, image: &mut image::RgbaImage, block_index: usize, bit: u8) {
+        let (left, top) = self.top_left(block_index);
+        self.pair.write_bit(image, left, top, bit);
+    }
+
+    fn encode_bits(&self, image: &mut image::RgbaImage, start: usize, bits: &[u8], repeat: usize) {
+        for (bit_index, bit) in bits.iter().enumerate() {
+            for repeat_index in 0..repeat {
+                let block = self.order[start + bit_index * repeat + repeat_index];
+                self.write_bit(image, block, *bit);
+            }
+        }
+    }
+
+    /// Blocks that `read_vote` reports as missing abstain.
+    fn decode_bits(
+        &self,
+        read_vote: &impl Fn(usize, usize) -> Option<f64>,
+        start: usize,
+        bit_count: usize,
+        repeat: usize,
+    ) -> Votes {
+        let mut agreeing = 0;
+        let mut total = 0;
+        let mut sums = Vec::with_capacity(bit_count);
+        let bits = (0..bit_count)
+            .map(|bit_index| {
+                let (mut sum, mut one_votes, mut present) = (0.0, 0, 0);
+                for repeat_index in 0..repeat {
+                    let (left, top) =
+                        self.top_left(self.order[start + bit_index * repeat + repeat_index]);
+                    if let Some(vote) = read_vote(left, top) {
+                        sum += vote;
+                        one_votes += usize::from(vote > 0.0);
+                        present += 1;
+                    }
+                }
+                sums.push(sum);
+                let bit = u8::from(sum > 0.0);
+                agreeing += if bit == 1 {
+                    one_votes
+                } else {
+                    present - one_votes
+                };
+                total += present;
+                bit
+            })
+            .collect::<Vec<_>>();
+        Votes {
+            bytes: bits_to_bytes(&bits),
+            sums,
+            agreeing,
+            total,
+        }
+    }
+
+    fn max_header_repeat(&self) -> usize {
+        ((self.block_count as f64 * ROBUST_HEADER_BLOCK_RATIO / ROBUST_HEADER_BITS as f64).floor()
+            as usize)
+            .clamp(1, ROBUST_MAX_HEADER_REPEATS)
+    }
+
+    fn body_repeat(&self, header_repeat: usize, body_bit_count: usize) -> usize {
+        ((self
+            .block_count
+            .saturating_sub(ROBUST_HEADER_BITS * header_repeat))
+            / body_bit_count)
+            .min(ROBUST_MAX_BODY_REPEATS)
+    }
+}
+
+fn write_robust(
+    image: &mut image::RgbaImage,
+    text: &str,
+    created_at_ms: f64,
+    signer: Option<(&SigningKeys, SignatureScheme)>,
+) -> Result<bool, String> {
+    let body = robust_body(text, created_at_ms, signer)?;
+    Ok(write_robust_body(image, &body))
+}
+
+fn robust_body(
+    text: &str,
+    created_at_ms: f64,
+    signer: Option<(&SigningKeys, SignatureScheme)>,
+) -> Result<Vec<u8>, String> {
+    let text_bytes = text.as_bytes();
+    let mut body = vec![ROBUST_WATERMARK_VERSION];
+    body.extend_from_slice(&((created_at_ms / 1000.0).floor() as u32).to_be_bytes());
+    body.extend_from_slice(&(text_bytes.len() as u16).to_be_bytes());
+    body.extend_from_slice(text_bytes);
+    if let Some((keys, scheme)) = signer {
+        watermark_signing::append_signature(keys, scheme, &mut body)?;
+    }
+    Ok(body)
+}
+
+/// Writes the header with the body's length and checksum, then the body. Returns whether both
+/// fit in the image.
+fn write_robust_body(image: &mut image::RgbaImage, body: &[u8]) -> bool {
+    if body.len() > ROBUST_MAX_BODY_BYTES {
+        return false;
+    }
+    let mut header = ROBUST_WATERMARK_MAGIC.to_vec();
+    header.extend_from_slice(&(body.len() as u16).to_be_bytes());
+    header.extend_from_slice(&fnv1a(body).to_be_bytes());
+
+    let layout = BlockLayout::new(image.width(), image.height());
+    if layout.block_count <= ROBUST_HEADER_BITS {
+        return false;
+    }
+    let header_bits = bytes_to_bits(&header);
+    let body_bits = bytes_to_bits(body);
+
+    let fits = |repeat: usize| repeat * ROBUST_HEADER_BITS + body_bits.len() <= layout.block_count;
+    let mut header_repeat = layout.max_header_repeat();
+    while header_repeat > 1 && !fits(header_repeat) {
+        header_repeat -= 1;
+    }
+    if !fits(header_repeat) {
+        return false;
+    }
+
+    let header_block_count = header_bits.len() * header_repeat;
+    let body_repeat = layout.body_repeat(header_repeat, body_bits.len());
+    if body_repeat < 1 {
+        return false;
+    }
+    layout.encode_bits(image, 0, &header_bits, header_repeat);
+    layout.encode_bits(image, header_block_count, &body_bits, body_repeat);
+    true
+}
+
+fn parse_robust_body(body: &[u8], verifier: &Verifier) -> Option<DecodedHiddenWatermark> {
+    if body.len() < 7 || body[0] != ROBUST_WATERMARK_VERSION {
+        return None;
+    }
+    let created_at_seconds = read_u32(body, 1);
+    let text_length = read_u16(body, 5);
+    if text_length == 0 || 7 + text_length > body.len() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&body[7..7 + text_length]);
+    if text.trim().is_empty() {
+        return None;
+    }
+    Some(DecodedHiddenWatermark {
+        text: truncate_text(&text),
+        created_at: Some(iso_timestamp(created_at_seconds as f64 * 1000.0)),
+        version: ROBUST_WATERMARK_VERSION as u32,
+        signature: watermark_signing::verify(body, 7 + text_length, verifier),
+    })
+}
+
+fn flip_bit(bytes: &mut [u8], bit: usize) {
+    bytes[bit >> 3] ^= 0x80 >> (bit % 8);
+}
+
+/// Indices of the `count` bits with the weakest votes.
+pub fn least_reliable(sums: &[f64], count: usize) -> Vec<usize> {
+    let mut indices = (0..sums.len()).collect::<Vec<_>>();
+    indices.sort_by(|a, b| sums[*a].abs().total_cmp(&sums[*b].abs()));
+    indices.truncate(count);
+    indices
+}
+
+/// Tries `bytes` with every combination of `bits` flipped until `accept` takes one.
+pub fn with_flips<T>(
+    bytes: &[u8],
+    bits: &[usize],
+    mut accept: impl FnMut(&[u8]) -> Option<T>,
+) -> Option<T> {
+    let mut candidate = bytes.to_vec();
+    (0u32..1 << bits.len()).find_map(|mask| {
+        candidate.copy_from_slice(bytes);
+        for (index, bit) in bits.iter().enumerate() {
+            if mask >> index & 1 == 1 {
+                flip_bit(&mut candidate, *bit);
+            }
+        }
+        accept(&candidate)
+    })
+}
+
+fn read_robust(image: &image::RgbaImage, verifier: &Verifier) -> Option<Detection> {
+    let layout = BlockLayout::new(image.width(), image.height());
+    // One plain vote per block, so ties and majorities match the webview decoder.
+    read_robust_blocks(
+        &layout,
+        |left, top| {
+            Some(if layout.pair.read_bit(image, left, top) == 1 {
+                1.0
+            } else {
+                -1.0
+            })
+        },
+        false,
+        verifier,
+    )
+}
+
+/// Decodes the DCT watermark laid out as `layout` from per-block votes. `read_vote` gets the
+/// block's top-left corner in the marked image and returns a value that is positive for a 1 bit,
+/// or `None` where that block is missing. With `repair`, a few bit errors in the magic are
+/// tolerated and the least reliable bits are flipped until the checksum matches; only reads
+/// already aligned some other way should ask for that.
+pub fn read_robust_blocks(
+    layout: &BlockLayout,
+    read_vote: impl Fn(usize, usize) -> Option<f64>,
+    repair: bool,
+    verifier: &Verifier,
+) -> Option<Detection> {
+    if layout.block_count <= ROBUST_HEADER_BITS {
+        return None;
+    }
+
+    let magic_len = ROBUST_WATERMARK_MAGIC.len();
+    (1..=layout.max_header_repeat())
+        .rev()
+        .find_map(|header_repeat| {
+            let header_votes = layout.decode_bits(&read_vote, 0, ROBUST_HEADER_BITS, header_repeat);
+            let header = &header_votes.bytes;
+            let magic_errors = bytes_to_bits(&header[..magic_len])
+                .iter()
+                .zip(bytes_to_bits(ROBUST_WATERMARK_MAGIC))
+                .filter(|(read, expected)| **read != *expected)
+                .count();
+            if magic_errors > if repair { REPAIR_MAGIC_ERRORS } else { 0 } {
+                return None;
+            }
+
+            let length_bits = magic_len * 8..(magic_len + 2) * 8;
+            let mut body_lengths = vec![read_u16(header, magic_len)];
+            if repair {
+                for bit in
+                    least_reliable(&header_votes.sums[length_bits.clone()], REPAIR_LENGTH_FLIPS)
+                {
+                    let mut flipped = header.clone();
+                    flip_bit(&mut flipped, length_bits.start + bit);
+                    body_lengths.push(read_u16(&flipped, magic_len));
+                }
+            }
+
+            body_lengths.into_iter().find_map(|body_length| {
+                if body_length == 0 || body_length > ROBUST_MAX_BODY_BYTES {
+                    return None;
+                }
+                let body_bit_count = body_length * 8;
+                let body_repeat = layout.body_repeat(header_repeat, body_bit_count);
+                if body_repeat < 1 {
+                    return None;
+                }
+                let body_votes = layout.decode_bits(
+                    &read_vote,
+                    ROBUST_HEADER_BITS * header_repeat,
+                    body_bit_count,
+                    body_repeat,
+                );
+
+                // Checksum and body, so repairs can flip bits in either.
+                let checksum_start = magic_len + 2;
+                let mut packet = header[checksum_start..].to_vec();
+                packet.extend_from_slice(&body_votes.bytes);
+                let mut sums = header_votes.sums[checksum_start * 8..].to_vec();
+                sums.extend_from_slice(&body_votes.sums);
+                let flips = if repair {
+                    least_reliable(&sums, REPAIR_BITS)
+                } else {
+                    Vec::new()
+                };
+                let watermark = with_flips(&packet, &flips, |packet| {
+                    let body = &packet[4..];
+                    if fnv1a(body) != read_u32(packet, 0) {
+                        return None;
+                    }
+                    parse_robust_body(body, verifier)
+                })?;
+                let vote_ratio = (header_votes.agreeing + body_votes.agreeing) as f64
+                    / (header_votes.total + body_votes.total) as f64;
+                Some(Detection {
+                    watermark,
+                    method: DetectionMethod::Dct,
+                    vote_ratio,
+                    confidence: (vote_ratio * 2.0 - 1.0).clamp(0.0, 1.0),
+                })
+            })
+        })
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    x
+}
+
+/// Visits every pixel once in a width/height-dependent stride, as `createLegacyPixelSequence`.
+struct LegacySequence {
+    start: u64,
+    step: u64,
+    total: u64,
+}
+
+impl LegacySequence {
+    fn new(width: u32, height: u32) -> Self {
+        let total = (width as u64 * height as u64).max(1);
+        let seed = (width.wrapping_mul(73_856_093) ^ height.wrapping_mul(19_349_663) ^ 0x9e3779b9)
+            .max(1) as u64;
+        let start = seed % total;
+        let mut step = (seed % (total - 1).max(1)) | 1;
+        while gcd(step, total) != 1 {
+            step += 2;
+            if step >= total {
+                step = (step % total) | 1;
+            }
+        }
+        Self { start, step, total }
+    }
+
+    fn blue_channel(&self, image_width: u32, bit_index: usize) -> (u32, u32) {
+        let pixel = (self.start + bit_index as u64 * self.step) % self.total;
+        (
+            (pixel % image_width as u64) as u32,
+            (pixel / image_width as u64) as u32,
+        )
+    }
+}
+
+fn write_legacy(image: &mut image::RgbaImage, text: &str, created_at_ms: f64) -> bool {
+    let Ok(payload) = serde_json::to_vec(&LegacyEnvelope {
+        version: Some(LEGACY_WATERMARK_VERSION.into()),
+        text: Some(text.into()),
+        created_at: Some(iso_timestamp(created_at_ms).into()),
+    }) else {
+        return false;
+    };
+    if payload.len() > LEGACY_MAX_PAYLOAD_BYTES {
+        return false;
+    }
+    let mut bytes = LEGACY_WATERMARK_MAGIC.to_vec();
+    bytes.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&fnv1a(&payload).to_be_bytes());
+    bytes.extend_from_slice(&payload);
+
+    let sequence = LegacySequence::new(image.width(), image.height());
+    if bytes.len() as u64 * 8 > sequence.total {
+        return false;
+    }
+    for (bit_index, bit) in bytes_to_bits(&bytes).into_iter().enumerate() {
+        let (x, y) = sequence.blue_channel(image.width(), bit_index);
+        let pixel = image.get_pixel_mut(x, y);
+        pixel[2] = (pixel[2] & 0xfe) | bit;
+    }
+    true
+}
+
+fn read_legacy_bytes(
+    image: &image::RgbaImage,
+    sequence: &LegacySequence,
+    byte_count: usize,
+) -> Option<Vec<u8>> {
+    if byte_count as u64 * 8 > sequence.total {
+        return None;
+    }
+    let bits = (0..byte_count * 8)
+        .map(|bit_index| {
+            let (x, y) = sequence.blue_channel(image.width(), bit_index);
+            image.get_pixel(x, y)[2] & 1
+        })
+        .collect::<Vec<_>>();
+    Some(bits_to_bytes(&bits))
+}
+
+fn read_legacy(image: &image::RgbaImage) -> Option<DecodedHiddenWatermark> {
+    if image.width() == 0 || image.height() == 0 {
+        return None;
+    }
+    let sequence = LegacySequence::new(image.width(), image.height());
+    let magic_len = LEGACY_WATERMARK_MAGIC.len();
+    let header_size = magic_len + 8;
+    let header = read_legacy_bytes(image, &sequence, header_size)?;
+    if &header[..magic_len] != LEGACY_WATERMARK_MAGIC {
+        return None;
+    }
+
+    let payload_length = read_u32(&header, magic_len) as usize;
+    let checksum = read_u32(&header, magic_len + 4);
+    if payload_length == 0 || payload_length > LEGACY_MAX_PAYLOAD_BYTES {
+        return None;
+    }
+    let all_bytes = read_legacy_bytes(image, &sequence, header_size + payload_length)?;
+    let payload = &all_bytes[header_size..];
+    if fnv1a(payload) != checksum {
+        return None;
+    }
+
+    let envelope = serde_json::from_slice::<LegacyEnvelope>(payload).ok()?;
+    let text = envelope.text.as_ref()?.as_str()?;
+    if text.trim().is_empty() {
+        return None;
+    }
+    Some(DecodedHiddenWatermark {
+        text: truncate_text(text),
+        created_at: envelope
+            .created_at
+            .as_ref()
+            .and_then(|value| value.as_str())
+            .map(str::to_string),
+        version: envelope
+            .version
+            .as_ref()
+            .and_then(|value| value.as_f64())
+            .map(|version| version as u32)
+            .unwrap_or(LEGACY_WATERMARK_VERSION),
+        signature: None,
+    })
+}
+
+/// Writes `text` as both the DCT watermark and the legacy LSB watermark, like
+/// `writeHiddenWatermark`, plus the sync tiles that let a rescaled or cropped copy be decoded.
+/// Returns whether either watermark fit in the image. With a signer, only the DCT watermark is
+/// signed and it must fit.
+pub fn embed(
+    image: &mut image::RgbaImage,
+    text: &str,
+    created_at_ms: f64,
+    signer: Option<(&SigningKeys, SignatureScheme)>,
+) -> Result<bool, String> {
+    write_hidden(image, text, created_at_ms, signer, true)
+}
+
+/// Without sync tiles and a signer, the output is byte-for-byte what `writeHiddenWatermark`
+/// writes for the same text and timestamp.
+fn write_hidden(
+    image: &mut image::RgbaImage,
+    text: &str,
+    created_at_ms: f64,
+    signer: Option<(&SigningKeys, SignatureScheme)>,
+    with_sync: bool,
+) -> Result<bool, String> {
+    let text = normalize_text(text);
+    if text.is_empty() {
+        return Ok(false);
+    }
+    let robust_written = write_robust(image, &text, created_at_ms, signer)?;
+    if signer.is_some() && !robust_written {
+        return Err("Image is too small to hold a signed watermark".to_string());
+    }
+    if robust_written && with_sync {
+        watermark_sync::write_sync(image);
+    }
+    let legacy_written = write_legacy(image, &text, created_at_ms);
+    Ok(robust_written || legacy_written)
+}
+
+/// Tries the DCT watermark first and falls back to the legacy LSB one, like
+/// `decodeHiddenWatermarkFromCanvas`. With `deep`, and neither found in place, also searches for
+/// the sync tiles in case the image was rescaled, cropped or screenshotted again. That search is
+/// much slower than reading in place, so callers opt into it.
+pub fn detect_detailed(
+    image: &image::RgbaImage,
+    verifier: &Verifier,
+    deep: bool,
+) -> Option<Detection> {
+    read_robust(image, verifier)
+        .or_else(|| {
+            read_legacy(image).map(|watermark| Detection {
+                watermark,
+                method: DetectionMethod::Lsb,
+                vote_ratio: 1.0,
+                confidence: 1.0,
+            })
+        })
+        .or_else(|| {
+            if deep {
+                watermark_sync::resync(image, verifier)
+            } else {
+                None
+            }
+        })
+}
+
+pub fn detect(
+    image: &image::RgbaImage,
+    verifier: &Verifier,
+    deep: bool,
+) -> Option<DecodedHiddenWatermark> {
+    detect_detailed(image, verifier, deep).map(|detection| detection.watermark)
+}
+
+#[tauri::command]
+pub async fn embed_hidden_watermark(
+    app: tauri::AppHandle,
+    blob_data: Vec<u8>,
+    text: String,
+    signature: Option<SignatureScheme>,
+) -> Result<tauri::ipc::Response, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut image = image::load_from_memory(&blob_data)
+            .map_err(|e| format!("Failed to decode image: {}", e))?
+            .to_rgba8();
+        if normalize_text(&text).is_empty() {
+            return Err("Watermark text is empty".to_string());
+        }
+        let keys = signature
+            .map(|_| watermark_signing::signing_keys(&app))
+            .transpose()?;
+        let signer = keys.as_ref().zip(signature);
+        if !embed(&mut image, &text, crate::unix_epoch_ms(), signer)? {
+            return Err("Image is too small to hold the watermark".to_string());
+        }
+        crate::encode_rgba_png_fast(&image).map(tauri::ipc::Response::new)
+    })
+    .await
+    .map_err(|e| format!("Watermark task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn detect_hidden_watermark(
+    app: tauri::AppHandle,
+    blob_data: Vec<u8>,
+    deep: Option<bool>,
+) -> Result<Option<DecodedHiddenWatermark>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let image = image::load_from_memory(&blob_data)
+            .map_err(|e| format!("Failed to decode image: {}", e))?
+            .to_rgba8();
+        Ok(detect(
+            &image,
+            &watermark_signing::verifier(&app),
+            deep.unwrap_or(false),
+        ))
+    })
+    .await
+    .map_err(|e| format!("Watermark task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watermark_signing::SignatureStatus;
+
+    // Written by `writeHiddenWatermark` in `src/logic/watermark.ts` with `Date.now()` fixed at
+    // FIXTURE_CREATED_AT_MS. The DCT pair is large enough for both watermarks, the LSB pair only
+    // for the legacy one.
+    const FIXTURE_TEXT: &str = "xshot fixture ✓ 截图";
+    const FIXTURE_CREATED_AT_MS: f64 = 1_767_323_045_678.0;
+    const TS_DCT_INPUT: &[u8] = include_bytes!("../tests/fixtures/watermark/ts-dct-input.png");
+    const TS_DCT_MARKED: &[u8] = include_bytes!("../tests/fixtures/watermark/ts-dct-marked.png");
+    const TS_LSB_INPUT: &[u8] = include_bytes!("../tests/fixtures/watermark/ts-lsb-input.png");
+    const TS_LSB_MARKED: &[u8] = include_bytes!("../tests/fixtures/watermark/ts-lsb-marked.png");
+
+    fn load(bytes: &[u8]) -> image::RgbaImage {
+        image::load_from_memory(bytes).unwrap().to_rgba8()
+    }
+
+    fn verifier() -> Verifier {
+        Verifier::new(None, &[]).unwrap()
+    }
+
+    #[test]
+    fn detects_ts_dct_watermark() {
+        let detection = detect_detailed(&load(TS_DCT_MARKED), &verifier(), false).unwrap();
+        assert_eq!(detection.method, DetectionMethod::Dct);
+        assert_eq!(detection.watermark.text, FIXTURE_TEXT);
+        assert_eq!(detection.watermark.version, ROBUST_WATERMARK_VERSION as u32);
+        // The DCT body only keeps whole seconds.
+        assert_eq!(
+            detection.watermark.created_at.as_deref(),
+            Some("2026-01-02T03:04:05.000Z")
+        );
+        assert_eq!(detection.watermark.signature, None);
+    }
+
+    #[test]
+    fn detects_ts_lsb_watermark() {
+        let detection = detect_detailed(&load(TS_LSB_MARKED), &verifier(), false).unwrap();
+        assert_eq!(detection.method, DetectionMethod::Lsb);
+        assert_eq!(detection.watermark.text, FIXTURE_TEXT);
+        assert_eq!(detection.watermark.version, LEGACY_WATERMARK_VERSION);
+        assert_eq!(
+            detection.watermark.created_at.as_deref(),
+            Some("2026-01-02T03:04:05.678Z")
+        );
+    }
+
+    #[test]
+    fn matches_ts_encoder_byte_for_byte() {
+        for (input, marked) in [(TS_DCT_INPUT, TS_DCT_MARKED), (TS_LSB_INPUT, TS_LSB_MARKED)] {
+            let mut image = load(input);
+            assert!(
+                write_hidden(&mut image, FIXTURE_TEXT, FIXTURE_CREATED_AT_MS, None, false).unwrap()
+            );
+            assert_eq!(image.as_raw(), load(marked).as_raw());
+        }
+    }
+
+    #[test]
+    fn embed_adds_sync_tiles_only_to_dct_images() {
+        let mut dct = load(TS_DCT_INPUT);
+        assert!(embed(&mut dct, FIXTURE_TEXT, FIXTURE_CREATED_AT_MS, None).unwrap());
+        assert_ne!(dct.as_raw(), load(TS_DCT_MARKED).as_raw());
+        assert_eq!(detect(&dct, &verifier(), false).unwrap().text, FIXTURE_TEXT);
+
+        let mut lsb = load(TS_LSB_INPUT);
+        assert!(embed(&mut lsb, FIXTURE_TEXT, FIXTURE_CREATED_AT_MS, None).unwrap());
+        assert_eq!(lsb.as_raw(), load(TS_LSB_MARKED).as_raw());
+    }
+
+    #[test]
+    fn tampered_signed_bodies_fail_verification() {
+        let path = std::env::temp_dir().join(format!(
+            "xshot-watermark-signing-{}.json",
+            std::process::id()
+        ));
+        let file = serde_json::json!({
+            "deviceId": "0011223344556677",
+            "ed25519SecretKey": "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=",
+            "hmacSecret": "org secret",
+        });
+        std::fs::write(&path, file.to_string()).unwrap();
+        let keys = watermark_signing::signing_keys_at(&path);
+        let _ = std::fs::remove_file(&path);
+        let keys = keys.unwrap();
+        let verifier = Verifier::new(Some("org secret"), &[]).unwrap();
+
+        for (scheme, intact) in [
+            (SignatureScheme::Hmac, SignatureStatus::Valid),
+            (SignatureScheme::Ed25519, SignatureStatus::UntrustedKey),
+        ] {
+            let body =
+                robust_body(FIXTURE_TEXT, FIXTURE_CREATED_AT_MS, Some((&keys, scheme))).unwrap();
+            // The timestamp's last byte, then the text's first. The header checksum is recomputed
+            // over the tampered body, so only the signature can tell.
+            for (tampered, expected) in [
+                (None, intact),
+                (Some(4), SignatureStatus::Invalid),
+                (Some(7), SignatureStatus::Invalid),
+            ] {
+                let mut body = body.clone();
+                if let Some(index) = tampered {
+                    body[index] ^= 1;
+                }
+                let mut image =
+                    image::RgbaImage::from_pixel(480, 320, image::Rgba([200, 200, 200, 255]));
+                assert!(write_robust_body(&mut image, &body));
+                let signature = detect(&image, &verifier, false).unwrap().signature;
+                assert_eq!(
+                    signature.map(|check| check.status),
+                    Some(expected),
+                    "{:?} {:?}",
+                    scheme,
+                    tampered
+                );
+            }
+        }
+    }
+}
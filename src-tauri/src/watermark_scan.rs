@@ -0,0 +1,506 @@
+use crate::watermark::{self, DetectionMethod};
+use crate::watermark_signing::{SignatureScheme, SignatureStatus, Verifier};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
+
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "webp"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScanStatus {
+    Found,
+    NotFound,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanEntry {
+    path: String,
+    status: ScanStatus,
+    text: Option<String>,
+    created_at: Option<String>,
+    version: Option<u32>,
+    method: Option<DetectionMethod>,
+    confidence: Option<f64>,
+    vote_ratio: Option<f64>,
+    signature_scheme: Option<SignatureScheme>,
+    signature_status: Option<SignatureStatus>,
+    device_id: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanReport {
+    directory: String,
+    total: usize,
+    scanned: usize,
+    found: usize,
+    failed: usize,
+    cancelled: bool,
+    entries: Vec<ScanEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub found: usize,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    /// Picks the format from a report file extension, defaulting to JSON.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => ReportFormat::Csv,
+            _ => ReportFormat::Json,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct WatermarkScanState {
+    running: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+fn is_scannable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|candidate| extension.eq_ignore_ascii_case(candidate))
+        })
+}
+
+fn collect_images(directory: &Path, recursive: bool) -> Result<Vec<PathBuf>, String> {
+    let mut images = Vec::new();
+    let mut pending = vec![directory.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = std::fs::read_dir(&current)
+            .map_err(|e| format!("Failed to read directory {}: {}", current.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Symlinked directories are skipped so a link cycle cannot keep the walk going.
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if recursive {
+                    pending.push(path);
+                }
+            } else if is_scannable(&path) {
+                images.push(path);
+            }
+        }
+    }
+    images.sort();
+    Ok(images)
+}
+
+fn scan_file(path: &Path, verifier: &Verifier, deep: bool) -> ScanEntry {
+    let mut entry = ScanEntry {
+        path: path.to_string_lossy().into_owned(),
+        status: ScanStatus::NotFound,
+        text: None,
+        created_at: None,
+        version: None,
+        method: None,
+        confidence: None,
+        vote_ratio: None,
+        signature_scheme: None,
+        signature_status: None,
+        device_id: None,
+        error: None,
+    };
+    match image::open(path) {
+        Ok(image) => {
+            if let Some(detection) = watermark::detect_detailed(&image.to_rgba8(), verifier, deep) {
+                entry.status = ScanStatus::Found;
+                entry.text = Some(detection.watermark.text);
+                entry.created_at = detection.watermark.created_at;
+                entry.version = Some(detection.watermark.version);
+                entry.method = Some(detection.method);
+                entry.confidence = Some(detection.confidence);
+                entry.vote_ratio = Some(detection.vote_ratio);
+                if let Some(signature) = detection.watermark.signature {
+                    entry.signature_scheme = Some(signature.scheme);
+                    entry.signature_status = Some(signature.status);
+                    entry.device_id = Some(signature.device_id);
+                }
+            }
+        }
+        Err(error) => {
+            entry.status = ScanStatus::Error;
+            entry.error = Some(format!("Failed to decode image: {}", error));
+        }
+    }
+    entry
+}
+
+/// Runs both hidden watermark decoders over every PNG, JPEG and WebP under `directory`, and with
+/// `deep` also the sync search for rescaled or cropped copies. Stops early, with `cancelled` set,
+/// once `cancelled` flips.
+pub fn scan_directory(
+    directory: &Path,
+    recursive: bool,
+    deep: bool,
+    verifier: &Verifier,
+    cancelled: &AtomicBool,
+    mut on_progress: impl FnMut(&ScanProgress),
+) -> Result<ScanReport, String> {
+    let images = collect_images(directory, recursive)?;
+    let mut report = ScanReport {
+        directory: directory.to_string_lossy().into_owned(),
+        total: images.len(),
+        scanned: 0,
+        found: 0,
+        failed: 0,
+        cancelled: false,
+        entries: Vec::with_capacity(images.len()),
+    };
+
+    for path in images {
+        if cancelled.load(Ordering::SeqCst) {
+            report.cancelled = true;
+            break;
+        }
+        let entry = scan_file(&path, verifier, deep);
+        report.scanned += 1;
+        match entry.status {
+            ScanStatus::Found => report.found += 1,
+            ScanStatus::Error => report.failed += 1,
+            ScanStatus::NotFound => {}
+        }
+        on_progress(&ScanProgress {
+            processed: report.scanned,
+            total: report.total,
+            found: report.found,
+            path: entry.path.clone(),
+        });
+        report.entries.push(entry);
+    }
+    Ok(report)
+}
+
+/// Quotes a field when needed. Watermark text and paths come from whoever made the image, so a
+/// field a spreadsheet would run as a formula gets a leading `'`.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn to_csv(report: &ScanReport) -> String {
+    let mut csv =
+        String::from("path,status,text,createdAt,version,method,confidence,voteRatio,signatureScheme,signatureStatus,deviceId,error\n");
+    for entry in &report.entries {
+        let status = match entry.status {
+            ScanStatus::Found => "found",
+            ScanStatus::NotFound => "notFound",
+            ScanStatus::Error => "error",
+        };
+        let method = entry.method.map(|method| match method {
+            DetectionMethod::Dct => "dct",
+            DetectionMethod::Lsb => "lsb",
+        });
+        let signature_scheme = entry.signature_scheme.map(|scheme| match scheme {
+            SignatureScheme::Hmac => "hmac",
+            SignatureScheme::Ed25519 => "ed25519",
+        });
+        let signature_status = entry.signature_status.map(|status| match status {
+            SignatureStatus::Valid => "valid",
+            SignatureStatus::UntrustedKey => "untrustedKey",
+            SignatureStatus::Unverifiable => "unverifiable",
+            SignatureStatus::Invalid => "invalid",
+        });
+        let fields = [
+            entry.path.clone(),
+            status.to_string(),
+            entry.text.clone().unwrap_or_default(),
+            entry.created_at.clone().unwrap_or_default(),
+            entry.version.map(|v| v.to_string()).unwrap_or_default(),
+            method.unwrap_or_default().to_string(),
+            entry
+                .confidence
+                .map(|v| format!("{:.3}", v))
+                .unwrap_or_default(),
+            entry
+                .vote_ratio
+                .map(|v| format!("{:.3}", v))
+                .unwrap_or_default(),
+            signature_scheme.unwrap_or_default().to_string(),
+            signature_status.unwrap_or_default().to_string(),
+            entry.device_id.clone().unwrap_or_default(),
+            entry.error.clone().unwrap_or_default(),
+        ];
+        let line = fields
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str(&line);
+        csv.push('\n');
+    }
+    csv
+}
+
+pub fn render_report(report: &ScanReport, format: ReportFormat) -> Result<String, String> {
+    match format {
+        ReportFormat::Json => serde_json::to_string_pretty(report)
+            .map_err(|e| format!("Failed to serialize scan report: {}", e)),
+        ReportFormat::Csv => Ok(to_csv(report)),
+    }
+}
+
+pub fn write_report(report: &ScanReport, path: &Path, format: ReportFormat) -> Result<(), String> {
+    std::fs::write(path, render_report(report, format)?)
+        .map_err(|e| format!("Failed to write scan report {}: {}", path.display(), e))
+}
+
+/// Scans a folder for hidden watermarks, emitting `watermark-scan-progress` after each image.
+/// When `report_path` is set the report is also written there, as CSV for a `.csv` path and
+/// JSON otherwise unless `report_format` says differently.
+#[tauri::command]
+pub async fn scan_hidden_watermarks(
+    app: AppHandle,
+    directory: String,
+    recursive: Option<bool>,
+    deep: Option<bool>,
+    report_path: Option<String>,
+    report_format: Option<ReportFormat>,
+) -> Result<ScanReport, String> {
+    let state = app.state::<WatermarkScanState>();
+    if state.running.swap(true, Ordering::SeqCst) {
+        return Err("A watermark scan is already running".to_string());
+    }
+    state.cancelled.store(false, Ordering::SeqCst);
+
+    let worker_app = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let state = worker_app.state::<WatermarkScanState>();
+        let report = scan_directory(
+            Path::new(&directory),
+            recursive.unwrap_or(true),
+            deep.unwrap_or(false),
+            &crate::watermark_signing::verifier(&worker_app),
+            &state.cancelled,
+            |progress| {
+                let _ = worker_app.emit("watermark-scan-progress", progress);
+            },
+        )?;
+        if let Some(report_path) = report_path {
+            let report_path = PathBuf::from(report_path);
+            let format = report_format.unwrap_or_else(|| ReportFormat::from_path(&report_path));
+            write_report(&report, &report_path, format)?;
+        }
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Watermark scan task failed: {}", e))
+    .and_then(|result| result);
+
+    state.running.store(false, Ordering::SeqCst);
+    if let Ok(report) = &result {
+        println!(
+            "[xshot][watermark-scan][rust] directory={} scanned={}/{} found={} failed={} cancelled={}",
+            report.directory,
+            report.scanned,
+            report.total,
+            report.found,
+            report.failed,
+            report.cancelled
+        );
+    }
+    result
+}
+
+#[tauri::command]
+pub fn cancel_watermark_scan(app: AppHandle) {
+    app.state::<WatermarkScanState>()
+        .cancelled
+        .store(true, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp dir, removed again when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("xshot-scan-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(path.join("nested")).unwrap();
+            Self(path)
+        }
+
+        fn image(&self, name: &str) {
+            image::RgbaImage::from_pixel(16, 16, image::Rgba([90, 120, 150, 255]))
+                .save(self.0.join(name))
+                .unwrap();
+        }
+
+        fn file(&self, name: &str, bytes: &[u8]) {
+            std::fs::write(self.0.join(name), bytes).unwrap();
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn entry(path: &str, text: Option<&str>) -> ScanEntry {
+        ScanEntry {
+            path: path.into(),
+            status: if text.is_some() {
+                ScanStatus::Found
+            } else {
+                ScanStatus::NotFound
+            },
+            text: text.map(str::to_string),
+            created_at: None,
+            version: text.map(|_| 2),
+            method: text.map(|_| DetectionMethod::Dct),
+            confidence: text.map(|_| 0.98765),
+            vote_ratio: None,
+            signature_scheme: None,
+            signature_status: None,
+            device_id: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_and_defused() {
+        assert_eq!(csv_field("plain text"), "plain text");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-2"), "'-2");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("\tcmd"), "'\tcmd");
+        assert_eq!(csv_field("a=b"), "a=b");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn csv_report_has_one_row_per_entry() {
+        let report = ScanReport {
+            directory: "/shots".into(),
+            total: 2,
+            scanned: 2,
+            found: 1,
+            failed: 0,
+            cancelled: false,
+            entries: vec![
+                entry("/shots/a.png", Some("=cmd|' /C calc'!A0")),
+                entry("/shots/b,c.png", None),
+            ],
+        };
+        let csv = render_report(&report, ReportFormat::Csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("path,status,text,"));
+        assert_eq!(lines[0].split(',').count(), 12);
+        assert_eq!(
+            lines[1],
+            "/shots/a.png,found,'=cmd|' /C calc'!A0,,2,dct,0.988,,,,,"
+        );
+        assert_eq!(lines[2], "\"/shots/b,c.png\",notFound,,,,,,,,,,");
+    }
+
+    #[test]
+    fn collects_images_by_extension() {
+        let dir = TempDir::new("collect");
+        dir.image("a.png");
+        dir.file("b.JPG", b"");
+        dir.file("notes.txt", b"");
+        dir.file("png", b"");
+        dir.file("nested/c.webp", b"");
+
+        let names = |recursive| {
+            collect_images(&dir.0, recursive)
+                .unwrap()
+                .into_iter()
+                .map(|path| path.strip_prefix(&dir.0).unwrap().to_path_buf())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(true),
+            [
+                PathBuf::from("a.png"),
+                PathBuf::from("b.JPG"),
+                Path::new("nested").join("c.webp"),
+            ]
+        );
+        assert_eq!(
+            names(false),
+            [PathBuf::from("a.png"), PathBuf::from("b.JPG")]
+        );
+        assert!(collect_images(&dir.0.join("missing"), true).is_err());
+    }
+
+    #[test]
+    fn scan_counts_results_and_stops_when_cancelled() {
+        let dir = TempDir::new("cancel");
+        dir.image("a.png");
+        dir.file("b.png", b"not an image");
+        dir.image("c.png");
+        let verifier = Verifier::default();
+
+        let report = scan_directory(
+            &dir.0,
+            true,
+            false,
+            &verifier,
+            &AtomicBool::new(false),
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!((report.total, report.scanned), (3, 3));
+        assert_eq!((report.found, report.failed), (0, 1));
+        assert!(!report.cancelled);
+        assert_eq!(report.entries[1].status, ScanStatus::Error);
+
+        let cancelled = AtomicBool::new(false);
+        let mut progress = Vec::new();
+        let report = scan_directory(&dir.0, true, false, &verifier, &cancelled, |update| {
+            progress.push(update.processed);
+            cancelled.store(true, Ordering::SeqCst);
+        })
+        .unwrap();
+        assert_eq!(progress, [1]);
+        assert_eq!((report.total, report.scanned), (3, 1));
+        assert_eq!(report.entries.len(), 1);
+        assert!(report.cancelled);
+    }
+}
@@ -0,0 +1,519 @@
+// Optional signature trailer for the DCT watermark body. The trailer follows the text, which the
+// webview decoder already ignores, so signed images still decode there:
+//
+//   u8 scheme (1 = HMAC-SHA256, 2 = Ed25519) | 8-byte device id | [32-byte public key] | signature
+//
+// The signature covers a domain tag plus every body byte before it, including the timestamp.
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier as _, VerifyingKey};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+const SIGNING_FILE: &str = "watermark-signing.json";
+// `identifier` in tauri.conf.json, which names the app data directory.
+const APP_IDENTIFIER: &str = "com.wangrongding.xshot";
+const SIGNATURE_DOMAIN: &[u8] = b"xshot-watermark-signature-v1";
+const DEVICE_ID_BYTES: usize = 8;
+const SCHEME_HMAC: u8 = 1;
+const SCHEME_ED25519: u8 = 2;
+const HMAC_BYTES: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureScheme {
+    Hmac,
+    Ed25519,
+}
+
+impl SignatureScheme {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "hmac" => Some(Self::Hmac),
+            "ed25519" => Some(Self::Ed25519),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureStatus {
+    /// Checked against the organization secret or a trusted public key.
+    Valid,
+    /// A correct Ed25519 signature, but from a key this device does not trust.
+    UntrustedKey,
+    /// An HMAC signature with no organization secret configured to check it.
+    Unverifiable,
+    Invalid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureCheck {
+    pub scheme: SignatureScheme,
+    pub status: SignatureStatus,
+    pub device_id: String,
+    pub public_key: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SigningFile {
+    device_id: String,
+    ed25519_secret_key: String,
+    #[serde(default)]
+    hmac_secret: Option<String>,
+    #[serde(default)]
+    trusted_public_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningInfo {
+    device_id: String,
+    public_key: String,
+    has_hmac_secret: bool,
+    trusted_public_keys: Vec<String>,
+}
+
+pub struct SigningKeys {
+    device_id: [u8; DEVICE_ID_BYTES],
+    signing_key: SigningKey,
+    hmac_secret: Option<Vec<u8>>,
+}
+
+/// What detection checks signatures against. The default verifier still recognizes
+/// well-formed Ed25519 signatures, it just trusts no key.
+#[derive(Default)]
+pub struct Verifier {
+    hmac_secret: Option<Vec<u8>>,
+    trusted_keys: Vec<[u8; 32]>,
+}
+
+impl Verifier {
+    pub fn new(hmac_secret: Option<&str>, trusted_public_keys: &[String]) -> Result<Self, String> {
+        Ok(Self {
+            hmac_secret: hmac_secret
+                .filter(|secret| !secret.is_empty())
+                .map(|secret| secret.as_bytes().to_vec()),
+            trusted_keys: trusted_public_keys
+                .iter()
+                .map(|key| decode_public_key(key))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_public_key(key: &str) -> Result<[u8; 32], String> {
+    base64::engine::general_purpose::STANDARD
+        .decode(key.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .filter(|bytes| VerifyingKey::from_bytes(bytes).is_ok())
+        .ok_or_else(|| format!("Invalid Ed25519 public key {}", key))
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|e| format!("Failed to generate random bytes: {}", e))?;
+    Ok(bytes)
+}
+
+fn signing_file_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("xshot"))
+        .join(SIGNING_FILE)
+}
+
+/// The app's signing file for callers without an `AppHandle`, such as the CLI. Follows the
+/// platform data directories Tauri's `app_data_dir` resolves to.
+pub fn default_signing_file_path() -> Option<PathBuf> {
+    let home = || std::env::var_os("HOME").map(PathBuf::from);
+    let data_dir = if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| home().map(|home| home.join(".local").join("share")))
+    }?;
+    Some(data_dir.join(APP_IDENTIFIER).join(SIGNING_FILE))
+}
+
+/// `Ok(None)` only when the file does not exist yet; an unreadable or corrupt file is an error
+/// so it never gets replaced with fresh keys.
+fn read_signing_file(path: &Path) -> Result<Option<SigningFile>, String> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read signing keys: {}", e)),
+    };
+    serde_json::from_slice::<SigningFile>(&bytes)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse signing keys {}: {}", path.display(), e))
+}
+
+/// Writes the signing file, readable by the owner only. With `create_new`, fails if the file
+/// already exists instead of replacing it.
+fn write_signing_file(path: &Path, file: &SigningFile, create_new: bool) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let bytes = serde_json::to_vec_pretty(file)?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true);
+    if create_new {
+        options.create_new(true);
+    } else {
+        options.create(true).truncate(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(&bytes)
+}
+
+/// Reads the device id and per-user Ed25519 key, creating both on first use.
+fn load_or_create(path: &Path) -> Result<SigningFile, String> {
+    if let Some(file) = read_signing_file(path)? {
+        return Ok(file);
+    }
+    let file = SigningFile {
+        device_id: hex(&random_bytes::<DEVICE_ID_BYTES>()?),
+        ed25519_secret_key: base64_encode(&random_bytes::<32>()?),
+        ..SigningFile::default()
+    };
+    match write_signing_file(path, &file, true) {
+        Ok(()) => Ok(file),
+        // Another caller created the keys first; use theirs.
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => read_signing_file(path)?
+            .ok_or_else(|| "Signing keys disappeared while being created".to_string()),
+        Err(e) => Err(format!("Failed to write signing keys: {}", e)),
+    }
+}
+
+fn keys_from_file(file: &SigningFile) -> Result<SigningKeys, String> {
+    let device_id = (0..DEVICE_ID_BYTES)
+        .map(|index| {
+            file.device_id
+                .get(index * 2..index * 2 + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        })
+        .collect::<Option<Vec<_>>>()
+        .and_then(|bytes| <[u8; DEVICE_ID_BYTES]>::try_from(bytes).ok())
+        .ok_or("Invalid device id in signing keys")?;
+    let secret = base64::engine::general_purpose::STANDARD
+        .decode(&file.ed25519_secret_key)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or("Invalid Ed25519 secret key in signing keys")?;
+    Ok(SigningKeys {
+        device_id,
+        signing_key: SigningKey::from_bytes(&secret),
+        hmac_secret: file
+            .hmac_secret
+            .as_ref()
+            .map(|secret| secret.as_bytes().to_vec()),
+    })
+}
+
+pub fn signing_keys(app: &AppHandle) -> Result<SigningKeys, String> {
+    signing_keys_at(&signing_file_path(app))
+}
+
+/// Keys from a signing file at `path`, creating it on first use like the app does.
+pub fn signing_keys_at(path: &Path) -> Result<SigningKeys, String> {
+    keys_from_file(&load_or_create(path)?)
+}
+
+/// Trusts this device's own key plus any configured ones. Never creates keys.
+pub fn verifier(app: &AppHandle) -> Verifier {
+    let file = match read_signing_file(&signing_file_path(app)) {
+        Ok(Some(file)) => file,
+        Ok(None) => return Verifier::default(),
+        Err(error) => {
+            eprintln!(
+                "[xshot][watermark][rust] signing keys unavailable: {}",
+                error
+            );
+            return Verifier::default();
+        }
+    };
+    let mut verifier =
+        Verifier::new(file.hmac_secret.as_deref(), &file.trusted_public_keys).unwrap_or_default();
+    if let Ok(keys) = keys_from_file(&file) {
+        verifier
+            .trusted_keys
+            .push(keys.signing_key.verifying_key().to_bytes());
+    }
+    verifier
+}
+
+fn signed_message(prefix: &[u8]) -> Vec<u8> {
+    [SIGNATURE_DOMAIN, prefix].concat()
+}
+
+fn hmac_tag(secret: &[u8], prefix: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(&signed_message(prefix));
+    mac
+}
+
+/// Appends the signature trailer to a watermark body that ends with its text.
+pub fn append_signature(
+    keys: &SigningKeys,
+    scheme: SignatureScheme,
+    body: &mut Vec<u8>,
+) -> Result<(), String> {
+    match scheme {
+        SignatureScheme::Hmac => {
+            let secret = keys
+                .hmac_secret
+                .as_deref()
+                .ok_or("No organization secret is configured for HMAC signing")?;
+            body.push(SCHEME_HMAC);
+            body.extend_from_slice(&keys.device_id);
+            let tag = hmac_tag(secret, body).finalize().into_bytes();
+            body.extend_from_slice(&tag);
+        }
+        SignatureScheme::Ed25519 => {
+            body.push(SCHEME_ED25519);
+            body.extend_from_slice(&keys.device_id);
+            body.extend_from_slice(&keys.signing_key.verifying_key().to_bytes());
+            let signature = keys.signing_key.sign(&signed_message(body));
+            body.extend_from_slice(&signature.to_bytes());
+        }
+    }
+    Ok(())
+}
+
+/// Checks the trailer starting at `text_end`. Returns `None` for unsigned bodies and for
+/// trailers this version does not understand.
+pub fn verify(body: &[u8], text_end: usize, verifier: &Verifier) -> Option<SignatureCheck> {
+    let trailer = body.get(text_end..)?;
+    let (&scheme, rest) = trailer.split_first()?;
+    let device_id = hex(rest.get(..DEVICE_ID_BYTES)?);
+    let rest = &rest[DEVICE_ID_BYTES..];
+    let header_end = text_end + 1 + DEVICE_ID_BYTES;
+
+    match scheme {
+        SCHEME_HMAC if rest.len() == HMAC_BYTES => {
+            let status = match verifier.hmac_secret.as_deref() {
+                None => SignatureStatus::Unverifiable,
+                Some(secret) => match hmac_tag(secret, &body[..header_end]).verify_slice(rest) {
+                    Ok(()) => SignatureStatus::Valid,
+                    Err(_) => SignatureStatus::Invalid,
+                },
+            };
+            Some(SignatureCheck {
+                scheme: SignatureScheme::Hmac,
+                status,
+                device_id,
+                public_key: None,
+            })
+        }
+        SCHEME_ED25519 if rest.len() == 32 + Signature::BYTE_SIZE => {
+            let public_key = <[u8; 32]>::try_from(&rest[..32]).ok()?;
+            let signature = Signature::from_slice(&rest[32..]).ok()?;
+            let verified = VerifyingKey::from_bytes(&public_key)
+                .map(|key| {
+                    key.verify(&signed_message(&body[..header_end + 32]), &signature)
+                        .is_ok()
+                })
+                .unwrap_or(false);
+            let status = if !verified {
+                SignatureStatus::Invalid
+            } else if verifier.trusted_keys.contains(&public_key) {
+                SignatureStatus::Valid
+            } else {
+                SignatureStatus::UntrustedKey
+            };
+            Some(SignatureCheck {
+                scheme: SignatureScheme::Ed25519,
+                status,
+                device_id,
+                public_key: Some(base64_encode(&public_key)),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// This device's id and public key, creating them on first use. Share the public key with
+/// whoever needs to verify this user's watermarks.
+#[tauri::command]
+pub fn get_watermark_signing(app: AppHandle) -> Result<SigningInfo, String> {
+    let file = load_or_create(&signing_file_path(&app))?;
+    let keys = keys_from_file(&file)?;
+    Ok(SigningInfo {
+        device_id: file.device_id,
+        public_key: base64_encode(&keys.signing_key.verifying_key().to_bytes()),
+        has_hmac_secret: file.hmac_secret.is_some(),
+        trusted_public_keys: file.trusted_public_keys,
+    })
+}
+
+/// Sets the organization HMAC secret (`None` keeps it, empty clears it) and the Ed25519 public
+/// keys whose signatures detection reports as valid.
+#[tauri::command]
+pub fn set_watermark_signing(
+    app: AppHandle,
+    hmac_secret: Option<String>,
+    trusted_public_keys: Vec<String>,
+) -> Result<SigningInfo, String> {
+    for key in &trusted_public_keys {
+        decode_public_key(key)?;
+    }
+    let path = signing_file_path(&app);
+    let mut file = load_or_create(&path)?;
+    if let Some(secret) = hmac_secret {
+        file.hmac_secret = Some(secret).filter(|secret| !secret.is_empty());
+    }
+    file.trusted_public_keys = trusted_public_keys
+        .iter()
+        .map(|key| key.trim().to_string())
+        .collect();
+    write_signing_file(&path, &file, false)
+        .map_err(|e| format!("Failed to write signing keys: {}", e))?;
+    get_watermark_signing(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &[u8] = b"alice@example.com";
+
+    fn keys(hmac_secret: Option<&str>) -> SigningKeys {
+        keys_from_file(&SigningFile {
+            device_id: "0011223344556677".into(),
+            ed25519_secret_key: base64_encode(&[7; 32]),
+            hmac_secret: hmac_secret.map(str::to_string),
+            trusted_public_keys: Vec::new(),
+        })
+        .unwrap()
+    }
+
+    /// A signed body laid out like the DCT watermark's, and where its text ends.
+    fn signed_body(keys: &SigningKeys, scheme: SignatureScheme) -> (Vec<u8>, usize) {
+        let mut body = vec![2, 0x69, 0x57, 0x4a, 0x25];
+        body.extend_from_slice(&(TEXT.len() as u16).to_be_bytes());
+        body.extend_from_slice(TEXT);
+        let text_end = body.len();
+        append_signature(keys, scheme, &mut body).unwrap();
+        (body, text_end)
+    }
+
+    fn status(body: &[u8], text_end: usize, verifier: &Verifier) -> Option<SignatureStatus> {
+        verify(body, text_end, verifier).map(|check| check.status)
+    }
+
+    #[test]
+    fn ed25519_is_valid_only_for_trusted_keys() {
+        let keys = keys(None);
+        let (body, text_end) = signed_body(&keys, SignatureScheme::Ed25519);
+        let public_key = base64_encode(&keys.signing_key.verifying_key().to_bytes());
+
+        let check = verify(
+            &body,
+            text_end,
+            &Verifier::new(None, std::slice::from_ref(&public_key)).unwrap(),
+        );
+        assert_eq!(
+            check,
+            Some(SignatureCheck {
+                scheme: SignatureScheme::Ed25519,
+                status: SignatureStatus::Valid,
+                device_id: "0011223344556677".into(),
+                public_key: Some(public_key),
+            })
+        );
+        let other_key = base64_encode(&SigningKey::from_bytes(&[9; 32]).verifying_key().to_bytes());
+        for verifier in [
+            Verifier::default(),
+            Verifier::new(None, &[other_key]).unwrap(),
+        ] {
+            assert_eq!(
+                status(&body, text_end, &verifier),
+                Some(SignatureStatus::UntrustedKey)
+            );
+        }
+    }
+
+    #[test]
+    fn hmac_needs_the_organization_secret() {
+        let (body, text_end) = signed_body(&keys(Some("org secret")), SignatureScheme::Hmac);
+        let with_secret = |secret| Verifier::new(secret, &[]).unwrap();
+        assert_eq!(
+            status(&body, text_end, &with_secret(Some("org secret"))),
+            Some(SignatureStatus::Valid)
+        );
+        assert_eq!(
+            status(&body, text_end, &with_secret(Some("wrong secret"))),
+            Some(SignatureStatus::Invalid)
+        );
+        assert_eq!(
+            status(&body, text_end, &with_secret(None)),
+            Some(SignatureStatus::Unverifiable)
+        );
+        assert!(append_signature(&keys(None), SignatureScheme::Hmac, &mut TEXT.to_vec()).is_err());
+    }
+
+    #[test]
+    fn tampered_bodies_are_invalid() {
+        let keys = keys(Some("org secret"));
+        let verifier = Verifier::new(Some("org secret"), &[]).unwrap();
+        for scheme in [SignatureScheme::Hmac, SignatureScheme::Ed25519] {
+            let (body, text_end) = signed_body(&keys, scheme);
+            // Timestamp, text, device id and signature.
+            for index in [2, 8, text_end + 1, body.len() - 1] {
+                let mut tampered = body.clone();
+                tampered[index] ^= 0x10;
+                assert_eq!(
+                    status(&tampered, text_end, &verifier),
+                    Some(SignatureStatus::Invalid),
+                    "{:?} byte {}",
+                    scheme,
+                    index
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ignores_missing_truncated_and_unknown_trailers() {
+        let keys = keys(Some("org secret"));
+        let verifier = Verifier::new(Some("org secret"), &[]).unwrap();
+        for scheme in [SignatureScheme::Hmac, SignatureScheme::Ed25519] {
+            let (body, text_end) = signed_body(&keys, scheme);
+            assert_eq!(verify(&body[..text_end], text_end, &verifier), None);
+            assert_eq!(verify(&body[..text_end + 5], text_end, &verifier), None);
+            assert_eq!(verify(&body[..body.len() - 1], text_end, &verifier), None);
+            assert_eq!(
+                verify(&[&body[..], &[0]].concat(), text_end, &verifier),
+                None
+            );
+
+            let mut unknown = body.clone();
+            unknown[text_end] = 9;
+            assert_eq!(verify(&unknown, text_end, &verifier), None);
+        }
+    }
+}
@@ -0,0 +1,623 @@
+// Resynchronisation for the DCT watermark after rescaling, cropping or a secondary screenshot.
+// The Rust embedder tiles a 128px sync pattern into a coefficient pair the main watermark leaves
+// alone. The tile period gives away the scale, its phase the crop offset modulo a tile, and every
+// tile carries the original size so the shuffled block order can be rebuilt.
+use crate::watermark::{self, BlockLayout, CoefficientPair, Detection, ROBUST_BLOCK_SIZE};
+use crate::watermark_signing::Verifier;
+use image::imageops::FilterType;
+use rustfft::{num_complex::Complex, FftPlanner};
+
+const SYNC_TILE_BLOCKS: usize = 16;
+const SYNC_TILE_SIZE: usize = SYNC_TILE_BLOCKS * ROBUST_BLOCK_SIZE;
+const SYNC_TILE_CELLS: usize = SYNC_TILE_BLOCKS * SYNC_TILE_BLOCKS;
+const SYNC_COEFF_A: (usize, usize) = (1, 2);
+const SYNC_COEFF_B: (usize, usize) = (2, 1);
+const SYNC_MARGIN: f64 = 8.0;
+const SYNC_MAX_COEFF_DELTA: f64 = 24.0;
+const GEOMETRY_BYTES: usize = 6;
+const GEOMETRY_BITS: usize = GEOMETRY_BYTES * 8;
+const GEOMETRY_COPIES: usize = 2;
+/// Weak geometry bits tried flipped. Blocks on pure white or black cannot take the full mark, so a
+/// few cells can read faintly even when the tile lines up.
+const GEOMETRY_REPAIR_BITS: usize = 4;
+const MIN_SCALE: f64 = 0.5;
+const MAX_SCALE: f64 = 2.0;
+const MAX_HARMONICS: usize = 8;
+const SCALE_CANDIDATES: usize = 8;
+/// Side of the central window, in original pixels, that screens each candidate scale before the
+/// whole image is registered.
+const PROBE_SIZE: usize = 512;
+/// Correlation with the sync cells in standard deviations. Chance peaks over every phase and tile
+/// offset stay around 4.
+const MIN_SYNC_SCORE: f64 = 6.0;
+const HIGH_PASS_RADIUS: usize = 16;
+const HIGH_PASS_LIMIT: f32 = 8.0;
+/// Blocks whose content overpowered the write read as large differences of the wrong sign, so
+/// votes are capped near a plain majority and only blurred, near-zero reads count for less.
+const MAX_BLOCK_VOTE: f64 = 6.0;
+
+/// Cell roles and sync bits of one tile. The first `GEOMETRY_BITS * GEOMETRY_COPIES` cells of
+/// `order` carry the original size, the rest a fixed pseudo-random pattern.
+struct SyncTile {
+    order: Vec<usize>,
+    pattern: Vec<u8>,
+}
+
+impl SyncTile {
+    fn new() -> Self {
+        let mut random = watermark::prng(watermark::fnv1a(b"xshot-watermark-sync-v1"));
+        let pattern = (0..SYNC_TILE_CELLS)
+            .map(|_| u8::from(random() >= 0.5))
+            .collect();
+        let mut order = (0..SYNC_TILE_CELLS).collect::<Vec<_>>();
+        for index in (1..SYNC_TILE_CELLS).rev() {
+            let swap_index = (random() * (index + 1) as f64).floor() as usize;
+            order.swap(index, swap_index);
+        }
+        Self { order, pattern }
+    }
+
+    fn geometry_cells(&self) -> &[usize] {
+        &self.order[..GEOMETRY_BITS * GEOMETRY_COPIES]
+    }
+
+    fn sync_cells(&self) -> &[usize] {
+        &self.order[GEOMETRY_BITS * GEOMETRY_COPIES..]
+    }
+
+    fn bits(&self, width: u16, height: u16) -> Vec<u8> {
+        let mut bits = self.pattern.clone();
+        let geometry = watermark::bytes_to_bits(&geometry_bytes(width, height));
+        for (cell, bit) in self.geometry_cells().iter().zip(geometry.iter().cycle()) {
+            bits[*cell] = *bit;
+        }
+        bits
+    }
+}
+
+fn geometry_bytes(width: u16, height: u16) -> [u8; GEOMETRY_BYTES] {
+    let mut bytes = [0u8; GEOMETRY_BYTES];
+    bytes[..2].copy_from_slice(&width.to_be_bytes());
+    bytes[2..4].copy_from_slice(&height.to_be_bytes());
+    let checksum = watermark::fnv1a(&bytes[..4]) as u16;
+    bytes[4..].copy_from_slice(&checksum.to_be_bytes());
+    bytes
+}
+
+fn parse_geometry(bytes: &[u8]) -> Option<(u32, u32)> {
+    let width = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let height = u16::from_be_bytes([bytes[2], bytes[3]]);
+    let checksum = u16::from_be_bytes([bytes[4], bytes[5]]);
+    (width > 0 && height > 0 && geometry_bytes(width, height)[4..] == checksum.to_be_bytes())
+        .then_some((width as u32, height as u32))
+}
+
+fn sync_pair() -> CoefficientPair {
+    CoefficientPair::new(
+        SYNC_COEFF_A,
+        SYNC_COEFF_B,
+        SYNC_MARGIN,
+        SYNC_MAX_COEFF_DELTA,
+    )
+}
+
+/// Writes the sync tiles over every whole 8x8 block. Images wider or taller than 65535px are left
+/// without them.
+pub fn write_sync(image: &mut image::RgbaImage) {
+    let (Ok(width), Ok(height)) = (u16::try_from(image.width()), u16::try_from(image.height()))
+    else {
+        return;
+    };
+    let bits = SyncTile::new().bits(width, height);
+    let pair = sync_pair();
+    for block_y in 0..height as usize / ROBUST_BLOCK_SIZE {
+        for block_x in 0..width as usize / ROBUST_BLOCK_SIZE {
+            let cell = (block_y % SYNC_TILE_BLOCKS) * SYNC_TILE_BLOCKS + block_x % SYNC_TILE_BLOCKS;
+            pair.write_bit(
+                image,
+                block_x * ROBUST_BLOCK_SIZE,
+                block_y * ROBUST_BLOCK_SIZE,
+                bits[cell],
+            );
+        }
+    }
+}
+
+fn luma_plane(image: &image::RgbaImage) -> Vec<f32> {
+    image
+        .pixels()
+        .map(|pixel| pixel[0] as f32 * 0.299 + pixel[1] as f32 * 0.587 + pixel[2] as f32 * 0.114)
+        .collect()
+}
+
+/// Removes the local mean and drops edges entirely, so text and UI borders do not drown the
+/// faint sync pattern in the flat areas around them.
+fn high_pass(line: &[f32]) -> Vec<f32> {
+    let mut prefix = Vec::with_capacity(line.len() + 1);
+    prefix.push(0.0f64);
+    for value in line {
+        prefix.push(prefix[prefix.len() - 1] + *value as f64);
+    }
+    (0..line.len())
+        .map(|index| {
+            let start = index.saturating_sub(HIGH_PASS_RADIUS);
+            let end = (index + HIGH_PASS_RADIUS + 1).min(line.len());
+            let mean = (prefix[end] - prefix[start]) / (end - start) as f64;
+            let value = line[index] - mean as f32;
+            if value.abs() > HIGH_PASS_LIMIT {
+                0.0
+            } else {
+                value
+            }
+        })
+        .collect()
+}
+
+/// Normalized autocorrelation of `lines`, averaged over all of them, for lags below half the
+/// line length.
+fn autocorrelation(planner: &mut FftPlanner<f64>, lines: &[Vec<f32>]) -> Vec<f64> {
+    let Some(length) = lines.first().map(Vec::len) else {
+        return Vec::new();
+    };
+    let size = (length * 2).next_power_of_two();
+    let forward = planner.plan_fft_forward(size);
+    let inverse = planner.plan_fft_inverse(size);
+
+    let mut power = vec![Complex::new(0.0, 0.0); size];
+    let mut buffer = vec![Complex::new(0.0, 0.0); size];
+    for line in lines {
+        buffer.fill(Complex::new(0.0, 0.0));
+        for (slot, value) in buffer.iter_mut().zip(high_pass(line)) {
+            slot.re = value as f64;
+        }
+        forward.process(&mut buffer);
+        for (sum, value) in power.iter_mut().zip(&buffer) {
+            sum.re += value.norm_sqr();
+        }
+    }
+    inverse.process(&mut power);
+
+    let zero_lag = power[0].re / length as f64;
+    if zero_lag <= f64::EPSILON {
+        return Vec::new();
+    }
+    (0..length / 2)
+        .map(|lag| power[lag].re / (length - lag) as f64 / zero_lag)
+        .collect()
+}
+
+/// Guesses how much the image was scaled from the period of the sync tiles, using a comb over
+/// the row and column autocorrelation so every visible repeat sharpens the estimate.
+fn estimate_scales(luma: &[f32], width: usize, height: usize) -> Vec<f64> {
+    let mut planner = FftPlanner::new();
+    let rows = luma
+        .chunks_exact(width)
+        .map(<[f32]>::to_vec)
+        .collect::<Vec<_>>();
+    let columns = (0..width)
+        .map(|x| (0..height).map(|y| luma[y * width + x]).collect())
+        .collect::<Vec<_>>();
+    let horizontal = autocorrelation(&mut planner, &rows);
+    let vertical = autocorrelation(&mut planner, &columns);
+
+    let at = |lag: f64| -> Option<f64> {
+        let index = lag.floor() as usize;
+        let fraction = lag - index as f64;
+        let sample = |series: &[f64]| {
+            (index + 1 < series.len())
+                .then(|| series[index] * (1.0 - fraction) + series[index + 1] * fraction)
+        };
+        match (sample(&horizontal), sample(&vertical)) {
+            (Some(x), Some(y)) => Some((x + y) / 2.0),
+            (value, None) | (None, value) => value,
+        }
+    };
+
+    // A little past both ends so the extremes can still show up as peaks.
+    let min_period = SYNC_TILE_SIZE as f64 * MIN_SCALE * 0.97;
+    let max_period = SYNC_TILE_SIZE as f64 * MAX_SCALE * 1.03;
+    let steps = ((max_period - min_period) / 0.02) as usize;
+    let scores = (0..=steps)
+        .map(|step| {
+            let period = min_period + step as f64 * 0.02;
+            let harmonics = (1..=MAX_HARMONICS)
+                .map_while(|harmonic| at(period * harmonic as f64))
+                .collect::<Vec<_>>();
+            let score = if harmonics.is_empty() {
+                f64::NEG_INFINITY
+            } else {
+                harmonics.iter().sum::<f64>() / harmonics.len() as f64
+            };
+            (period, score)
+        })
+        .collect::<Vec<_>>();
+
+    let mut peaks = scores
+        .windows(3)
+        .filter(|window| {
+            window[1].1.is_finite()
+                && window[1].1 > 0.0
+                && window[1].1 >= window[0].1
+                && window[1].1 > window[2].1
+        })
+        .map(|window| window[1])
+        .collect::<Vec<_>>();
+    peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut scales: Vec<f64> = Vec::new();
+    for (period, _) in peaks {
+        let scale = period / SYNC_TILE_SIZE as f64;
+        if scales
+            .iter()
+            .all(|existing| (existing / scale - 1.0).abs() > 0.02)
+        {
+            scales.push(scale);
+        }
+        if scales.len() == SCALE_CANDIDATES {
+            break;
+        }
+    }
+    scales
+}
+
+fn dct_vector(frequency: usize) -> [f32; ROBUST_BLOCK_SIZE] {
+    std::array::from_fn(|x| {
+        0.5 * (((2 * x + 1) * frequency) as f64 * std::f64::consts::PI / 16.0).cos() as f32
+    })
+}
+
+/// The sync coefficient difference for a block whose top-left corner sits at every pixel,
+/// computed separably. The map is `width - 7` by `height - 7`.
+fn sync_differences(luma: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let map_width = width + 1 - ROBUST_BLOCK_SIZE;
+    let map_height = height + 1 - ROBUST_BLOCK_SIZE;
+    let (a_x, a_y) = (dct_vector(SYNC_COEFF_A.0), dct_vector(SYNC_COEFF_A.1));
+    let (b_x, b_y) = (dct_vector(SYNC_COEFF_B.0), dct_vector(SYNC_COEFF_B.1));
+
+    let mut rows_a = vec![0.0f32; map_width * height];
+    let mut rows_b = vec![0.0f32; map_width * height];
+    for y in 0..height {
+        let row = &luma[y * width..(y + 1) * width];
+        for x in 0..map_width {
+            let window = &row[x..x + ROBUST_BLOCK_SIZE];
+            rows_a[y * map_width + x] = window.iter().zip(&a_x).map(|(l, c)| l * c).sum();
+            rows_b[y * map_width + x] = window.iter().zip(&b_x).map(|(l, c)| l * c).sum();
+        }
+    }
+
+    let mut differences = vec![0.0f32; map_width * map_height];
+    for y in 0..map_height {
+        for x in 0..map_width {
+            let mut difference = 0.0;
+            for offset in 0..ROBUST_BLOCK_SIZE {
+                let index = (y + offset) * map_width + x;
+                difference += rows_a[index] * a_y[offset] - rows_b[index] * b_y[offset];
+            }
+            differences[y * map_width + x] = difference;
+        }
+    }
+    differences
+}
+
+/// Where the sync tiles line up in an image brought back to the original scale.
+struct Registration {
+    score: f64,
+    image: image::RgbaImage,
+    original_width: u32,
+    original_height: u32,
+    /// Pixel offset of the block grid in `image`.
+    phase: (usize, usize),
+    /// Tile cell of the first grid block.
+    tile: (usize, usize),
+}
+
+/// Best tile alignment found while folding the sync differences.
+struct Alignment {
+    score: f64,
+    phase: (usize, usize),
+    tile: (usize, usize),
+    folded: Vec<f64>,
+}
+
+fn register_at(image: &image::RgbaImage, scale: f64, tile: &SyncTile) -> Option<Registration> {
+    let image = if (scale - 1.0).abs() < 1e-3 {
+        image.clone()
+    } else {
+        let width = (image.width() as f64 / scale).round() as u32;
+        let height = (image.height() as f64 / scale).round() as u32;
+        image::imageops::resize(image, width, height, FilterType::Lanczos3)
+    };
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    if width < SYNC_TILE_SIZE || height < SYNC_TILE_SIZE {
+        return None;
+    }
+    let differences = sync_differences(&luma_plane(&image), width, height);
+    let map_width = width + 1 - ROBUST_BLOCK_SIZE;
+    let map_height = height + 1 - ROBUST_BLOCK_SIZE;
+    let limit = (SYNC_MARGIN * 2.0) as f32;
+
+    let fold_index = |cell: usize, tile_x: usize, tile_y: usize| {
+        let (cell_x, cell_y) = (cell % SYNC_TILE_BLOCKS, cell / SYNC_TILE_BLOCKS);
+        ((cell_y + SYNC_TILE_BLOCKS - tile_y) % SYNC_TILE_BLOCKS) * SYNC_TILE_BLOCKS
+            + (cell_x + SYNC_TILE_BLOCKS - tile_x) % SYNC_TILE_BLOCKS
+    };
+
+    let mut best: Option<Alignment> = None;
+    for phase_y in 0..ROBUST_BLOCK_SIZE {
+        for phase_x in 0..ROBUST_BLOCK_SIZE {
+            // Sum every block onto one tile so all repeats vote together.
+            let mut folded = vec![0.0f64; SYNC_TILE_CELLS];
+            for (block_y, y) in (phase_y..map_height).step_by(ROBUST_BLOCK_SIZE).enumerate() {
+                for (block_x, x) in (phase_x..map_width).step_by(ROBUST_BLOCK_SIZE).enumerate() {
+                    let cell = (block_y % SYNC_TILE_BLOCKS) * SYNC_TILE_BLOCKS
+                        + block_x % SYNC_TILE_BLOCKS;
+                    folded[cell] += differences[y * map_width + x].clamp(-limit, limit) as f64;
+                }
+            }
+
+            for tile_y in 0..SYNC_TILE_BLOCKS {
+                for tile_x in 0..SYNC_TILE_BLOCKS {
+                    let (mut correlation, mut energy) = (0.0, 0.0);
+                    for &cell in tile.sync_cells() {
+                        let value = folded[fold_index(cell, tile_x, tile_y)];
+                        correlation += if tile.pattern[cell] == 1 {
+                            value
+                        } else {
+                            -value
+                        };
+                        energy += value * value;
+                    }
+                    let score = correlation / energy.sqrt().max(f64::EPSILON);
+                    if best
+                        .as_ref()
+                        .is_none_or(|alignment| score > alignment.score)
+                    {
+                        best = Some(Alignment {
+                            score,
+                            phase: (phase_x, phase_y),
+                            tile: (tile_x, tile_y),
+                            folded: folded.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let Alignment {
+        score,
+        phase,
+        tile: (tile_x, tile_y),
+        folded,
+    } = best?;
+    if score < MIN_SYNC_SCORE {
+        return None;
+    }
+    let mut sums = vec![0.0; GEOMETRY_BITS];
+    for (index, cell) in tile.geometry_cells().iter().enumerate() {
+        sums[index % GEOMETRY_BITS] += folded[fold_index(*cell, tile_x, tile_y)];
+    }
+    let bits = sums
+        .iter()
+        .map(|sum| u8::from(*sum > 0.0))
+        .collect::<Vec<_>>();
+    let (original_width, original_height) = watermark::with_flips(
+        &watermark::bits_to_bytes(&bits),
+        &watermark::least_reliable(&sums, GEOMETRY_REPAIR_BITS),
+        parse_geometry,
+    )?;
+    Some(Registration {
+        score,
+        image,
+        original_width,
+        original_height,
+        phase,
+        tile: (tile_x, tile_y),
+    })
+}
+
+/// Every offset of the registered image inside the original that agrees with the tile phase and
+/// still overlaps it, nearest to the original's origin first.
+fn candidate_offsets(registration: &Registration) -> Vec<(i64, i64)> {
+    let axis = |phase: usize, tile: usize, size: u32, original: u32| {
+        let base = (tile * ROBUST_BLOCK_SIZE) as i64 - phase as i64;
+        let period = SYNC_TILE_SIZE as i64;
+        let first = (-(size as i64) - base).div_euclid(period);
+        (first..)
+            .map(move |repeat| base + repeat * period)
+            .skip_while(move |offset| *offset <= -(size as i64))
+            .take_while(move |offset| *offset < original as i64)
+    };
+    let xs = axis(
+        registration.phase.0,
+        registration.tile.0,
+        registration.image.width(),
+        registration.original_width,
+    )
+    .collect::<Vec<_>>();
+    let ys = axis(
+        registration.phase.1,
+        registration.tile.1,
+        registration.image.height(),
+        registration.original_height,
+    )
+    .collect::<Vec<_>>();
+    let mut offsets = ys
+        .iter()
+        .flat_map(|y| xs.iter().map(move |x| (*x, *y)))
+        .collect::<Vec<_>>();
+    offsets.sort_by_key(|(x, y)| x.abs() + y.abs());
+    offsets
+}
+
+fn read_registered(registration: &Registration, verifier: &Verifier) -> Option<Detection> {
+    let pair = watermark::robust_pair();
+    let (phase_x, phase_y) = registration.phase;
+    let grid_width =
+        (registration.image.width() as usize).saturating_sub(phase_x) / ROBUST_BLOCK_SIZE;
+    let grid_height =
+        (registration.image.height() as usize).saturating_sub(phase_y) / ROBUST_BLOCK_SIZE;
+    let grid = (0..grid_height)
+        .flat_map(|grid_y| (0..grid_width).map(move |grid_x| (grid_x, grid_y)))
+        .map(|(grid_x, grid_y)| {
+            pair.difference(
+                &registration.image,
+                phase_x + grid_x * ROBUST_BLOCK_SIZE,
+                phase_y + grid_y * ROBUST_BLOCK_SIZE,
+            )
+            .clamp(-MAX_BLOCK_VOTE, MAX_BLOCK_VOTE)
+        })
+        .collect::<Vec<_>>();
+
+    let layout = BlockLayout::new(registration.original_width, registration.original_height);
+    candidate_offsets(registration)
+        .into_iter()
+        .find_map(|(offset_x, offset_y)| {
+            // Resampling blurs the marks, so votes are weighted by how clearly each block reads.
+            let read_vote = |left: usize, top: usize| {
+                let x = left as i64 - offset_x - phase_x as i64;
+                let y = top as i64 - offset_y - phase_y as i64;
+                if x < 0 || y < 0 {
+                    return None;
+                }
+                let grid_x = x as usize / ROBUST_BLOCK_SIZE;
+                let grid_y = y as usize / ROBUST_BLOCK_SIZE;
+                (grid_x < grid_width && grid_y < grid_height)
+                    .then(|| grid[grid_y * grid_width + grid_x])
+            };
+            watermark::read_robust_blocks(&layout, read_vote, true, verifier)
+        })
+}
+
+/// Looks for the sync tiles at the original scale and at the scales the tile period suggests,
+/// then decodes the DCT watermark through the best registration that carries a valid size.
+pub fn resync(image: &image::RgbaImage, verifier: &Verifier) -> Option<Detection> {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    if width < SYNC_TILE_SIZE / 2 || height < SYNC_TILE_SIZE / 2 {
+        return None;
+    }
+    let tile = SyncTile::new();
+    let mut scales = vec![1.0];
+    scales.extend(
+        estimate_scales(&luma_plane(image), width, height)
+            .into_iter()
+            .filter(|scale| (scale - 1.0).abs() > 0.005),
+    );
+
+    // Repeating UI such as list rows also makes autocorrelation peaks, so each candidate is first
+    // tried on a central window.
+    let mut registrations = scales
+        .into_iter()
+        .filter(|scale| {
+            let side = ((PROBE_SIZE as f64 * scale).round() as usize)
+                .min(width)
+                .min(height) as u32;
+            let left = (image.width() - side) / 2;
+            let top = (image.height() - side) / 2;
+            let probe = image::imageops::crop_imm(image, left, top, side, side).to_image();
+            register_at(&probe, *scale, &tile).is_some()
+        })
+        .filter_map(|scale| register_at(image, scale, &tile))
+        .collect::<Vec<_>>();
+    registrations.sort_by(|a, b| b.score.total_cmp(&a.score));
+    registrations
+        .iter()
+        .find_map(|registration| read_registered(registration, verifier))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{imageops, Rgba, RgbaImage};
+
+    const TEXT: &str = "alice@example.com 2026";
+
+    /// A window-like screenshot: a dark sidebar with menu items, a white header, and white cards
+    /// of text lines on a light grey page.
+    fn screenshot(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| {
+            let (card_x, card_y) = ((x + 80) % 340, (y + 140) % 230);
+            if x < 180 {
+                let item = y % 36 > 14 && y % 36 < 24 && (24..150).contains(&x);
+                Rgba(if item && (x / 7) % 4 != 0 {
+                    [200, 200, 210, 255]
+                } else {
+                    [40, 44, 52, 255]
+                })
+            } else if y < 56 {
+                Rgba([255, 255, 255, 255])
+            } else if card_x < 310 && card_y < 200 {
+                let line = card_y > 20 && card_y % 22 < 9 && card_x > 16;
+                let ink = line && card_x < 16 + (x * 7 + y * 3) % 260 && (x / 6) % 5 != 0;
+                Rgba(if ink {
+                    [60, 60, 70, 255]
+                } else {
+                    [255, 255, 255, 255]
+                })
+            } else {
+                Rgba([246, 247, 249, 255])
+            }
+        })
+    }
+
+    fn marked(width: u32, height: u32) -> RgbaImage {
+        let mut image = screenshot(width, height);
+        assert!(watermark::embed(&mut image, TEXT, 1_760_000_000_000.0, None).unwrap());
+        image
+    }
+
+    fn detect(image: &RgbaImage, deep: bool) -> Option<String> {
+        let verifier = Verifier::new(None, &[]).unwrap();
+        watermark::detect(image, &verifier, deep).map(|watermark| watermark.text)
+    }
+
+    fn resize(image: &RgbaImage, scale: f64) -> RgbaImage {
+        let width = (image.width() as f64 * scale).round() as u32;
+        let height = (image.height() as f64 * scale).round() as u32;
+        imageops::resize(image, width, height, imageops::FilterType::Triangle)
+    }
+
+    #[test]
+    fn recovers_rescaled_copies() {
+        let image = marked(1024, 720);
+        for scale in [0.8, 1.25] {
+            let scaled = resize(&image, scale);
+            assert_eq!(detect(&scaled, false), None, "scale {}", scale);
+            assert_eq!(
+                detect(&scaled, true).as_deref(),
+                Some(TEXT),
+                "scale {}",
+                scale
+            );
+        }
+    }
+
+    #[test]
+    fn recovers_cropped_copies() {
+        let image = marked(1024, 720);
+        let cropped = imageops::crop_imm(&image, 101, 67, 860, 600).to_image();
+        assert_eq!(detect(&cropped, false), None);
+        assert_eq!(detect(&cropped, true).as_deref(), Some(TEXT));
+    }
+
+    #[test]
+    fn finds_nothing_in_unmarked_images() {
+        let mut state = 0x2545_f491_u32;
+        let noise = RgbaImage::from_fn(640, 480, |_, _| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let [r, g, b, _] = state.to_le_bytes();
+            Rgba([r, g, b, 255])
+        });
+        let plain = screenshot(1024, 720);
+        for (name, image) in [
+            ("screenshot", plain.clone()),
+            ("rescaled screenshot", resize(&plain, 0.8)),
+            ("noise", noise),
+            ("white", RgbaImage::from_pixel(640, 480, Rgba([255; 4]))),
+        ] {
+            assert_eq!(detect(&image, true), None, "{}", name);
+        }
+    }
+}
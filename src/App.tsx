@@ -1,46 +1,68 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { disable, enable, isEnabled } from "@tauri-apps/plugin-autostart";
-import { open } from "@tauri-apps/plugin-dialog";
+import { open, save } from "@tauri-apps/plugin-dialog";
 import { useTranslation } from "react-i18next";
 import {
   AppWindowMac,
   Check,
+  Copy,
   Crosshair,
   Eye,
   ExternalLink,
+  FileDown,
+  Files,
   Fingerprint,
   FolderOpen,
+  FolderSearch,
   Keyboard,
   Languages,
+  Monitor,
+  MousePointer2,
+  MousePointerClick,
   Pencil,
+  Pin,
   Power,
   RefreshCw,
   RotateCcw,
   Search,
   Settings2,
   ShieldCheck,
+  Video,
   X,
+  Zap,
 } from "lucide-react";
 import {
   DEFAULT_SHORTCUT,
   getShortcut,
   registerShortcut,
+  setCapturePrewarm,
+  setInputHistory,
   setShortcut,
   startCapture,
 } from "./logic/shortcut";
 import {
   getSettings,
+  PDF_MARGIN_OPTIONS,
+  PDF_PAGE_SIZES,
+  RECORDING_FORMATS,
+  RECORDING_FPS_OPTIONS,
   SUPPORTED_LANGUAGES,
   VISIBLE_WATERMARK_PLACEMENTS,
   WATERMARK_OPACITY_MAX,
   WATERMARK_OPACITY_MIN,
+  WATERMARK_SIGNATURES,
   WATERMARK_TEXT_MAX_LENGTH,
   updateSettings,
   type AppSettings,
   type AppLanguage,
+  type PdfPageSize,
+  type RecordingFormat,
   type VisibleWatermarkPlacement,
+  type WatermarkSignature,
 } from "./logic/settings";
+import { exportPdfFromFiles, getPdfOptions } from "./logic/pdf";
 import { decodeHiddenWatermarkFromFile } from "./logic/watermark";
 import PinWindow from "./windows/Pin";
 import ScreenshotWindow from "./windows/Screenshot";
@@ -57,6 +79,14 @@ const MODIFIER_KEYS = new Set([
   "Super",
 ]);
 
+const RECORDING_FORMAT_LABELS: Record<RecordingFormat, string> = {
+  gif: "GIF",
+  apng: "APNG",
+  webp: "WebP",
+  mp4: "MP4",
+  webm: "WebM",
+};
+
 const VISIBLE_WATERMARK_PLACEMENT_LABEL_KEYS: Record<
   VisibleWatermarkPlacement,
   string
@@ -69,6 +99,12 @@ const VISIBLE_WATERMARK_PLACEMENT_LABEL_KEYS: Record<
   "bottom-right": "settings.watermark.bottomRight",
 };
 
+const WATERMARK_SIGNATURE_LABEL_KEYS: Record<WatermarkSignature, string> = {
+  none: "settings.watermark.signatureNone",
+  ed25519: "settings.watermark.signatureEd25519",
+  hmac: "settings.watermark.signatureHmac",
+};
+
 type MacosPermissionKind = "accessibility" | "screenRecording";
 
 type MacosPermissionStatus = {
@@ -87,6 +123,25 @@ type HiddenWatermarkDetectionState = {
   status: HiddenWatermarkDetectionStatus;
   message: string;
 };
+type WatermarkScanProgress = {
+  processed: number;
+  total: number;
+  found: number;
+  path: string;
+};
+type WatermarkSigningInfo = {
+  deviceId: string;
+  publicKey: string;
+  hasHmacSecret: boolean;
+  trustedPublicKeys: string[];
+};
+type WatermarkScanReport = {
+  total: number;
+  scanned: number;
+  found: number;
+  failed: number;
+  cancelled: boolean;
+};
 
 function normalizeKey(key: string) {
   if (/^[a-z]$/i.test(key)) return key.toUpperCase();
@@ -187,17 +242,34 @@ function App() {
     }).catch((error) => {
       console.warn("Failed to apply Dock icon setting:", error);
     });
+    if (initialSettings.prewarmCapture) {
+      void setCapturePrewarm(true).catch((error) => {
+        console.warn("Failed to start capture prewarming:", error);
+      });
+    }
+    if (initialSettings.captureInput) {
+      void setInputHistory(true).catch((error) => {
+        console.warn("Failed to start input history:", error);
+      });
+    }
 
     void isEnabled()
       .then((enabled) => setAutoStart(enabled))
       .catch((error) => {
         console.warn("Failed to read autostart state:", error);
       });
+
+    void invoke<boolean>("get_pin_persistence")
+      .then((enabled) => setPinPersistence(enabled))
+      .catch((error) => {
+        console.warn("Failed to read pin persistence state:", error);
+      });
   }, []);
 
   const [draftShortcut, setDraftShortcut] = useState(getShortcut);
   const [settings, setSettings] = useState(getSettings);
   const [autoStart, setAutoStart] = useState(false);
+  const [pinPersistence, setPinPersistence] = useState(false);
   const [permissions, setPermissions] = useState<MacosPermissionStatus | null>(
     null
   );
@@ -210,6 +282,21 @@ function App() {
       status: "idle",
       message: "",
     });
+  const [watermarkScan, setWatermarkScan] =
+    useState<HiddenWatermarkDetectionState>({
+      status: "idle",
+      message: "",
+    });
+  const [pdfCombine, setPdfCombine] =
+    useState<HiddenWatermarkDetectionState>({
+      status: "idle",
+      message: "",
+    });
+  const [deepWatermarkScan, setDeepWatermarkScan] = useState(false);
+  const [watermarkSigning, setWatermarkSigning] =
+    useState<WatermarkSigningInfo | null>(null);
+  const [hmacSecretDraft, setHmacSecretDraft] = useState("");
+  const [trustedKeysDraft, setTrustedKeysDraft] = useState("");
   const [
     isHiddenWatermarkDetectionOverflowing,
     setIsHiddenWatermarkDetectionOverflowing,
@@ -226,6 +313,15 @@ function App() {
   const hiddenWatermarkDetectionLabel =
     hiddenWatermarkDetection.message || t("settings.watermark.detectHint");
 
+  useEffect(() => {
+    if (settings.hiddenWatermark.signature === "none" || watermarkSigning) {
+      return;
+    }
+    void loadWatermarkSigning().catch((error) => {
+      console.warn("[xshot][settings] load watermark signing failed", error);
+    });
+  }, [settings.hiddenWatermark.signature, watermarkSigning]);
+
   useEffect(() => {
     const element = hiddenWatermarkDetectionResultRef.current;
     if (!element) return;
@@ -375,6 +471,36 @@ function App() {
     }
   };
 
+  const handlePrewarmCaptureChange = async (
+    event: React.ChangeEvent<HTMLInputElement>
+  ) => {
+    const enabled = event.currentTarget.checked;
+    applySettings({ prewarmCapture: enabled });
+
+    try {
+      await setCapturePrewarm(enabled);
+      setStatus(t("settings.status.updated"));
+    } catch {
+      applySettings({ prewarmCapture: !enabled });
+      setStatus(t("settings.status.updateFailed"));
+    }
+  };
+
+  const handleCaptureInputChange = async (
+    event: React.ChangeEvent<HTMLInputElement>
+  ) => {
+    const enabled = event.currentTarget.checked;
+    applySettings({ captureInput: enabled });
+
+    try {
+      await setInputHistory(enabled);
+      setStatus(t("settings.status.updated"));
+    } catch {
+      applySettings({ captureInput: !enabled });
+      setStatus(t("settings.status.updateFailed"));
+    }
+  };
+
   const handleAutoStartChange = async (
     event: React.ChangeEvent<HTMLInputElement>
   ) => {
@@ -392,6 +518,21 @@ function App() {
     }
   };
 
+  const handlePinPersistenceChange = async (
+    event: React.ChangeEvent<HTMLInputElement>
+  ) => {
+    const enabled = event.currentTarget.checked;
+    setPinPersistence(enabled);
+
+    try {
+      await invoke("set_pin_persistence", { enabled });
+      setStatus(t("settings.status.updated"));
+    } catch {
+      setPinPersistence(!enabled);
+      setStatus(t("settings.status.updateFailed"));
+    }
+  };
+
   const chooseSaveDirectory = async () => {
     const selectedPath = await open({
       directory: true,
@@ -442,6 +583,47 @@ function App() {
     setStatus(t("settings.status.updated"));
   };
 
+  const applyWatermarkSigning = (info: WatermarkSigningInfo) => {
+    setWatermarkSigning(info);
+    setHmacSecretDraft("");
+    setTrustedKeysDraft(info.trustedPublicKeys.join("\n"));
+    return info;
+  };
+
+  const loadWatermarkSigning = async () =>
+    applyWatermarkSigning(
+      await invoke<WatermarkSigningInfo>("get_watermark_signing")
+    );
+
+  const copyWatermarkPublicKey = async () => {
+    try {
+      const info = watermarkSigning ?? (await loadWatermarkSigning());
+      await invoke("copy_text_to_clipboard", { text: info.publicKey });
+      setStatus(t("settings.watermark.publicKeyCopied"));
+    } catch (error) {
+      console.error("[xshot][settings] copy public key failed", error);
+      setStatus(t("settings.status.updateFailed"));
+    }
+  };
+
+  const saveWatermarkSigning = async () => {
+    try {
+      applyWatermarkSigning(
+        await invoke<WatermarkSigningInfo>("set_watermark_signing", {
+          hmacSecret: hmacSecretDraft || null,
+          trustedPublicKeys: trustedKeysDraft
+            .split("\n")
+            .map((key) => key.trim())
+            .filter(Boolean),
+        })
+      );
+      setStatus(t("settings.status.updated"));
+    } catch (error) {
+      console.error("[xshot][settings] save watermark signing failed", error);
+      setStatus(String(error));
+    }
+  };
+
   const handleHiddenWatermarkFileChange = async (
     event: React.ChangeEvent<HTMLInputElement>
   ) => {
@@ -454,7 +636,10 @@ function App() {
       message: t("settings.watermark.detecting"),
     });
     try {
-      const decoded = await decodeHiddenWatermarkFromFile(file);
+      const decoded = await decodeHiddenWatermarkFromFile(
+        file,
+        deepWatermarkScan
+      );
       setHiddenWatermarkDetection(
         decoded
           ? {
@@ -477,6 +662,109 @@ function App() {
     }
   };
 
+  const handleWatermarkScan = async () => {
+    if (watermarkScan.status === "detecting") {
+      await invoke("cancel_watermark_scan");
+      return;
+    }
+
+    const directory = await open({
+      directory: true,
+      multiple: false,
+      title: t("settings.watermark.scanFolder"),
+    });
+    if (typeof directory !== "string") return;
+    const reportPath = await save({
+      defaultPath: "watermark-report.csv",
+      filters: [
+        { name: "CSV", extensions: ["csv"] },
+        { name: "JSON", extensions: ["json"] },
+      ],
+    });
+    if (!reportPath) return;
+
+    setWatermarkScan({
+      status: "detecting",
+      message: t("settings.watermark.scanning", { processed: 0, total: 0 }),
+    });
+    const unlisten = await listen<WatermarkScanProgress>(
+      "watermark-scan-progress",
+      (event) => {
+        setWatermarkScan({
+          status: "detecting",
+          message: t("settings.watermark.scanning", {
+            processed: event.payload.processed,
+            total: event.payload.total,
+          }),
+        });
+      }
+    );
+    try {
+      const report = await invoke<WatermarkScanReport>(
+        "scan_hidden_watermarks",
+        { directory, reportPath, deep: deepWatermarkScan }
+      );
+      setWatermarkScan({
+        status: report.found > 0 ? "detected" : "empty",
+        message: t(
+          report.cancelled
+            ? "settings.watermark.scanCancelled"
+            : "settings.watermark.scanDone",
+          { found: report.found, scanned: report.scanned }
+        ),
+      });
+    } catch (error) {
+      console.warn("Failed to scan folder for hidden watermarks:", error);
+      setWatermarkScan({
+        status: "failed",
+        message: t("settings.watermark.scanFailed"),
+      });
+    } finally {
+      unlisten();
+    }
+  };
+
+  const handlePdfCombine = async () => {
+    if (pdfCombine.status === "detecting") return;
+
+    const selected = await open({
+      multiple: true,
+      title: t("settings.pdf.combine"),
+      filters: [
+        { name: "Images", extensions: ["png", "jpg", "jpeg", "webp"] },
+      ],
+    });
+    const paths = Array.isArray(selected) ? selected : [];
+    if (paths.length === 0) return;
+    const output = await save({
+      defaultPath: `xshot-${Date.now()}.pdf`,
+      filters: [{ name: "PDF", extensions: ["pdf"] }],
+    });
+    if (!output) return;
+
+    setPdfCombine({
+      status: "detecting",
+      message: t("settings.pdf.combining"),
+    });
+    try {
+      const summary = await exportPdfFromFiles(
+        paths,
+        output,
+        getPdfOptions(settings)
+      );
+      setPdfCombine({
+        status: "detected",
+        message: t("settings.pdf.combined", { pages: summary.pages }),
+      });
+    } catch (error) {
+      console.warn("Failed to combine images into PDF:", error);
+      setPdfCombine({
+        status: "failed",
+        message: t("settings.pdf.combineFailed"),
+      });
+    }
+  };
+
   const permissionRows = [
     {
       kind: "accessibility" as const,
@@ -738,6 +1026,266 @@ function App() {
               </label>
             </div>
 
+            <div className="settings-row">
+              <div className="settings-row-icon">
+                <Pin size={17} />
+              </div>
+              <div className="settings-row-copy">
+                <div className="settings-row-title">
+                  {t("settings.restorePins")}
+                </div>
+                <p>{t("settings.restorePinsHint")}</p>
+              </div>
+              <label className="switch">
+                <input
+                  type="checkbox"
+                  checked={pinPersistence}
+                  aria-label={t("settings.restorePins")}
+                  onChange={handlePinPersistenceChange}
+                />
+                <span />
+              </label>
+            </div>
+
+            <div className="settings-row">
+              <div className="settings-row-icon">
+                <Monitor size={17} />
+              </div>
+              <div className="settings-row-copy">
+                <div className="settings-row-title">
+                  {t("settings.spanMonitors")}
+                </div>
+                <p>{t("settings.spanMonitorsHint")}</p>
+              </div>
+              <label className="switch">
+                <input
+                  type="checkbox"
+                  checked={settings.spanMonitors}
+                  aria-label={t("settings.spanMonitors")}
+                  onChange={(event) => {
+                    applySettings({ spanMonitors: event.currentTarget.checked });
+                    setStatus(t("settings.status.updated"));
+                  }}
+                />
+                <span />
+              </label>
+            </div>
+
+            <div className="settings-row">
+              <div className="settings-row-icon">
+                <MousePointer2 size={17} />
+              </div>
+              <div className="settings-row-copy">
+                <div className="settings-row-title">
+                  {t("settings.includeCursor")}
+                </div>
+                <p>{t("settings.includeCursorHint")}</p>
+              </div>
+              <label className="switch">
+                <input
+                  type="checkbox"
+                  checked={settings.includeCursor}
+                  aria-label={t("settings.includeCursor")}
+                  onChange={(event) => {
+                    applySettings({
+                      includeCursor: event.currentTarget.checked,
+                    });
+                    setStatus(t("settings.status.updated"));
+                  }}
+                />
+                <span />
+              </label>
+            </div>
+
+            <div className="settings-row">
+              <div className="settings-row-icon">
+                <Zap size={17} />
+              </div>
+              <div className="settings-row-copy">
+                <div className="settings-row-title">
+                  {t("settings.prewarmCapture")}
+                </div>
+                <p>{t("settings.prewarmCaptureHint")}</p>
+              </div>
+              <label className="switch">
+                <input
+                  type="checkbox"
+                  checked={settings.prewarmCapture}
+                  aria-label={t("settings.prewarmCapture")}
+                  onChange={handlePrewarmCaptureChange}
+                />
+                <span />
+              </label>
+            </div>
+
+            <div className="settings-row">
+              <div className="settings-row-icon">
+                <MousePointerClick size={17} />
+              </div>
+              <div className="settings-row-copy">
+                <div className="settings-row-title">
+                  {t("settings.captureInput")}
+                </div>
+                <p>{t("settings.captureInputHint")}</p>
+              </div>
+              <label className="switch">
+                <input
+                  type="checkbox"
+                  checked={settings.captureInput}
+                  aria-label={t("settings.captureInput")}
+                  onChange={handleCaptureInputChange}
+                />
+                <span />
+              </label>
+            </div>
+
+            <div className="settings-row">
+              <div className="settings-row-icon">
+                <Video size={17} />
+              </div>
+              <div className="settings-row-copy">
+                <div className="settings-row-title">
+                  {t("settings.recording")}
+                </div>
+                <p>{t("settings.recordingHint")}</p>
+              </div>
+              <div className="select-wrap">
+                <Settings2 size={15} />
+                <select
+                  value={settings.recordingFormat}
+                  aria-label={t("settings.recordingFormat")}
+                  onChange={(event) => {
+                    applySettings({
+                      recordingFormat: event.currentTarget
+                        .value as RecordingFormat,
+                    });
+                    setStatus(t("settings.status.updated"));
+                  }}
+                >
+                  {RECORDING_FORMATS.map((format) => (
+                    <option key={format} value={format}>
+                      {RECORDING_FORMAT_LABELS[format]}
+                    </option>
+                  ))}
+                </select>
+              </div>
+              <div className="select-wrap">
+                <Settings2 size={15} />
+                <select
+                  value={settings.recordingFps}
+                  aria-label={t("settings.recordingFps")}
+                  onChange={(event) => {
+                    applySettings({
+                      recordingFps: Number(event.currentTarget.value),
+                    });
+                    setStatus(t("settings.status.updated"));
+                  }}
+                >
+                  {RECORDING_FPS_OPTIONS.map((fps) => (
+                    <option key={fps} value={fps}>
+                      {fps} fps
+                    </option>
+                  ))}
+                </select>
+              </div>
+            </div>
+
+            <div className="settings-row stacked watermark-row">
+              <div className="settings-row-main watermark-row-main">
+                <div className="settings-row-icon">
+                  <FileDown size={17} />
+                </div>
+                <div className="settings-row-copy">
+                  <div className="settings-row-title">
+                    {t("settings.pdf.title")}
+                  </div>
+                  <p>{t("settings.pdf.hint")}</p>
+                </div>
+                <label className="switch">
+                  <input
+                    type="checkbox"
+                    checked={settings.pdfTextLayer}
+                    aria-label={t("settings.pdf.textLayer")}
+                    onChange={(event) => {
+                      applySettings({
+                        pdfTextLayer: event.currentTarget.checked,
+                      });
+                      setStatus(t("settings.status.updated"));
+                    }}
+                  />
+                  <span />
+                </label>
+              </div>
+              <div className="watermark-controls">
+                <div className="watermark-options">
+                  <div className="select-wrap watermark-select">
+                    <Settings2 size={15} />
+                    <select
+                      value={settings.pdfPageSize}
+                      aria-label={t("settings.pdf.pageSize")}
+                      onChange={(event) => {
+                        applySettings({
+                          pdfPageSize: event.currentTarget
+                            .value as PdfPageSize,
+                        });
+                        setStatus(t("settings.status.updated"));
+                      }}
+                    >
+                      {PDF_PAGE_SIZES.map((pageSize) => (
+                        <option key={pageSize} value={pageSize}>
+                          {t(`settings.pdf.${pageSize}`)}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                  <div className="select-wrap watermark-select">
+                    <Settings2 size={15} />
+                    <select
+                      value={settings.pdfMargin}
+                      aria-label={t("settings.pdf.margin")}
+                      onChange={(event) => {
+                        applySettings({
+                          pdfMargin: Number(event.currentTarget.value),
+                        });
+                        setStatus(t("settings.status.updated"));
+                      }}
+                    >
+                      {PDF_MARGIN_OPTIONS.map((margin) => (
+                        <option key={margin} value={margin}>
+                          {t("settings.pdf.marginValue", { margin })}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                </div>
+                <div className="watermark-detect-row">
+                  <button
+                    className="inline-action-button"
+                    type="button"
+                    disabled={pdfCombine.status === "detecting"}
+                    onClick={() => void handlePdfCombine()}
+                  >
+                    <Files size={15} />
+                    <span>{t("settings.pdf.combine")}</span>
+                  </button>
+                  <span
+                    className={[
+                      "watermark-detection-result",
+                      pdfCombine.status !== "idle"
+                        ? `is-${pdfCombine.status}`
+                        : "",
+                    ]
+                      .filter(Boolean)
+                      .join(" ")}
+                    title={pdfCombine.message || undefined}
+                    aria-live="polite"
+                  >
+                    {pdfCombine.message || t("settings.pdf.combineHint")}
+                  </span>
+                </div>
+              </div>
+            </div>
+
             <div className="settings-row stacked watermark-row">
               <div className="settings-row-main watermark-row-main">
                 <div className="settings-row-icon">
@@ -856,6 +1404,74 @@ function App() {
                     updateHiddenWatermark({ text: event.currentTarget.value })
                   }
                 />
+                <div className="watermark-options">
+                  <div className="select-wrap watermark-select">
+                    <ShieldCheck size={15} />
+                    <select
+                      value={settings.hiddenWatermark.signature}
+                      disabled={!settings.hiddenWatermark.enabled}
+                      aria-label={t("settings.watermark.signature")}
+                      onChange={(event) =>
+                        updateHiddenWatermark({
+                          signature: event.currentTarget
+                            .value as WatermarkSignature,
+                        })
+                      }
+                    >
+                      {WATERMARK_SIGNATURES.map((signature) => (
+                        <option key={signature} value={signature}>
+                          {t(WATERMARK_SIGNATURE_LABEL_KEYS[signature])}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                  <button
+                    className="inline-action-button"
+                    type="button"
+                    title={watermarkSigning?.publicKey}
+                    onClick={() => void copyWatermarkPublicKey()}
+                  >
+                    <Copy size={15} />
+                    <span>{t("settings.watermark.copyPublicKey")}</span>
+                  </button>
+                </div>
+                {settings.hiddenWatermark.signature !== "none" ? (
+                  <div className="watermark-signing">
+                    <input
+                      className="settings-text-input"
+                      type="password"
+                      value={hmacSecretDraft}
+                      autoComplete="off"
+                      aria-label={t("settings.watermark.hmacSecret")}
+                      placeholder={
+                        watermarkSigning?.hasHmacSecret
+                          ? t("settings.watermark.hmacSecretSet")
+                          : t("settings.watermark.hmacSecret")
+                      }
+                      onChange={(event) =>
+                        setHmacSecretDraft(event.currentTarget.value)
+                      }
+                    />
+                    <textarea
+                      className="settings-text-input"
+                      value={trustedKeysDraft}
+                      spellCheck={false}
+                      aria-label={t("settings.watermark.trustedKeys")}
+                      placeholder={t("settings.watermark.trustedKeys")}
+                      onChange={(event) =>
+                        setTrustedKeysDraft(event.currentTarget.value)
+                      }
+                    />
+                    <button
+                      className="inline-action-button"
+                      type="button"
+                      onClick={() => void saveWatermarkSigning()}
+                    >
+                      <ShieldCheck size={15} />
+                      <span>{t("settings.watermark.saveSigning")}</span>
+                    </button>
+                  </div>
+                ) : null}
                 <div className="watermark-detect-row">
                   <button
                     className="inline-action-button"
@@ -894,6 +1510,45 @@ function App() {
                     }
                   />
                 </div>
+                <div className="watermark-detect-row">
+                  <button
+                    className="inline-action-button"
+                    type="button"
+                    onClick={() => void handleWatermarkScan()}
+                  >
+                    <FolderSearch size={15} />
+                    <span>
+                      {watermarkScan.status === "detecting"
+                        ? t("settings.watermark.scanCancel")
+                        : t("settings.watermark.scanFolder")}
+                    </span>
+                  </button>
+                  <span
+                    className={[
+                      "watermark-detection-result",
+                      watermarkScan.status !== "idle"
+                        ? `is-${watermarkScan.status}`
+                        : "",
+                    ]
+                      .filter(Boolean)
+                      .join(" ")}
+                    title={watermarkScan.message || undefined}
+                    aria-live="polite"
+                  >
+                    {watermarkScan.message ||
+                      t("settings.watermark.scanFolderHint")}
+                  </span>
+                </div>
+                <label className="watermark-deep-scan">
+                  <input
+                    type="checkbox"
+                    checked={deepWatermarkScan}
+                    onChange={(event) =>
+                      setDeepWatermarkScan(event.currentTarget.checked)
+                    }
+                  />
+                  <span>{t("settings.watermark.deepScan")}</span>
+                </label>
               </div>
             </div>
 
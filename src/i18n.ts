@@ -28,6 +28,40 @@ export const resources = {
         macOnly: "仅 macOS",
         autoStart: "开机自启",
         autoStartHint: "登录系统后自动启动 xshot",
+        restorePins: "恢复贴图",
+        restorePinsHint: "退出时保留贴图窗口，下次启动时自动恢复",
+        spanMonitors: "跨屏截图",
+        spanMonitorsHint: "将所有显示器拼接为一张画布，选区可跨越屏幕",
+        includeCursor: "截取鼠标指针",
+        includeCursorHint: "在截图中保留当前鼠标指针，便于制作教程",
+        prewarmCapture: "预热截图",
+        prewarmCaptureHint:
+          "在后台持续保留最新画面，按下快捷键后立即显示，会占用更多资源",
+        captureInput: "显示按键与点击",
+        captureInputHint:
+          "在截图上标出最近的按键和鼠标点击，适合制作教程；记录仅保存在内存中",
+        recording: "录屏",
+        recordingHint:
+          "MP4 和 WebM 需要系统已安装 ffmpeg，录制时可在托盘中暂停或停止",
+        recordingFormat: "录屏格式",
+        recordingFps: "录屏帧率",
+        pdf: {
+          title: "PDF 导出",
+          hint:
+            "长截图会自动分页，并尽量在空白处断开；开启后附带可搜索、可复制的 OCR 文字层（仅 macOS）",
+          textLayer: "添加 OCR 文字层",
+          pageSize: "纸张大小",
+          margin: "页边距",
+          marginValue: "边距 {{margin}} mm",
+          a4: "A4",
+          letter: "Letter",
+          fit: "适应图片",
+          combine: "将多张图片合并为 PDF",
+          combineHint: "按选择顺序合并，每张图片从新的一页开始",
+          combining: "正在生成 PDF...",
+          combined: "已生成 {{pages}} 页 PDF",
+          combineFailed: "生成 PDF 失败",
+        },
         defaultSaveDirectory: "默认保存位置",
         defaultSaveDirectoryHint: "下载截图时保存到这里",
         defaultSaveDirectoryEmpty: "未设置，默认保存到下载目录",
@@ -48,6 +82,24 @@ export const resources = {
           detected: "识别到：{{text}}",
           notDetected: "未识别到暗水印",
           detectFailed: "识别失败",
+          scanFolder: "批量扫描文件夹",
+          scanFolderHint: "扫描文件夹并导出报告",
+          deepScan: "深度检测：也识别缩放或裁剪过的图片，速度较慢",
+          signature: "暗水印签名",
+          signatureNone: "不签名",
+          signatureEd25519: "设备签名 (Ed25519)",
+          signatureHmac: "组织签名 (HMAC)",
+          copyPublicKey: "复制公钥",
+          publicKeyCopied: "公钥已复制",
+          hmacSecret: "组织 HMAC 密钥",
+          hmacSecretSet: "已设置密钥，留空保持不变",
+          trustedKeys: "受信任的公钥，每行一个",
+          saveSigning: "保存签名设置",
+          scanCancel: "取消扫描",
+          scanning: "正在扫描 {{processed}}/{{total}}",
+          scanDone: "已扫描 {{scanned}} 张，{{found}} 张含暗水印",
+          scanCancelled: "已取消，扫描 {{scanned}} 张，{{found}} 张含暗水印",
+          scanFailed: "扫描失败",
           repeatDiagonal: "倾斜平铺",
           repeatHorizontal: "水平平铺",
           topLeft: "左上角",
@@ -104,6 +156,9 @@ export const resources = {
           ocr: "文字识别",
           translateOverlay: "翻译并覆盖原文",
           download: "下载",
+          saveProject: "保存为可编辑工程",
+          exportPdf: "导出为 PDF",
+          record: "录制选区",
           pin: "固定到屏幕",
           copy: "复制到剪贴板",
         },
@@ -146,6 +201,7 @@ export const resources = {
           translate: "翻译",
           showOriginal: "原文",
           copy: "复制",
+          export: "导出为 Markdown、HTML、hOCR、ALTO 或 JSON",
           close: "关闭",
           empty: "暂无识别结果",
           failed: "文字识别失败",
@@ -154,6 +210,7 @@ export const resources = {
           translateFailed: "翻译失败",
           qrCodes: "二维码",
         },
+        redactionFailed: "马赛克处理失败，已取消导出：{{error}}",
       },
     },
   },
@@ -182,6 +239,43 @@ export const resources = {
         macOnly: "macOS only",
         autoStart: "Launch at login",
         autoStartHint: "Start xshot after signing in",
+        restorePins: "Restore pinned images",
+        restorePinsHint:
+          "Keep pinned windows across restarts and reopen them at launch",
+        spanMonitors: "Span all displays",
+        spanMonitorsHint:
+          "Stitch every display into one canvas so selections can cross screens",
+        includeCursor: "Capture mouse pointer",
+        includeCursorHint:
+          "Keep the current pointer in captures, handy for tutorials",
+        prewarmCapture: "Prewarm captures",
+        prewarmCaptureHint:
+          "Keep a recent frame in the background so the overlay appears instantly; uses more CPU and memory",
+        captureInput: "Show keystrokes and clicks",
+        captureInputHint:
+          "Mark recent key presses and mouse clicks on captures for tutorials; kept in memory only",
+        recording: "Screen recording",
+        recordingHint:
+          "MP4 and WebM need ffmpeg installed; pause or stop from the tray while recording",
+        recordingFormat: "Recording format",
+        recordingFps: "Recording frame rate",
+        pdf: {
+          title: "PDF export",
+          hint:
+            "Long screenshots are split across pages at blank rows where possible; turn on to add a searchable, selectable OCR text layer (macOS only)",
+          textLayer: "Add OCR text layer",
+          pageSize: "Page size",
+          margin: "Page margin",
+          marginValue: "{{margin}} mm margin",
+          a4: "A4",
+          letter: "Letter",
+          fit: "Fit image",
+          combine: "Combine images into PDF",
+          combineHint: "Images are combined in order, each on a new page",
+          combining: "Creating PDF...",
+          combined: "Created a {{pages}}-page PDF",
+          combineFailed: "Failed to create PDF",
+        },
         defaultSaveDirectory: "Default save location",
         defaultSaveDirectoryHint: "Downloaded captures are saved here",
         defaultSaveDirectoryEmpty: "Not set, using Downloads",
@@ -202,6 +296,24 @@ export const resources = {
           detected: "Detected: {{text}}",
           notDetected: "No hidden watermark detected",
           detectFailed: "Detection failed",
+          scanFolder: "Scan a folder",
+          scanFolderHint: "Scan every image and save a report",
+          deepScan: "Deep scan: also find rescaled or cropped copies (slower)",
+          signature: "Hidden watermark signature",
+          signatureNone: "Unsigned",
+          signatureEd25519: "Device (Ed25519)",
+          signatureHmac: "Organization (HMAC)",
+          copyPublicKey: "Copy public key",
+          publicKeyCopied: "Public key copied",
+          hmacSecret: "Organization HMAC secret",
+          hmacSecretSet: "Secret set, leave empty to keep it",
+          trustedKeys: "Trusted public keys, one per line",
+          saveSigning: "Save signing keys",
+          scanCancel: "Cancel scan",
+          scanning: "Scanning {{processed}}/{{total}}",
+          scanDone: "{{found}} of {{scanned}} images watermarked",
+          scanCancelled: "Cancelled: {{found}} of {{scanned}} images watermarked",
+          scanFailed: "Scan failed",
           repeatDiagonal: "Diagonal repeat",
           repeatHorizontal: "Horizontal repeat",
           topLeft: "Top left",
@@ -259,6 +371,9 @@ export const resources = {
           ocr: "Text recognition",
           translateOverlay: "Translate over original text",
           download: "Download",
+          saveProject: "Save as editable project",
+          exportPdf: "Export as PDF",
+          record: "Record selection",
           pin: "Pin to screen",
           copy: "Copy to clipboard",
         },
@@ -306,6 +421,7 @@ export const resources = {
           translate: "Translate",
           showOriginal: "Original",
           copy: "Copy",
+          export: "Export as Markdown, HTML, hOCR, ALTO or JSON",
           close: "Close",
           empty: "No recognition result",
           failed: "Text recognition failed",
@@ -314,6 +430,8 @@ export const resources = {
           translateFailed: "Translation failed",
           qrCodes: "QR Codes",
         },
+        redactionFailed:
+          "Mosaic redaction failed, so nothing was exported: {{error}}",
       },
     },
   },
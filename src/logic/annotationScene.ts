@@ -0,0 +1,248 @@
+import { invoke } from "@tauri-apps/api/core";
+import * as fabric from "fabric";
+
+export const ANNOTATION_SCENE_VERSION = 1;
+
+export type ScenePoint = {
+  x: number;
+  y: number;
+};
+
+export type SceneAnnotation =
+  | {
+      type: "rect";
+      x: number;
+      y: number;
+      width: number;
+      height: number;
+      color: string;
+      strokeWidth: number;
+    }
+  | {
+      type: "line" | "arrow";
+      from: ScenePoint;
+      to: ScenePoint;
+      color: string;
+      strokeWidth: number;
+    }
+  | {
+      type: "pen";
+      points: ScenePoint[];
+      color: string;
+      strokeWidth: number;
+    }
+  | {
+      type: "marker";
+      center: ScenePoint;
+      number: number;
+      color: string;
+    }
+  | {
+      type: "text";
+      x: number;
+      y: number;
+      text: string;
+      color: string;
+      fontSize: number;
+      width?: number;
+      background?: string;
+      lineHeight?: number;
+    }
+  | {
+      type: "mosaic";
+      x: number;
+      y: number;
+      width: number;
+      height: number;
+      blockSize?: number;
+    };
+
+export type AnnotationScene = {
+  version: number;
+  width: number;
+  height: number;
+  annotations: SceneAnnotation[];
+};
+
+export type SceneBounds = {
+  left: number;
+  top: number;
+  width: number;
+  height: number;
+};
+
+type SceneSourceData = {
+  role?: string;
+  tool?: string;
+  color?: string;
+  strokeWidth?: number;
+  fontSize?: number;
+};
+
+const DEFAULT_COLOR = "#ff4d4f";
+const DEFAULT_STROKE_WIDTH = 4;
+
+function colorOf(value: unknown, fallback: string) {
+  return typeof value === "string" && value ? value : fallback;
+}
+
+function roundCoordinate(value: number) {
+  return Math.round(value * 100) / 100;
+}
+
+/** Maps a point in the object's centre-relative space into the scene. */
+function toScene(
+  object: fabric.Object,
+  bounds: SceneBounds,
+  x: number,
+  y: number
+): ScenePoint {
+  const point = fabric.util.transformPoint(
+    new fabric.Point(x, y),
+    object.calcTransformMatrix()
+  );
+  return {
+    x: roundCoordinate(point.x - bounds.left),
+    y: roundCoordinate(point.y - bounds.top),
+  };
+}
+
+function sceneBox(object: fabric.Object, bounds: SceneBounds) {
+  const halfWidth = (object.width ?? 0) / 2;
+  const halfHeight = (object.height ?? 0) / 2;
+  const topLeft = toScene(object, bounds, -halfWidth, -halfHeight);
+  const bottomRight = toScene(object, bounds, halfWidth, halfHeight);
+  return {
+    x: Math.min(topLeft.x, bottomRight.x),
+    y: Math.min(topLeft.y, bottomRight.y),
+    width: roundCoordinate(Math.abs(bottomRight.x - topLeft.x)),
+    height: roundCoordinate(Math.abs(bottomRight.y - topLeft.y)),
+  };
+}
+
+/** End point of every path command, in scene coordinates. */
+function pathPoints(path: fabric.Path, bounds: SceneBounds) {
+  const offset = path.pathOffset;
+  return path.path
+    .filter((command) => command.length >= 3)
+    .map((command) => {
+      const values = command as unknown as (string | number)[];
+      const x = Number(values[values.length - 2]);
+      const y = Number(values[values.length - 1]);
+      return toScene(path, bounds, x - offset.x, y - offset.y);
+    });
+}
+
+function serializeAnnotation(
+  object: fabric.Object,
+  data: SceneSourceData,
+  bounds: SceneBounds
+): SceneAnnotation | null {
+  const color = colorOf(data.color ?? object.stroke, DEFAULT_COLOR);
+  const strokeWidth =
+    data.strokeWidth ?? object.strokeWidth ?? DEFAULT_STROKE_WIDTH;
+
+  switch (data.tool) {
+    case "rect":
+      return { type: "rect", ...sceneBox(object, bounds), color, strokeWidth };
+    case "line": {
+      if (!(object instanceof fabric.Line)) return null;
+      const { x1, y1, x2, y2 } = object.calcLinePoints();
+      return {
+        type: "line",
+        from: toScene(object, bounds, x1, y1),
+        to: toScene(object, bounds, x2, y2),
+        color,
+        strokeWidth,
+      };
+    }
+    case "arrow": {
+      if (!(object instanceof fabric.Path)) return null;
+      // makeArrowPath starts with the shaft: M start L end.
+      const [from, to] = pathPoints(object, bounds);
+      if (!from || !to) return null;
+      return { type: "arrow", from, to, color, strokeWidth };
+    }
+    case "pen": {
+      if (!(object instanceof fabric.Path)) return null;
+      const points = pathPoints(object, bounds);
+      if (points.length === 0) return null;
+      return { type: "pen", points, color, strokeWidth };
+    }
+    case "sequence": {
+      if (!(object instanceof fabric.Group)) return null;
+      const label = object
+        .getObjects()
+        .find((item): item is fabric.Text => item instanceof fabric.Text);
+      const number = Number.parseInt(label?.text ?? "", 10);
+      if (!Number.isFinite(number)) return null;
+      const circle = object
+        .getObjects()
+        .find((item) => item instanceof fabric.Circle);
+      return {
+        type: "marker",
+        center: toScene(object, bounds, 0, 0),
+        number,
+        color: colorOf(data.color ?? circle?.fill, "#1677ff"),
+      };
+    }
+    case "text": {
+      if (!(object instanceof fabric.IText)) return null;
+      const box = sceneBox(object, bounds);
+      return {
+        type: "text",
+        x: box.x,
+        y: box.y,
+        text: object.text ?? "",
+        color: colorOf(data.color ?? object.fill, DEFAULT_COLOR),
+        fontSize: data.fontSize ?? object.fontSize,
+        width: object instanceof fabric.Textbox ? box.width : undefined,
+        background: object.backgroundColor || undefined,
+        lineHeight: object.lineHeight,
+      };
+    }
+    case "mosaic-rect":
+      return { type: "mosaic", ...sceneBox(object, bounds) };
+    default:
+      return null;
+  }
+}
+
+/**
+ * Serializes the editor's visible annotations, in stacking order, into a scene
+ * whose coordinates are relative to `bounds` (the selection).
+ */
+export function buildAnnotationScene(
+  objects: fabric.Object[],
+  bounds: SceneBounds,
+  mosaicBlockSize?: number
+): AnnotationScene {
+  const annotations = objects.flatMap((object) => {
+    const data = (object as fabric.Object & { data?: SceneSourceData }).data;
+    if (data?.role !== "annotation" || !object.visible) return [];
+    const annotation = serializeAnnotation(object, data, bounds);
+    if (annotation?.type === "mosaic" && mosaicBlockSize) {
+      annotation.blockSize = mosaicBlockSize;
+    }
+    return annotation ? [annotation] : [];
+  });
+
+  return {
+    version: ANNOTATION_SCENE_VERSION,
+    width: bounds.width,
+    height: bounds.height,
+    annotations,
+  };
+}
+
+/** Rasterizes `scene` over the image in `blob` at the image's resolution. */
+export async function renderAnnotationScene(
+  blob: Blob,
+  scene: AnnotationScene
+) {
+  const rendered = await invoke<ArrayBuffer>("render_annotation_scene", {
+    blobData: new Uint8Array(await blob.arrayBuffer()),
+    scene,
+  });
+  return new Blob([rendered], { type: "image/png" });
+}
@@ -0,0 +1,222 @@
+// Mirrors `src-tauri/src/frame.rs`: little-endian header of magic[4] version:u8 format:u8
+// reserved:u16 width:u32 height:u32 stride:u32, followed by the payload.
+const FRAME_MAGIC = [0x58, 0x53, 0x52, 0x46]; // "XSRF"
+const FRAME_VERSION = 1;
+const FRAME_HEADER_LENGTH = 20;
+const FRAME_FORMAT_RGBA = 1;
+const FRAME_FORMAT_QOI = 2;
+
+export type CaptureFrameFormat = "png" | "rgba" | "qoi";
+
+export type CaptureFrameSource = HTMLImageElement | HTMLCanvasElement;
+
+export type DecodedCaptureFrame = {
+  source: CaptureFrameSource;
+  url: string | null;
+  width: number;
+  height: number;
+  format: CaptureFrameFormat;
+};
+
+type FrameHeader = {
+  format: number;
+  width: number;
+  height: number;
+  stride: number;
+};
+
+function readFrameHeader(bytes: ArrayBuffer): FrameHeader | null {
+  if (bytes.byteLength < FRAME_HEADER_LENGTH) return null;
+
+  const view = new DataView(bytes);
+  const hasMagic = FRAME_MAGIC.every(
+    (byte, index) => view.getUint8(index) === byte
+  );
+  if (!hasMagic || view.getUint8(4) !== FRAME_VERSION) return null;
+
+  return {
+    format: view.getUint8(5),
+    width: view.getUint32(8, true),
+    height: view.getUint32(12, true),
+    stride: view.getUint32(16, true),
+  };
+}
+
+function packRgbaRows(
+  bytes: ArrayBuffer,
+  offset: number,
+  width: number,
+  height: number,
+  stride: number
+) {
+  const rowLength = width * 4;
+  if (stride < rowLength || bytes.byteLength < offset + stride * height) {
+    throw new Error("Capture frame is truncated");
+  }
+  if (stride === rowLength) {
+    return new Uint8ClampedArray(bytes, offset, rowLength * height);
+  }
+
+  const source = new Uint8Array(bytes, offset);
+  const pixels = new Uint8ClampedArray(rowLength * height);
+  for (let row = 0; row < height; row += 1) {
+    pixels.set(
+      source.subarray(row * stride, row * stride + rowLength),
+      row * rowLength
+    );
+  }
+  return pixels;
+}
+
+// https://qoiformat.org/qoi-specification.pdf
+function decodeQoi(bytes: Uint8Array, width: number, height: number) {
+  if (
+    bytes.length < 22 ||
+    bytes[0] !== 0x71 ||
+    bytes[1] !== 0x6f ||
+    bytes[2] !== 0x69 ||
+    bytes[3] !== 0x66
+  ) {
+    throw new Error("Invalid QOI stream");
+  }
+
+  const pixels = new Uint8ClampedArray(width * height * 4);
+  const index = new Uint8Array(64 * 4);
+  const end = bytes.length - 8;
+  let position = 14;
+  let r = 0;
+  let g = 0;
+  let b = 0;
+  let a = 255;
+  let run = 0;
+
+  for (let offset = 0; offset < pixels.length; offset += 4) {
+    if (run > 0) {
+      run -= 1;
+    } else if (position < end) {
+      const byte = bytes[position++];
+
+      if (byte === 0xfe) {
+        r = bytes[position++];
+        g = bytes[position++];
+        b = bytes[position++];
+      } else if (byte === 0xff) {
+        r = bytes[position++];
+        g = bytes[position++];
+        b = bytes[position++];
+        a = bytes[position++];
+      } else if ((byte & 0xc0) === 0x00) {
+        const slot = byte * 4;
+        r = index[slot];
+        g = index[slot + 1];
+        b = index[slot + 2];
+        a = index[slot + 3];
+      } else if ((byte & 0xc0) === 0x40) {
+        r = (r + ((byte >> 4) & 0x03) - 2) & 0xff;
+        g = (g + ((byte >> 2) & 0x03) - 2) & 0xff;
+        b = (b + (byte & 0x03) - 2) & 0xff;
+      } else if ((byte & 0xc0) === 0x80) {
+        const next = bytes[position++];
+        const greenDelta = (byte & 0x3f) - 32;
+        r = (r + greenDelta - 8 + ((next >> 4) & 0x0f)) & 0xff;
+        g = (g + greenDelta) & 0xff;
+        b = (b + greenDelta - 8 + (next & 0x0f)) & 0xff;
+      } else {
+        run = byte & 0x3f;
+      }
+
+      const slot = ((r * 3 + g * 5 + b * 7 + a * 11) % 64) * 4;
+      index[slot] = r;
+      index[slot + 1] = g;
+      index[slot + 2] = b;
+      index[slot + 3] = a;
+    }
+
+    pixels[offset] = r;
+    pixels[offset + 1] = g;
+    pixels[offset + 2] = b;
+    pixels[offset + 3] = a;
+  }
+
+  return pixels;
+}
+
+function pixelsToCanvas(
+  pixels: Uint8ClampedArray,
+  width: number,
+  height: number
+) {
+  const canvas = document.createElement("canvas");
+  canvas.width = width;
+  canvas.height = height;
+  const context = canvas.getContext("2d");
+  if (!context) throw new Error("Failed to create capture canvas");
+  context.putImageData(new ImageData(pixels, width, height), 0, 0);
+  return canvas;
+}
+
+export function getCaptureFrameSize(source: CaptureFrameSource) {
+  if (source instanceof HTMLImageElement) {
+    return {
+      width: source.naturalWidth || source.width,
+      height: source.naturalHeight || source.height,
+    };
+  }
+  return { width: source.width, height: source.height };
+}
+
+// Decodes bytes from `capture_fullscreen`: a framed RGBA/QOI capture, or an encoded image
+// (PNG from the fallback paths) that the browser decodes itself.
+export async function decodeCaptureFrame(
+  bytes: ArrayBuffer
+): Promise<DecodedCaptureFrame> {
+  const header = readFrameHeader(bytes);
+
+  if (header?.format === FRAME_FORMAT_RGBA) {
+    const pixels = packRgbaRows(
+      bytes,
+      FRAME_HEADER_LENGTH,
+      header.width,
+      header.height,
+      header.stride
+    );
+    return {
+      source: pixelsToCanvas(pixels, header.width, header.height),
+      url: null,
+      width: header.width,
+      height: header.height,
+      format: "rgba",
+    };
+  }
+
+  if (header?.format === FRAME_FORMAT_QOI) {
+    const pixels = decodeQoi(
+      new Uint8Array(bytes, FRAME_HEADER_LENGTH),
+      header.width,
+      header.height
+    );
+    return {
+      source: pixelsToCanvas(pixels, header.width, header.height),
+      url: null,
+      width: header.width,
+      height: header.height,
+      format: "qoi",
+    };
+  }
+
+  if (header) {
+    throw new Error(`Unsupported capture frame format ${header.format}`);
+  }
+
+  const url = URL.createObjectURL(new Blob([bytes], { type: "image/png" }));
+  const image = new Image();
+  image.src = url;
+  await image.decode();
+  return {
+    source: image,
+    url,
+    width: image.naturalWidth,
+    height: image.naturalHeight,
+    format: "png",
+  };
+}
@@ -0,0 +1,52 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { AppSettings, PdfPageSize } from "./settings";
+
+export type PdfOptions = {
+  pageSize: PdfPageSize;
+  landscape?: boolean;
+  /** Page margin in millimetres. */
+  margin: number;
+  /** Adds an invisible OCR text layer (macOS only). */
+  textLayer: boolean;
+  title?: string;
+};
+
+export type PdfSummary = {
+  path: string;
+  pages: number;
+  textBlocks: number;
+};
+
+export function getPdfOptions(settings: AppSettings): PdfOptions {
+  return {
+    pageSize: settings.pdfPageSize,
+    margin: settings.pdfMargin,
+    textLayer: settings.pdfTextLayer,
+  };
+}
+
+/** Writes `image` to `path` as a PDF, splitting long captures across pages. */
+export async function exportPdf(
+  image: Blob,
+  path: string,
+  options: PdfOptions
+) {
+  return invoke<PdfSummary>("export_pdf", {
+    blobData: new Uint8Array(await image.arrayBuffer()),
+    path,
+    options,
+  });
+}
+
+/** Combines the images at `paths`, in order, into one PDF at `output`. */
+export async function exportPdfFromFiles(
+  paths: string[],
+  output: string,
+  options: PdfOptions
+) {
+  return invoke<PdfSummary>("export_pdf_from_files", {
+    paths,
+    output,
+    options,
+  });
+}
@@ -0,0 +1,82 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { AnnotationScene } from "./annotationScene";
+import type { AppSettings } from "./settings";
+
+export const PROJECT_EXTENSION = "xshot";
+
+export type CropRect = {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+};
+
+export type ProjectWatermark = {
+  visible?: {
+    text: string;
+    placement: string;
+    opacity: number;
+  };
+  hidden?: string;
+  signature?: "ed25519" | "hmac";
+};
+
+export type ProjectDocument<Ocr = unknown> = {
+  captureId?: string | null;
+  crop?: CropRect | null;
+  scene: AnnotationScene;
+  watermark?: ProjectWatermark;
+  ocr?: Ocr | null;
+};
+
+export type PreparedProject<Ocr = unknown> = ProjectDocument<Ocr> & {
+  frameCrop: CropRect;
+};
+
+/** The enabled watermarks, in the shape the Rust exporter applies them. */
+export function getProjectWatermark(settings: AppSettings): ProjectWatermark {
+  const { visibleWatermark, hiddenWatermark } = settings;
+  return {
+    visible:
+      visibleWatermark.enabled && visibleWatermark.text.trim()
+        ? {
+            text: visibleWatermark.text,
+            placement: visibleWatermark.placement,
+            opacity: visibleWatermark.opacity,
+          }
+        : undefined,
+    hidden:
+      hiddenWatermark.enabled && hiddenWatermark.text.trim()
+        ? hiddenWatermark.text
+        : undefined,
+    signature:
+      hiddenWatermark.enabled && hiddenWatermark.signature !== "none"
+        ? hiddenWatermark.signature
+        : undefined,
+  };
+}
+
+/**
+ * Saves `original` with the project, or, for an imported capture, the image
+ * it was imported from. The crop of an imported capture is in editor frame
+ * pixels and is mapped back to that image in Rust.
+ */
+export async function saveProject(
+  path: string,
+  original: Blob | { importedCaptureId: string },
+  project: ProjectDocument
+) {
+  return invoke<string>("save_project", {
+    path,
+    ...(original instanceof Blob
+      ? { blobData: new Uint8Array(await original.arrayBuffer()) }
+      : original),
+    project,
+  });
+}
+
+export async function takePreparedProject<Ocr>(captureId: string) {
+  return invoke<PreparedProject<Ocr> | null>("take_prepared_project", {
+    captureId,
+  });
+}
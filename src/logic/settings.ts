@@ -7,6 +7,9 @@ export type VisibleWatermarkPlacement =
   | "bottom-left"
   | "bottom-right";
 
+export type RecordingFormat = "gif" | "apng" | "webp" | "mp4" | "webm";
+export type PdfPageSize = "a4" | "letter" | "fit";
+
 export type VisibleWatermarkSettings = {
   enabled: boolean;
   text: string;
@@ -14,13 +17,25 @@ export type VisibleWatermarkSettings = {
   opacity: number;
 };
 
+export type WatermarkSignature = "none" | "ed25519" | "hmac";
+
 export type HiddenWatermarkSettings = {
   enabled: boolean;
   text: string;
+  signature: WatermarkSignature;
 };
 
 export type AppSettings = {
   showDockIcon: boolean;
+  spanMonitors: boolean;
+  includeCursor: boolean;
+  prewarmCapture: boolean;
+  captureInput: boolean;
+  recordingFormat: RecordingFormat;
+  recordingFps: number;
+  pdfPageSize: PdfPageSize;
+  pdfMargin: number;
+  pdfTextLayer: boolean;
   defaultSaveDirectory: string;
   language: AppLanguage;
   visibleWatermark: VisibleWatermarkSettings;
@@ -32,6 +47,23 @@ export const WATERMARK_TEXT_MAX_LENGTH = 160;
 export const WATERMARK_OPACITY_MIN = 0.08;
 export const WATERMARK_OPACITY_MAX = 0.35;
 
+export const RECORDING_FORMATS: RecordingFormat[] = [
+  "gif",
+  "apng",
+  "webp",
+  "mp4",
+  "webm",
+];
+export const RECORDING_FPS_OPTIONS = [5, 10, 15, 24, 30];
+export const PDF_PAGE_SIZES: PdfPageSize[] = ["a4", "letter", "fit"];
+export const PDF_MARGIN_OPTIONS = [0, 5, 10, 15, 20];
+
+export const WATERMARK_SIGNATURES: WatermarkSignature[] = [
+  "none",
+  "ed25519",
+  "hmac",
+];
+
 export const VISIBLE_WATERMARK_PLACEMENTS: VisibleWatermarkPlacement[] = [
   "repeat-diagonal",
   "repeat-horizontal",
@@ -51,6 +83,15 @@ export const SUPPORTED_LANGUAGES: Array<{
 
 export const DEFAULT_SETTINGS: AppSettings = {
   showDockIcon: true,
+  spanMonitors: false,
+  includeCursor: false,
+  prewarmCapture: false,
+  captureInput: false,
+  recordingFormat: "gif",
+  recordingFps: 10,
+  pdfPageSize: "a4",
+  pdfMargin: 10,
+  pdfTextLayer: false,
   defaultSaveDirectory: "",
   language: "zh-CN",
   visibleWatermark: {
@@ -62,6 +103,7 @@ export const DEFAULT_SETTINGS: AppSettings = {
   hiddenWatermark: {
     enabled: false,
     text: "",
+    signature: "none",
   },
 };
 
@@ -69,12 +111,32 @@ function isAppLanguage(value: unknown): value is AppLanguage {
   return SUPPORTED_LANGUAGES.some((language) => language.value === value);
 }
 
+function isRecordingFormat(value: unknown): value is RecordingFormat {
+  return RECORDING_FORMATS.some((format) => format === value);
+}
+
+function isRecordingFps(value: unknown): value is number {
+  return RECORDING_FPS_OPTIONS.some((fps) => fps === value);
+}
+
+function isPdfPageSize(value: unknown): value is PdfPageSize {
+  return PDF_PAGE_SIZES.some((pageSize) => pageSize === value);
+}
+
+function isPdfMargin(value: unknown): value is number {
+  return PDF_MARGIN_OPTIONS.some((margin) => margin === value);
+}
+
 function isVisibleWatermarkPlacement(
   value: unknown
 ): value is VisibleWatermarkPlacement {
   return VISIBLE_WATERMARK_PLACEMENTS.some((placement) => placement === value);
 }
 
+function isWatermarkSignature(value: unknown): value is WatermarkSignature {
+  return WATERMARK_SIGNATURES.some((signature) => signature === value);
+}
+
 function isRecord(value: unknown): value is Record<string, unknown> {
   return Boolean(value && typeof value === "object" && !Array.isArray(value));
 }
@@ -122,6 +184,9 @@ function normalizeHiddenWatermarkSettings(
         ? rawWatermark.enabled
         : DEFAULT_SETTINGS.hiddenWatermark.enabled,
     text: normalizeWatermarkText(rawWatermark.text),
+    signature: isWatermarkSignature(rawWatermark.signature)
+      ? rawWatermark.signature
+      : DEFAULT_SETTINGS.hiddenWatermark.signature,
   };
 }
 
@@ -175,6 +240,38 @@ export function getSettings(): AppSettings {
       typeof rawSettings.showDockIcon === "boolean"
         ? rawSettings.showDockIcon
         : DEFAULT_SETTINGS.showDockIcon,
+    spanMonitors:
+      typeof rawSettings.spanMonitors === "boolean"
+        ? rawSettings.spanMonitors
+        : DEFAULT_SETTINGS.spanMonitors,
+    includeCursor:
+      typeof rawSettings.includeCursor === "boolean"
+        ? rawSettings.includeCursor
+        : DEFAULT_SETTINGS.includeCursor,
+    prewarmCapture:
+      typeof rawSettings.prewarmCapture === "boolean"
+        ? rawSettings.prewarmCapture
+        : DEFAULT_SETTINGS.prewarmCapture,
+    captureInput:
+      typeof rawSettings.captureInput === "boolean"
+        ? rawSettings.captureInput
+        : DEFAULT_SETTINGS.captureInput,
+    recordingFormat: isRecordingFormat(rawSettings.recordingFormat)
+      ? rawSettings.recordingFormat
+      : DEFAULT_SETTINGS.recordingFormat,
+    recordingFps: isRecordingFps(rawSettings.recordingFps)
+      ? rawSettings.recordingFps
+      : DEFAULT_SETTINGS.recordingFps,
+    pdfPageSize: isPdfPageSize(rawSettings.pdfPageSize)
+      ? rawSettings.pdfPageSize
+      : DEFAULT_SETTINGS.pdfPageSize,
+    pdfMargin: isPdfMargin(rawSettings.pdfMargin)
+      ? rawSettings.pdfMargin
+      : DEFAULT_SETTINGS.pdfMargin,
+    pdfTextLayer:
+      typeof rawSettings.pdfTextLayer === "boolean"
+        ? rawSettings.pdfTextLayer
+        : DEFAULT_SETTINGS.pdfTextLayer,
     defaultSaveDirectory:
       typeof rawSettings.defaultSaveDirectory === "string"
         ? rawSettings.defaultSaveDirectory
@@ -217,6 +314,15 @@ export function updateSettings(patch: Partial<AppSettings>) {
   };
   const nextStoredSettings: Partial<AppSettings> = {
     showDockIcon: nextSettings.showDockIcon,
+    spanMonitors: nextSettings.spanMonitors,
+    includeCursor: nextSettings.includeCursor,
+    prewarmCapture: nextSettings.prewarmCapture,
+    captureInput: nextSettings.captureInput,
+    recordingFormat: nextSettings.recordingFormat,
+    recordingFps: nextSettings.recordingFps,
+    pdfPageSize: nextSettings.pdfPageSize,
+    pdfMargin: nextSettings.pdfMargin,
+    pdfTextLayer: nextSettings.pdfTextLayer,
     defaultSaveDirectory: nextSettings.defaultSaveDirectory,
     visibleWatermark: nextSettings.visibleWatermark,
     hiddenWatermark: nextSettings.hiddenWatermark,
@@ -1,7 +1,11 @@
 import { register, unregister } from "@tauri-apps/plugin-global-shortcut";
 import { invoke } from "@tauri-apps/api/core";
+import type { CaptureFrameFormat } from "./frame";
+import { getSettings } from "./settings";
 
 export const DEFAULT_SHORTCUT = "Alt+X";
+// Raw frames skip the PNG encode in Rust and the image decode in the webview.
+const CAPTURE_FRAME_FORMAT: CaptureFrameFormat = "rgba";
 const SHORTCUT_STORAGE_KEY = "xshot.shortcut";
 
 let registeredShortcut: string | null = null;
@@ -47,8 +51,19 @@ export async function startCapture(
   const captureId = nextCaptureId();
   const triggeredAtMs = performance.timeOrigin + triggeredAt;
   logCaptureTrigger(captureId, source, triggeredAt, "triggered");
+  const { spanMonitors, includeCursor, captureInput } = getSettings();
   try {
-    await invoke("start_capture", { captureId, source, triggeredAtMs });
+    await invoke("start_capture", {
+      captureId,
+      source,
+      triggeredAtMs,
+      options: {
+        spanMonitors,
+        includeCursor,
+        frameFormat: CAPTURE_FRAME_FORMAT,
+        inputOverlay: captureInput,
+      },
+    });
     logCaptureTrigger(captureId, source, triggeredAt, "rust_command_done");
   } catch (error) {
     logCaptureTrigger(captureId, source, triggeredAt, "rust_command_failed");
@@ -56,6 +71,18 @@ export async function startCapture(
   }
 }
 
+// Prewarmed frames are only reused when they match the format `startCapture` asks for.
+export async function setCapturePrewarm(enabled: boolean) {
+  await invoke("set_capture_prewarm", {
+    enabled,
+    frameFormat: CAPTURE_FRAME_FORMAT,
+  });
+}
+
+export async function setInputHistory(enabled: boolean) {
+  await invoke("set_input_history", { enabled });
+}
+
 async function registerAccelerator(shortcut: string) {
   await register(shortcut, async (event) => {
     if (event.state !== "Pressed") return;
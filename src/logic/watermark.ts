@@ -1,3 +1,4 @@
+import { invoke } from "@tauri-apps/api/core";
 import {
   WATERMARK_TEXT_MAX_LENGTH,
   type AppSettings,
@@ -779,6 +780,40 @@ function decodeHiddenWatermarkFromCanvas(canvas: HTMLCanvasElement) {
   );
 }
 
+function getWatermarkSignature(settings: AppSettings) {
+  const { signature } = settings.hiddenWatermark;
+  return signature === "none" ? null : signature;
+}
+
+// The Rust renderer shares its output with CLI exports, and its hidden encoder
+// also writes the sync tiles that let rescaled or cropped copies be detected.
+async function applyWatermarksInRust(
+  blob: Blob,
+  settings: AppSettings,
+  hasVisibleWatermark: boolean,
+  hasHiddenWatermark: boolean
+) {
+  let imageBytes = await blob.arrayBuffer();
+  if (hasVisibleWatermark) {
+    imageBytes = await invoke<ArrayBuffer>("render_visible_watermark", {
+      blobData: new Uint8Array(imageBytes),
+      options: {
+        text: settings.visibleWatermark.text,
+        placement: settings.visibleWatermark.placement,
+        opacity: settings.visibleWatermark.opacity,
+      },
+    });
+  }
+  if (hasHiddenWatermark) {
+    imageBytes = await invoke<ArrayBuffer>("embed_hidden_watermark", {
+      blobData: new Uint8Array(imageBytes),
+      text: settings.hiddenWatermark.text,
+      signature: getWatermarkSignature(settings),
+    });
+  }
+  return new Blob([imageBytes], { type: "image/png" });
+}
+
 export async function applyWatermarksToBlob(blob: Blob, settings: AppSettings) {
   const hasVisibleWatermark =
     settings.visibleWatermark.enabled &&
@@ -789,6 +824,19 @@ export async function applyWatermarksToBlob(blob: Blob, settings: AppSettings) {
 
   if (!hasVisibleWatermark && !hasHiddenWatermark) return blob;
 
+  try {
+    return await applyWatermarksInRust(
+      blob,
+      settings,
+      hasVisibleWatermark,
+      hasHiddenWatermark
+    );
+  } catch (error) {
+    // The canvas encoder cannot sign, so a signed export must not fall back to it.
+    if (hasHiddenWatermark && getWatermarkSignature(settings)) throw error;
+    console.warn("Failed to apply watermarks in Rust:", error);
+  }
+
   const image = await imageFromBlob(blob);
   const width = image.naturalWidth || image.width;
   const height = image.naturalHeight || image.height;
@@ -803,7 +851,20 @@ export async function applyWatermarksToBlob(blob: Blob, settings: AppSettings) {
   return (await canvasToPngBlob(canvas)) || blob;
 }
 
-export async function decodeHiddenWatermarkFromBlob(blob: Blob) {
+export async function decodeHiddenWatermarkFromBlob(blob: Blob, deep = false) {
+  try {
+    const decoded = await invoke<DecodedHiddenWatermark | null>(
+      "detect_hidden_watermark",
+      {
+        blobData: new Uint8Array(await blob.arrayBuffer()),
+        deep,
+      }
+    );
+    if (decoded) return decoded;
+  } catch (error) {
+    console.warn("Failed to detect hidden watermark in Rust:", error);
+  }
+
   const image = await imageFromBlob(blob);
   const width = image.naturalWidth || image.width;
   const height = image.naturalHeight || image.height;
@@ -815,6 +876,6 @@ export async function decodeHiddenWatermarkFromBlob(blob: Blob) {
   return decodeHiddenWatermarkFromCanvas(canvas);
 }
 
-export function decodeHiddenWatermarkFromFile(file: File) {
-  return decodeHiddenWatermarkFromBlob(file);
+export function decodeHiddenWatermarkFromFile(file: File, deep = false) {
+  return decodeHiddenWatermarkFromBlob(file, deep);
 }
@@ -8,7 +8,7 @@ import {
   type PointerEvent,
   type WheelEvent,
 } from "react";
-import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+import { invoke } from "@tauri-apps/api/core";
 import {
   getCurrentWindow,
   LogicalPosition,
@@ -19,11 +19,15 @@ import { useTranslation } from "react-i18next";
 import { getSettings } from "../logic/settings";
 
 type PinWindowPayload = {
-  imagePath: string;
+  imageUrl: string;
   imageWidth: number;
   imageHeight: number;
   initialWidth: number;
   initialHeight: number;
+  opacity: number;
+  clickThrough: boolean;
+  locked: boolean;
+  alwaysOnTop: boolean;
 };
 
 type ContextMenuState = {
@@ -56,10 +60,7 @@ export default function PinWindow() {
   const [contextMenu, setContextMenu] = useState<ContextMenuState | null>(null);
   const zoomRef = useRef(1);
   const isClosingRef = useRef(false);
-  const imageUrl = useMemo(
-    () => (payload ? convertFileSrc(payload.imagePath) : ""),
-    [payload]
-  );
+  const imageUrl = payload?.imageUrl ?? "";
 
   useEffect(() => {
     zoomRef.current = zoom;
@@ -92,6 +93,19 @@ export default function PinWindow() {
     };
   }, [win]);
 
+  useEffect(() => {
+    const unlisten = win.listen<PinWindowPayload>(
+      "pin-window-state",
+      (event) => {
+        setPayload(event.payload);
+      }
+    );
+
+    return () => {
+      void unlisten.then((dispose) => dispose());
+    };
+  }, [win]);
+
   const closeWindow = useCallback(async () => {
     if (isClosingRef.current) return;
     isClosingRef.current = true;
@@ -106,7 +120,10 @@ export default function PinWindow() {
     if (!imageUrl) return;
 
     const blobData = await imageUrlToBytes(imageUrl);
-    await invoke("copy_to_clipboard", { blobData });
+    await invoke("copy_to_clipboard", {
+      blobData,
+      formats: { png: true, file: true, html: true },
+    });
     setContextMenu(null);
   }, [imageUrl]);
 
@@ -245,6 +262,7 @@ export default function PinWindow() {
 
   const handleWheel = (event: WheelEvent<HTMLDivElement>) => {
     event.preventDefault();
+    if (payload?.locked) return;
     const factor = Math.exp(-event.deltaY * ZOOM_STEP);
     void zoomAroundPoint(factor, event.clientX, event.clientY);
   };
@@ -259,6 +277,7 @@ export default function PinWindow() {
     }
 
     setContextMenu(null);
+    if (payload?.locked) return;
     void win.startDragging();
   };
 
@@ -278,7 +297,10 @@ export default function PinWindow() {
       onPointerDown={handlePointerDown}
       onWheel={handleWheel}
     >
-      <div className="pin-window-frame">
+      <div
+        className="pin-window-frame"
+        style={{ opacity: payload.opacity ?? 1 }}
+      >
         <img
           className="pin-window-image"
           src={imageUrl}
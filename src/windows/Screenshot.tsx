@@ -8,6 +8,7 @@ import {
 import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
 import { getCurrentWindow } from "@tauri-apps/api/window";
+import { save } from "@tauri-apps/plugin-dialog";
 import { register, unregister } from "@tauri-apps/plugin-global-shortcut";
 import { useTranslation } from "react-i18next";
 import {
@@ -15,6 +16,7 @@ import {
   Copy,
   Download,
   Eraser,
+  FileDown,
   FileText,
   Grid3X3,
   Languages,
@@ -25,15 +27,33 @@ import {
   Pointer,
   Redo2,
   RectangleHorizontal,
+  Save,
   Slash,
   Type,
   Undo2,
+  Video,
   X,
   createLucideIcon,
   type LucideIcon,
 } from "lucide-react";
 import * as fabric from "fabric";
+import {
+  buildAnnotationScene,
+  type SceneAnnotation,
+} from "../logic/annotationScene";
 import { cursorManager, ToolType } from "../logic/cursor";
+import {
+  decodeCaptureFrame,
+  getCaptureFrameSize,
+  type CaptureFrameSource,
+} from "../logic/frame";
+import { exportPdf, getPdfOptions } from "../logic/pdf";
+import {
+  getProjectWatermark,
+  PROJECT_EXTENSION,
+  saveProject,
+  takePreparedProject,
+} from "../logic/project";
 import { getSettings } from "../logic/settings";
 import { applyWatermarksToBlob } from "../logic/watermark";
 
@@ -73,6 +93,8 @@ type RawCaptureWindowRegion = {
   is_focused: boolean;
   title: string;
   app_name: string;
+  coordinate_space: "logical" | "physical";
+  scale_factor: number;
 };
 type CaptureStartPayload = {
   id: number;
@@ -84,9 +106,36 @@ type CaptureStartPayload = {
   scaleFactor: number;
   isPrimary: boolean;
   name: string;
+  physicalX: number;
+  physicalY: number;
+  physicalWidth: number;
+  physicalHeight: number;
   captureId: string;
   source: string;
   triggeredAtMs: number;
+  frameCapturedAtMs: number;
+  framePrewarmed: boolean;
+  inputEvents: CaptureInputEvent[];
+};
+type CaptureInputEvent = {
+  kind: "key" | "click";
+  label: string;
+  x: number;
+  y: number;
+  coordinateSpace: "logical" | "physical";
+  timestampMs: number;
+};
+type VirtualDesktopSegment = {
+  label: string;
+  monitorId: number;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  desktopX: number;
+  desktopY: number;
+  scaleFactor: number;
+  coordinateSpace: "logical" | "physical";
 };
 type CaptureHoverPointPayload = {
   label: string;
@@ -177,6 +226,7 @@ type HistoryAction =
   | { type: "remove"; objects: fabric.Object[] };
 
 const MIN_SELECTION_SIZE = 18;
+const VIRTUAL_DESKTOP_LABEL_SUFFIX = "_virtual";
 const DEFAULT_STROKE_WIDTH = 4;
 const STROKE_WIDTH_OPTIONS = [2, 4, 6, 8];
 const DEFAULT_TEXT_SIZE = 28;
@@ -192,6 +242,8 @@ const TOOLBAR_SAFE_TOP = 76;
 const DEFAULT_TOOLBAR_WIDTH = 560;
 const DEFAULT_TOOLBAR_HEIGHT = 54;
 const WINDOW_CLICK_DRAG_THRESHOLD = 5;
+// Prewarmed frames older than this at trigger time are grabbed again before editing.
+const PREWARMED_FRAME_MAX_AGE_MS = 2000;
 const LONG_CAPTURE_MANUAL_CAPTURE_INTERVAL = 150;
 const LONG_CAPTURE_SCROLL_SETTLE_DELAY = 250;
 const LONG_CAPTURE_MAX_HEIGHT = 30000;
@@ -201,6 +253,7 @@ const LONG_CAPTURE_MIN_SHIFT_RATIO = 0.1;
 const LONG_CAPTURE_MIN_OVERLAP_RATIO = 0.2;
 const LONG_CAPTURE_OFFSET_SCORE_BIAS = 6;
 const LONG_CAPTURE_SHORTCUTS = ["Enter", "Escape"];
+const IMPORTED_CAPTURE_SOURCES = ["file", "clipboard", "project"];
 const LONG_CAPTURE_PREVIEW_WIDTH = 152;
 const LONG_CAPTURE_PREVIEW_HEIGHT = 240;
 const LONG_CAPTURE_PANEL_WIDTH = 380;
@@ -427,6 +480,19 @@ async function imageFromBlob(blob: Blob) {
   return { image, url };
 }
 
+async function drawBlobsStacked(base: Blob, overlay: Blob) {
+  const bottom = await imageFromBlob(base);
+  const top = await imageFromBlob(overlay);
+  try {
+    const canvas = imageToCanvas(bottom.image);
+    canvas.getContext("2d")?.drawImage(top.image, 0, 0);
+    return canvasToBlob(canvas);
+  } finally {
+    URL.revokeObjectURL(bottom.url);
+    URL.revokeObjectURL(top.url);
+  }
+}
+
 function clampByte(value: number) {
   return Math.max(0, Math.min(255, Math.round(value)));
 }
@@ -477,7 +543,7 @@ function sampleAverageColor(canvas: HTMLCanvasElement, bounds: OcrBounds) {
 }
 
 function cropSelectionFrame(
-  image: HTMLImageElement,
+  image: CaptureFrameSource,
   bounds: Bounds,
   scale: number
 ) {
@@ -979,6 +1045,157 @@ function makeArrowPath(
   );
 }
 
+function makeSequenceMarker(point: Point, number: number, color: string) {
+  const circle = new fabric.Circle({
+    left: 0,
+    top: 0,
+    radius: 13,
+    fill: color,
+    originX: "center",
+    originY: "center",
+    selectable: false,
+    evented: false,
+  });
+  const label = new fabric.Text(String(number), {
+    left: 0,
+    top: 0,
+    fill: "#ffffff",
+    fontSize: number > 99 ? 10 : 14,
+    fontWeight: "700",
+    fontFamily: "Inter, Arial, sans-serif",
+    originX: "center",
+    originY: "center",
+    selectable: false,
+    evented: false,
+  });
+  return new fabric.Group([circle, label], {
+    left: point.x,
+    top: point.y,
+    originX: "center",
+    originY: "center",
+    selectable: false,
+    evented: false,
+  });
+}
+
+/**
+ * Rebuilds an editor object from a saved scene annotation. `toCanvas` maps
+ * scene points onto the canvas and `scale` sizes strokes and text to match.
+ * Mosaics are rebuilt from the capture itself, so they are left to the caller.
+ */
+function makeSceneObject(
+  annotation: SceneAnnotation,
+  toCanvas: (point: Point) => Point,
+  scale: number
+): { object: fabric.Object; data: Omit<AnnotationData, "role"> } | null {
+  switch (annotation.type) {
+    case "rect": {
+      const topLeft = toCanvas(annotation);
+      const strokeWidth = annotation.strokeWidth * scale;
+      return {
+        object: new fabric.Rect({
+          left: topLeft.x,
+          top: topLeft.y,
+          width: annotation.width * scale,
+          height: annotation.height * scale,
+          fill: "transparent",
+          stroke: annotation.color,
+          strokeWidth,
+        }),
+        data: { tool: "rect", color: annotation.color, strokeWidth },
+      };
+    }
+    case "line": {
+      const from = toCanvas(annotation.from);
+      const to = toCanvas(annotation.to);
+      const strokeWidth = annotation.strokeWidth * scale;
+      return {
+        object: new fabric.Line([from.x, from.y, to.x, to.y], {
+          stroke: annotation.color,
+          strokeWidth,
+          strokeLineCap: "round",
+        }),
+        data: { tool: "line", color: annotation.color, strokeWidth },
+      };
+    }
+    case "arrow": {
+      const strokeWidth = annotation.strokeWidth * scale;
+      return {
+        object: makeArrowPath(
+          toCanvas(annotation.from),
+          toCanvas(annotation.to),
+          annotation.color,
+          strokeWidth
+        ),
+        data: { tool: "arrow", color: annotation.color, strokeWidth },
+      };
+    }
+    case "pen": {
+      const points = annotation.points.map(toCanvas);
+      if (points.length === 0) return null;
+      const [first, ...rest] = points;
+      const commands = [`M ${first.x} ${first.y}`];
+      (rest.length > 0 ? rest : [first]).forEach((point) =>
+        commands.push(`L ${point.x} ${point.y}`)
+      );
+      const strokeWidth = annotation.strokeWidth * scale;
+      return {
+        object: new fabric.Path(commands.join(" "), {
+          fill: "",
+          stroke: annotation.color,
+          strokeWidth,
+          strokeLineCap: "round",
+          strokeLineJoin: "round",
+        }),
+        data: { tool: "pen", color: annotation.color, strokeWidth },
+      };
+    }
+    case "marker":
+      return {
+        object: makeSequenceMarker(
+          toCanvas(annotation.center),
+          annotation.number,
+          annotation.color
+        ),
+        data: { tool: "sequence", color: annotation.color },
+      };
+    case "text": {
+      const topLeft = toCanvas(annotation);
+      const fontSize = annotation.fontSize * scale;
+      const options = {
+        left: topLeft.x,
+        top: topLeft.y,
+        fill: annotation.color,
+        fontSize,
+        fontFamily: "Inter, Arial, sans-serif",
+        fontWeight: "700",
+        editable: true,
+        ...(annotation.lineHeight ? { lineHeight: annotation.lineHeight } : {}),
+      };
+      const object =
+        annotation.width === undefined
+          ? new fabric.IText(annotation.text, options)
+          : new fabric.Textbox(annotation.text, {
+              ...options,
+              width: annotation.width * scale,
+              backgroundColor: annotation.background ?? "",
+              splitByGrapheme: true,
+            });
+      return {
+        object,
+        data: {
+          tool: "text",
+          source: annotation.background ? "translation-overlay" : undefined,
+          color: annotation.color,
+          fontSize,
+        },
+      };
+    }
+    default:
+      return null;
+  }
+}
+
 function getAnnotationData(object: fabric.Object | null | undefined) {
   return (object as fabric.Object & { data?: AnnotationData })?.data;
 }
@@ -995,7 +1212,7 @@ export default function ScreenshotWindow() {
   const longCapturePanelRef = useRef<HTMLDivElement | null>(null);
   const toolbarRef = useRef<HTMLDivElement | null>(null);
   const fabricCanvasRef = useRef<fabric.Canvas | null>(null);
-  const sourceImageRef = useRef<HTMLImageElement | null>(null);
+  const sourceImageRef = useRef<CaptureFrameSource | null>(null);
   const sourceUrlRef = useRef<string | null>(null);
   const bgImgRef = useRef<fabric.FabricImage | null>(null);
   const selectionImgRef = useRef<fabric.FabricImage | null>(null);
@@ -1057,6 +1274,7 @@ export default function ScreenshotWindow() {
     showingTranslation: false,
   }));
   const [translationOverlayBusy, setTranslationOverlayBusy] = useState(false);
+  const [exportError, setExportError] = useState<string | null>(null);
   const [longCapture, setLongCapture] = useState<LongCaptureState>({
     status: "idle",
     frameCount: 0,
@@ -1807,37 +2025,7 @@ export default function ScreenshotWindow() {
     const number = markerNumberRef.current;
     markerNumberRef.current += 1;
 
-    const circle = new fabric.Circle({
-      left: 0,
-      top: 0,
-      radius: 13,
-      fill: markerColorRef.current,
-      originX: "center",
-      originY: "center",
-      selectable: false,
-      evented: false,
-    });
-    const label = new fabric.Text(String(number), {
-      left: 0,
-      top: 0,
-      fill: "#ffffff",
-      fontSize: number > 99 ? 10 : 14,
-      fontWeight: "700",
-      fontFamily: "Inter, Arial, sans-serif",
-      originX: "center",
-      originY: "center",
-      selectable: false,
-      evented: false,
-    });
-    const marker = new fabric.Group([circle, label], {
-      left: point.x,
-      top: point.y,
-      originX: "center",
-      originY: "center",
-      selectable: false,
-      evented: false,
-    });
-
+    const marker = makeSequenceMarker(point, number, markerColorRef.current);
     addAnnotation(marker, { tool: "sequence", color: markerColorRef.current });
   };
 
@@ -1970,8 +2158,7 @@ export default function ScreenshotWindow() {
     if (!canvas || !selectionImg || !sourceBounds || !sourceImage) return;
 
     const scale =
-      sourceBounds.width /
-      Math.max(1, sourceImage.naturalWidth || sourceImage.width);
+      sourceBounds.width / Math.max(1, getCaptureFrameSize(sourceImage).width);
     const cropX = (bounds.left - sourceBounds.left) / scale;
     const cropY = (bounds.top - sourceBounds.top) / scale;
     selectionImg.set({
@@ -2032,6 +2219,52 @@ export default function ScreenshotWindow() {
     requestAnimationFrame(() => syncToolbarPosition(nextBounds));
   };
 
+  const mapVirtualDesktopSelection = async (
+    bounds: Bounds
+  ): Promise<VirtualDesktopSegment[] | null> => {
+    const monitor = currentCaptureMonitorRef.current;
+    const canvas = fabricCanvasRef.current;
+    if (
+      !monitor ||
+      !canvas ||
+      !currentWindowLabelRef.current.endsWith(VIRTUAL_DESKTOP_LABEL_SUFFIX)
+    ) {
+      return null;
+    }
+
+    const scaleX = monitor.width / Math.max(1, canvas.getWidth());
+    const scaleY = monitor.height / Math.max(1, canvas.getHeight());
+    return invoke<VirtualDesktopSegment[]>("map_virtual_desktop_selection", {
+      captureId: monitor.captureId,
+      x: bounds.left * scaleX,
+      y: bounds.top * scaleY,
+      width: bounds.width * scaleX,
+      height: bounds.height * scaleY,
+    });
+  };
+
+  // Selections on the virtual desktop are exported at the density of the
+  // densest display they cover, so each display keeps its own detail.
+  const getExportMultiplier = async (bounds: Bounds) => {
+    const monitor = currentCaptureMonitorRef.current;
+    const canvas = fabricCanvasRef.current;
+    try {
+      const segments = await mapVirtualDesktopSelection(bounds);
+      if (!monitor || !canvas || !segments?.length) return 1;
+
+      // Physical desktop units are device pixels already.
+      const density = Math.max(
+        ...segments.map((segment) =>
+          segment.coordinateSpace === "logical" ? segment.scaleFactor : 1
+        )
+      );
+      return (density * monitor.width) / Math.max(1, canvas.getWidth());
+    } catch (error) {
+      console.warn("[xshot][capture][ui] virtual_selection_failed", error);
+      return 1;
+    }
+  };
+
   const removeDraftObject = () => {
     const canvas = fabricCanvasRef.current;
     const object = draftObjectRef.current;
@@ -2223,6 +2456,7 @@ export default function ScreenshotWindow() {
     const bounds = selectionBoundsRef.current;
     if (!canvas || !selectionImg || !bounds) return null;
 
+    const multiplier = await getExportMultiplier(bounds);
     const borderWasVisible = selectionBorder?.visible ?? false;
     try {
       selectionBorder?.set("visible", false);
@@ -2230,15 +2464,86 @@ export default function ScreenshotWindow() {
       canvas.discardActiveObject();
       canvas.requestRenderAll();
 
+      const exportOptions = {
+        left: bounds.left,
+        top: bounds.top,
+        width: bounds.width,
+        height: bounds.height,
+        format: "png" as const,
+        multiplier,
+      };
+      const objects = canvas.getObjects();
+      const mosaics = objects.filter(
+        (object) =>
+          isAnnotation(object) &&
+          getAnnotationData(object)?.tool === "mosaic-rect"
+      );
+      if (mosaics.length === 0) {
+        return await maybeApplyWatermarks(await canvas.toBlob(exportOptions));
+      }
+
+      // The canvas mosaic is only a preview; the exported pixels are redacted
+      // in Rust. Annotations stacked above the last mosaic are drawn back on
+      // afterwards so they stay sharp.
+      const lastMosaicIndex = objects.indexOf(mosaics[mosaics.length - 1]);
+      const overlays = objects
+        .slice(lastMosaicIndex + 1)
+        .filter((object) => isAnnotation(object) && object.visible);
+      overlays.forEach((object) => object.set("visible", false));
+      let base: Blob | null;
+      try {
+        base = await canvas.toBlob(exportOptions);
+      } finally {
+        overlays.forEach((object) => object.set("visible", true));
+      }
+      if (!base) return null;
+
+      let imageBytes: ArrayBuffer;
+      try {
+        imageBytes = await invoke<ArrayBuffer>("redact_regions", {
+          blobData: new Uint8Array(await base.arrayBuffer()),
+          regions: mosaics.map((object) => {
+            // Round both edges outward so partly covered pixels are redacted.
+            const rect = object.getBoundingRect();
+            const x = (rect.left - bounds.left) * multiplier;
+            const y = (rect.top - bounds.top) * multiplier;
+            const left = Math.floor(x);
+            const top = Math.floor(y);
+            const right = Math.ceil(x + rect.width * multiplier);
+            const bottom = Math.ceil(y + rect.height * multiplier);
+            return {
+              x: left,
+              y: top,
+              width: right - left,
+              height: bottom - top,
+            };
+          }),
+          mode: "pixelate",
+          strength: MOSAIC_BLOCK_SIZE,
+        });
+      } catch (error) {
+        // The canvas mosaic can be reversed, so never export it in place of
+        // the redacted pixels.
+        console.warn("Failed to redact mosaic regions:", error);
+        throw new Error(
+          t("screenshot.redactionFailed", { error: String(error) })
+        );
+      }
+      const redacted = new Blob([imageBytes], { type: "image/png" });
+      if (overlays.length === 0) return await maybeApplyWatermarks(redacted);
+
+      const hiddenObjects = objects.filter(
+        (object) => !overlays.includes(object) && object.visible
+      );
+      hiddenObjects.forEach((object) => object.set("visible", false));
+      let overlay: Blob | null;
+      try {
+        overlay = await canvas.toBlob(exportOptions);
+      } finally {
+        hiddenObjects.forEach((object) => object.set("visible", true));
+      }
       return await maybeApplyWatermarks(
-        await canvas.toBlob({
-          left: bounds.left,
-          top: bounds.top,
-          width: bounds.width,
-          height: bounds.height,
-          format: "png",
-          multiplier: 1,
-        })
+        overlay ? await drawBlobsStacked(redacted, overlay) : redacted
       );
     } finally {
       selectionBorder?.set("visible", borderWasVisible);
@@ -2877,8 +3182,17 @@ export default function ScreenshotWindow() {
     if (!blob) return;
 
     const arrayBuffer = await blob.arrayBuffer();
+    const ocrText = ocrPanel.result
+      ? ocrPanel.displayText || getOcrCopyText(ocrPanel.result)
+      : "";
     await invoke("copy_to_clipboard", {
       blobData: new Uint8Array(arrayBuffer),
+      formats: {
+        png: true,
+        file: true,
+        html: true,
+        text: ocrText.trim() ? ocrText : null,
+      },
     });
     await closeCapture();
   };
@@ -2896,6 +3210,35 @@ export default function ScreenshotWindow() {
     await closeCapture();
   };
 
+  const exportCapturePdf = async () => {
+    const blob = await exportSelectionBlob({ watermarked: true });
+    if (!blob) return;
+
+    const path = await save({
+      defaultPath: `xshot-${Date.now()}.pdf`,
+      filters: [{ name: "PDF", extensions: ["pdf"] }],
+    });
+    if (!path) return;
+
+    try {
+      const summary = await exportPdf(blob, path, getPdfOptions(getSettings()));
+      console.info("Exported PDF:", summary);
+      await closeCapture();
+    } catch (error) {
+      console.warn("Failed to export PDF:", error);
+    }
+  };
+
+  const runExport = async (action: () => Promise<void>) => {
+    setExportError(null);
+    try {
+      await action();
+    } catch (error) {
+      console.warn("Failed to export capture:", error);
+      setExportError(error instanceof Error ? error.message : String(error));
+    }
+  };
+
   const pinCapture = async () => {
     const blob = await exportSelectionBlob({ watermarked: true });
     if (!blob) return;
@@ -2908,6 +3251,161 @@ export default function ScreenshotWindow() {
     await closeCapture();
   };
 
+  const getProjectSource = () =>
+    longCaptureResultCanvasRef.current ?? sourceImageRef.current;
+
+  // Imported images sit padded inside the editor frame, so their projects keep
+  // the image they were imported from instead.
+  const getImportedCaptureId = () => {
+    const capture = currentCaptureMonitorRef.current;
+    if (!capture || longCaptureResultCanvasRef.current) return null;
+    return IMPORTED_CAPTURE_SOURCES.includes(capture.source)
+      ? capture.captureId
+      : null;
+  };
+
+  const getProjectOriginal = async (source: CaptureFrameSource) => {
+    const importedCaptureId = getImportedCaptureId();
+    if (importedCaptureId) return { importedCaptureId };
+
+    const size = getCaptureFrameSize(source);
+    const original = makeCanvas(size.width, size.height);
+    const context = original.getContext("2d");
+    if (!context) return null;
+    context.drawImage(source, 0, 0, size.width, size.height);
+    return canvasToBlob(original);
+  };
+
+  const saveCaptureProject = async () => {
+    const canvas = fabricCanvasRef.current;
+    const bounds = selectionBoundsRef.current;
+    const sourceBounds = getSourceDisplayBounds();
+    const source = getProjectSource();
+    if (!canvas || !bounds || !sourceBounds || !source) return;
+
+    const path = await save({
+      defaultPath: `xshot-${Date.now()}.${PROJECT_EXTENSION}`,
+      filters: [{ name: "xshot", extensions: [PROJECT_EXTENSION] }],
+    });
+    if (!path) return;
+
+    const original = await getProjectOriginal(source);
+    if (!original) return;
+
+    const size = getCaptureFrameSize(source);
+    const scale = sourceBounds.width / Math.max(1, size.width);
+    const x = Math.max(
+      0,
+      Math.round((bounds.left - sourceBounds.left) / scale)
+    );
+    const y = Math.max(0, Math.round((bounds.top - sourceBounds.top) / scale));
+    try {
+      const savedPath = await saveProject(path, original, {
+        captureId: currentCaptureMonitorRef.current?.captureId ?? null,
+        crop: {
+          x,
+          y,
+          width: Math.max(1, Math.round(bounds.width / scale)),
+          height: Math.max(1, Math.round(bounds.height / scale)),
+        },
+        scene: buildAnnotationScene(
+          canvas.getObjects(),
+          bounds,
+          MOSAIC_BLOCK_SIZE
+        ),
+        watermark: getProjectWatermark(getSettings()),
+        ocr: ocrPanel.result,
+      });
+      console.info("Saved project:", savedPath);
+    } catch (error) {
+      console.warn("Failed to save project:", error);
+    }
+  };
+
+  const restoreCaptureProject = async (captureId: string) => {
+    const project = await takePreparedProject<OcrScanResult>(captureId);
+    const canvas = fabricCanvasRef.current;
+    const sourceBounds = getSourceDisplayBounds();
+    const sourceImage = sourceImageRef.current;
+    if (!project || !canvas || !sourceBounds || !sourceImage) return;
+
+    const frameScale =
+      sourceBounds.width / Math.max(1, getCaptureFrameSize(sourceImage).width);
+    const { frameCrop, scene } = project;
+    finishSelection({
+      left: sourceBounds.left + frameCrop.x * frameScale,
+      top: sourceBounds.top + frameCrop.y * frameScale,
+      width: frameCrop.width * frameScale,
+      height: frameCrop.height * frameScale,
+    });
+    const bounds = selectionBoundsRef.current;
+    if (!bounds) return;
+
+    const sceneScale = bounds.width / Math.max(1, scene.width);
+    const toCanvas = (point: Point) => ({
+      x: bounds.left + point.x * sceneScale,
+      y: bounds.top + point.y * sceneScale,
+    });
+    for (const annotation of scene.annotations) {
+      if (annotation.type === "mosaic") {
+        const topLeft = toCanvas(annotation);
+        await addMosaicBounds({
+          left: topLeft.x,
+          top: topLeft.y,
+          width: annotation.width * sceneScale,
+          height: annotation.height * sceneScale,
+        });
+        continue;
+      }
+      const restored = makeSceneObject(annotation, toCanvas, sceneScale);
+      if (!restored) continue;
+      canvas.add(markAnnotation(restored.object, restored.data));
+      if (annotation.type === "marker") {
+        markerNumberRef.current = Math.max(
+          markerNumberRef.current,
+          annotation.number + 1
+        );
+      }
+    }
+    bringSelectionHandlesToFront();
+    canvas.requestRenderAll();
+
+    const ocr = project.ocr;
+    if (ocr) {
+      setOcrPanel((current) => ({
+        ...current,
+        status: "ready",
+        result: ocr,
+        displayText: getOcrCopyText(ocr),
+        error: null,
+        showingTranslation: false,
+      }));
+    }
+  };
+
+  const startRecording = async () => {
+    const selection = selectionBoundsRef.current;
+    if (!selection) return;
+
+    const bounds = roundBounds(selection);
+    const windowLabel = currentWindowLabelRef.current;
+    const settings = getSettings();
+    // The overlay has to be gone before the first frame is grabbed.
+    await closeCapture();
+    await invoke("start_recording", {
+      windowLabel,
+      x: bounds.left,
+      y: bounds.top,
+      width: bounds.width,
+      height: bounds.height,
+      format: settings.recordingFormat,
+      fps: settings.recordingFps,
+      directory: settings.defaultSaveDirectory || null,
+    }).catch((error) => {
+      console.warn("Failed to start recording:", error);
+    });
+  };
+
   const setOcrTargetLang = (targetLang: string) => {
     setStoredOcrTargetLanguage(targetLang);
     setOcrPanel((current) => ({
@@ -3023,6 +3521,27 @@ export default function ScreenshotWindow() {
     });
   };
 
+  const exportOcrResult = async () => {
+    const result = ocrPanel.result;
+    if (!result) return;
+
+    const path = await save({
+      defaultPath: `xshot-ocr-${Date.now()}.md`,
+      filters: [
+        { name: "Markdown", extensions: ["md"] },
+        { name: "HTML", extensions: ["html"] },
+        { name: "hOCR", extensions: ["hocr"] },
+        { name: "ALTO XML", extensions: ["xml"] },
+        { name: "JSON", extensions: ["json"] },
+      ],
+    });
+    if (!path) return;
+
+    await invoke("export_ocr_result", { result, path }).catch((error) => {
+      console.warn("Failed to export OCR result:", error);
+    });
+  };
+
   const removeTranslatedOverlay = () => {
     const canvas = fabricCanvasRef.current;
     if (!canvas) return false;
@@ -3554,30 +4073,31 @@ export default function ScreenshotWindow() {
           });
 
           currentStage = "capture_fullscreen_ipc";
+          const frameAgeMs =
+            event.payload.triggeredAtMs - event.payload.frameCapturedAtMs;
+          const refreshFrame =
+            event.payload.framePrewarmed &&
+            frameAgeMs > PREWARMED_FRAME_MAX_AGE_MS;
           const imageBytes = await invoke<ArrayBuffer>("capture_fullscreen", {
             windowLabel: currentWindowLabelRef.current,
             captureId: trace.captureId,
             source: trace.source,
+            refresh: refreshFrame,
           });
           logCaptureTiming(
             trace,
             currentStage,
-            `bytes=${imageBytes.byteLength}`
+            `bytes=${imageBytes.byteLength} prewarmed=${event.payload.framePrewarmed} frame_age_ms=${frameAgeMs.toFixed(1)} refresh=${refreshFrame} input_events=${event.payload.inputEvents.length}`
           );
 
           currentStage = "source_image_decode";
-          const blob = new Blob([imageBytes], { type: "image/png" });
-          const url = URL.createObjectURL(blob);
-          sourceUrlRef.current = url;
-
-          const sourceImage = new Image();
-          sourceImage.src = url;
-          await sourceImage.decode();
-          sourceImageRef.current = sourceImage;
+          const frame = await decodeCaptureFrame(imageBytes);
+          sourceUrlRef.current = frame.url;
+          sourceImageRef.current = frame.source;
           logCaptureTiming(
             trace,
             currentStage,
-            `${sourceImage.naturalWidth}x${sourceImage.naturalHeight}`
+            `${frame.width}x${frame.height} format=${frame.format}`
           );
 
           const canvas = fabricCanvasRef.current;
@@ -3585,8 +4105,8 @@ export default function ScreenshotWindow() {
             throw new Error("Fabric canvas is not initialized");
           }
 
-          currentStage = "fabric_background_from_url";
-          const img = await fabric.FabricImage.fromURL(url);
+          currentStage = "fabric_background_from_source";
+          const img = new fabric.FabricImage(frame.source);
           logCaptureTiming(trace, currentStage);
 
           const scale = canvas.getWidth() / img.width;
@@ -3617,8 +4137,8 @@ export default function ScreenshotWindow() {
           canvas.add(mask);
           logCaptureTiming(trace, currentStage);
 
-          currentStage = "fabric_selection_from_url";
-          const selectionImg = await fabric.FabricImage.fromURL(url);
+          currentStage = "fabric_selection_from_source";
+          const selectionImg = new fabric.FabricImage(frame.source);
           logCaptureTiming(trace, currentStage);
           selectionImg.set({
             left: 0,
@@ -3650,6 +4170,12 @@ export default function ScreenshotWindow() {
           logCaptureTiming(trace, currentStage);
           recordCaptureUiResult(trace, "ready", currentStage);
 
+          if (trace.source === "project") {
+            await restoreCaptureProject(trace.captureId).catch((error) => {
+              console.warn("Failed to restore project:", error);
+            });
+          }
+
           const readyTrace = trace;
           void captureWindowsPromise.then((captureWindows) => {
             windowRegionsRef.current = mapCaptureWindowsToCanvas(
@@ -3741,7 +4267,7 @@ export default function ScreenshotWindow() {
       } else if (event.key === "Backspace" || event.key === "Delete") {
         if (deleteSelectedAnnotation()) event.preventDefault();
       } else if (event.key === "Enter") {
-        await copyToClipboard();
+        await runExport(copyToClipboard);
       } else if (
         (event.metaKey || event.ctrlKey) &&
         event.key.toLowerCase() === "z"
@@ -3861,6 +4387,16 @@ export default function ScreenshotWindow() {
             : undefined
         }
       >
+        {exportError && (
+          <div
+            className={`capture-export-error ${
+              popoverPlacement === "above" ? "below" : "above"
+            }`}
+            role="alert"
+          >
+            {exportError}
+          </div>
+        )}
         {!isLongCaptureResultReady && (
           <div className="toolbar-group">
             {TOOL_BUTTONS.map(({ tool, titleKey, icon: Icon }) => (
@@ -3958,15 +4494,39 @@ export default function ScreenshotWindow() {
             className="tool-button"
             type="button"
             title={t("screenshot.tools.download")}
-            onClick={() => void downloadCapture()}
+            onClick={() => void runExport(downloadCapture)}
           >
             <Download size={18} />
           </button>
+          <button
+            className="tool-button"
+            type="button"
+            title={t("screenshot.tools.saveProject")}
+            onClick={() => void saveCaptureProject()}
+          >
+            <Save size={18} />
+          </button>
+          <button
+            className="tool-button"
+            type="button"
+            title={t("screenshot.tools.exportPdf")}
+            onClick={() => void runExport(exportCapturePdf)}
+          >
+            <FileDown size={18} />
+          </button>
+          <button
+            className="tool-button"
+            type="button"
+            title={t("screenshot.tools.record")}
+            onClick={() => void startRecording()}
+          >
+            <Video size={18} />
+          </button>
           <button
             className="tool-button"
             type="button"
             title={t("screenshot.tools.pin")}
-            onClick={() => void pinCapture()}
+            onClick={() => void runExport(pinCapture)}
           >
             <Pin size={18} />
           </button>
@@ -3974,7 +4534,7 @@ export default function ScreenshotWindow() {
             className="tool-button primary"
             type="button"
             title={t("screenshot.tools.copy")}
-            onClick={() => void copyToClipboard()}
+            onClick={() => void runExport(copyToClipboard)}
           >
             <Check size={18} />
           </button>
@@ -3985,6 +4545,7 @@ export default function ScreenshotWindow() {
     activeTool,
     canRedo,
     canUndo,
+    exportError,
     isLongCaptureActive,
     isLongCaptureResultReady,
     markerColor,
@@ -4075,6 +4636,15 @@ export default function ScreenshotWindow() {
             <Copy size={15} />
             <span>{t("screenshot.ocr.copy")}</span>
           </button>
+          <button
+            className="ocr-icon-button"
+            type="button"
+            title={t("screenshot.ocr.export")}
+            disabled={!ocrPanel.result || isOcrBusy}
+            onClick={() => void exportOcrResult()}
+          >
+            <FileDown size={15} />
+          </button>
         </div>
 
         <textarea